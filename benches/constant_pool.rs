@@ -0,0 +1,43 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use Fejvm::c_pool::{ConstantPool, ConstantPoolEntry};
+
+// A pool the size of a large generated class: names and the classes and strings that refer to
+// them, with every name asked for twice
+const ENTRIES: u16 = 10_000;
+
+fn entries() -> Vec<ConstantPoolEntry> {
+    (0..ENTRIES / 2)
+        .flat_map(|n| {
+            let name = 2 * (n % (ENTRIES / 4)) + 1;
+            [
+                ConstantPoolEntry::Utf8(format!("com/example/Name{}", n % (ENTRIES / 4))),
+                ConstantPoolEntry::ClassReference(name),
+            ]
+        })
+        .collect()
+}
+
+fn build_pool(c: &mut Criterion) {
+    let entries = entries();
+    c.bench_function("add 10k entries", |b| {
+        b.iter(|| {
+            let mut pool = ConstantPool::new();
+            for entry in black_box(&entries) {
+                pool.add(entry.clone()).unwrap();
+            }
+            pool
+        })
+    });
+    c.bench_function("add_or_get 10k entries", |b| {
+        b.iter(|| {
+            let mut pool = ConstantPool::new();
+            for entry in black_box(&entries) {
+                pool.add_or_get(entry.clone()).unwrap();
+            }
+            pool
+        })
+    });
+}
+
+criterion_group!(benches, build_pool);
+criterion_main!(benches);
@@ -0,0 +1,30 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use Fejvm::class_reader;
+
+// Embedded so that reading the files is not measured
+const CLASSES: [(&str, &[u8]); 2] = [
+    ("hi", include_bytes!("../tests/resources/Fejvm/hi.class")),
+    (
+        "Lambdas",
+        include_bytes!("../tests/resources/Fejvm/Lambdas.class"),
+    ),
+];
+
+fn full_parse(c: &mut Criterion) {
+    for (name, data) in CLASSES {
+        c.bench_function(&format!("read_buffer {}", name), |b| {
+            b.iter(|| class_reader::read_buffer(black_box(data)).unwrap())
+        });
+    }
+}
+
+fn name_only(c: &mut Criterion) {
+    for (name, data) in CLASSES {
+        c.bench_function(&format!("read_name_only {}", name), |b| {
+            b.iter(|| class_reader::read_name_only(black_box(data)).unwrap())
+        });
+    }
+}
+
+criterion_group!(benches, full_parse, name_only);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+// Reports the heap taken by constant pools, counted by the allocator. Long and double entries
+// are stored without a placeholder for their unusable second slot, which is compared here with
+// a vector that has one.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use Fejvm::c_pool::{ConstantPool, ConstantPoolEntry};
+use Fejvm::class_reader;
+
+struct Counting;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// Heap held by the value that the function builds
+fn heap_of<T>(build: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = build();
+    (value, ALLOCATED.load(Ordering::Relaxed) - before)
+}
+
+const CLASSES: [(&str, &[u8]); 2] = [
+    ("hi", include_bytes!("../tests/resources/Fejvm/hi.class")),
+    (
+        "Constants",
+        include_bytes!("../tests/resources/Fejvm/Constants.class"),
+    ),
+];
+
+fn main() {
+    for (name, data) in CLASSES {
+        let ((_, pool), bytes) = heap_of(|| class_reader::read_constants_only(data).unwrap());
+        println!("{}: {} slots in {} bytes", name, pool.slot_count(), bytes);
+    }
+
+    let count = 10_000;
+    let (pool, pool_bytes) = heap_of(|| {
+        let mut pool = ConstantPool::with_capacity(count);
+        for n in 0..count {
+            pool.add(ConstantPoolEntry::Long(n as i64)).unwrap();
+        }
+        pool
+    });
+    let (placeholders, placeholder_bytes) = heap_of(|| {
+        let mut entries = Vec::with_capacity(2 * count);
+        for n in 0..count {
+            entries.push(ConstantPoolEntry::Long(n as i64));
+            entries.push(ConstantPoolEntry::Integer(0));
+        }
+        entries
+    });
+    println!(
+        "{} longs: {} bytes, against {} with a placeholder entry per unusable slot",
+        pool.slot_count() / 2,
+        pool_bytes,
+        placeholder_bytes
+    );
+    drop(placeholders);
+}
@@ -0,0 +1,455 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::buffer::BufferReader;
+use crate::c_pool::{ConstantPool, ConstantPoolEntry, ConstantPoolError};
+use crate::class_reader_error::{ClassReaderError, Result};
+
+// Annotations nested in each other deeper than this are rejected, rather than risking
+// running out of stack while decoding them
+const MAX_NESTING: usize = 255;
+
+/// An annotation, with the values given to its elements.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7.16
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// Field descriptor of the annotation interface, like Ljava/lang/Deprecated;.
+    pub type_descriptor: String,
+    pub elements: Vec<(String, ElementValue)>,
+}
+
+/// The value of an annotation element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+    Byte(i8),
+    /// A UTF-16 code unit, which may be half of a surrogate pair.
+    Char(u16),
+    Double(f64),
+    Float(f32),
+    Int(i32),
+    Long(i64),
+    Short(i16),
+    Boolean(bool),
+    String(String),
+    /// An enum constant, by the descriptor of the enum and the name of the constant.
+    Enum {
+        type_descriptor: String,
+        const_name: String,
+    },
+    /// A class literal, by its return descriptor, like Ljava/lang/String; or V.
+    Class(String),
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
+}
+
+/// An annotation on a use of a type, with the location of the annotated type.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7.20
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAnnotation {
+    /// The kind of target, like 0x13 for the type of a field.
+    pub target_type: u8,
+    pub target: TypeAnnotationTarget,
+    /// Steps from the target type to the annotated part of it. Empty when the annotation
+    /// applies to the target type itself.
+    pub type_path: Vec<TypePathStep>,
+    pub annotation: Annotation,
+}
+
+/// A step into a part of a type, towards the one that a type annotation applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypePathStep {
+    /// The element type of an array type.
+    Array,
+    /// A type nested in the current one, like Inner in Outer.Inner.
+    Nested,
+    /// The bound of a wildcard type argument.
+    WildcardBound,
+    /// A type argument of a parameterized type, by its index.
+    TypeArgument(u8),
+}
+
+/// Which type of a declaration or expression a type annotation applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeAnnotationTarget {
+    /// A type parameter of a generic class or method.
+    TypeParameter {
+        index: u8,
+    },
+    /// The superclass, or an interface when the index is not 65535.
+    Supertype {
+        index: u16,
+    },
+    /// A bound of a type parameter.
+    TypeParameterBound {
+        type_parameter: u8,
+        bound: u8,
+    },
+    /// The type of a field, the return type or the receiver type of a method.
+    Empty,
+    FormalParameter {
+        index: u8,
+    },
+    /// A type in the throws clause, by its index in the Exceptions attribute.
+    Throws {
+        index: u16,
+    },
+    /// The type of a local variable, by the code ranges where it is live.
+    LocalVariable(Vec<LocalVariableRange>),
+    /// The type in a catch clause, by its index in the exception table.
+    Catch {
+        exception_table_index: u16,
+    },
+    /// A type in an instanceof, new, or method reference expression.
+    Offset {
+        offset: u16,
+    },
+    /// A type argument of a cast, constructor call or method call.
+    TypeArgument {
+        offset: u16,
+        index: u8,
+    },
+}
+
+/// A range of code where a local variable is live, and its local variable index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalVariableRange {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+/// The details given by a java.lang.Deprecated annotation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeprecationInfo {
+    /// The version in which the element was deprecated, if given.
+    pub since: Option<String>,
+    /// Whether the element is going to be removed in a future version.
+    pub for_removal: bool,
+}
+
+impl DeprecationInfo {
+    /// Finds the java.lang.Deprecated annotation among the given ones and reads its elements.
+    /// Elements left to their default value are not in the class file.
+    pub fn from_annotations<'a>(
+        annotations: impl IntoIterator<Item = &'a Annotation>,
+    ) -> Option<DeprecationInfo> {
+        let deprecated = annotations
+            .into_iter()
+            .find(|annotation| annotation.type_descriptor == "Ljava/lang/Deprecated;")?;
+        let mut info = DeprecationInfo::default();
+        for (name, value) in &deprecated.elements {
+            match (name.as_str(), value) {
+                ("since", ElementValue::String(since)) => info.since = Some(since.clone()),
+                ("forRemoval", ElementValue::Boolean(for_removal)) => {
+                    info.for_removal = *for_removal
+                }
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+}
+
+/// Decodes the body of a RuntimeVisibleAnnotations or RuntimeInvisibleAnnotations attribute.
+pub fn read_annotations(info: &[u8], constants: &ConstantPool) -> Result<Vec<Annotation>> {
+    let mut reader = AnnotationReader::new(info, constants);
+    let annotations = reader.read_annotations()?;
+    reader.check_end()?;
+    Ok(annotations)
+}
+
+/// Decodes the body of a RuntimeVisibleParameterAnnotations or
+/// RuntimeInvisibleParameterAnnotations attribute, with the annotations of each parameter.
+pub fn read_parameter_annotations(
+    info: &[u8],
+    constants: &ConstantPool,
+) -> Result<Vec<Vec<Annotation>>> {
+    let mut reader = AnnotationReader::new(info, constants);
+    let parameters_count = reader.buffer.read_u8()?;
+    let parameters = (0..parameters_count)
+        .map(|_| reader.read_annotations())
+        .collect::<Result<Vec<Vec<Annotation>>>>()?;
+    reader.check_end()?;
+    Ok(parameters)
+}
+
+/// Decodes the body of a RuntimeVisibleTypeAnnotations or RuntimeInvisibleTypeAnnotations
+/// attribute.
+pub fn read_type_annotations(info: &[u8], constants: &ConstantPool) -> Result<Vec<TypeAnnotation>> {
+    let mut reader = AnnotationReader::new(info, constants);
+    let count = reader.buffer.read_u16()?;
+    let annotations = (0..count)
+        .map(|_| reader.read_type_annotation())
+        .collect::<Result<Vec<TypeAnnotation>>>()?;
+    reader.check_end()?;
+    Ok(annotations)
+}
+
+struct AnnotationReader<'a> {
+    buffer: BufferReader<'a>,
+    constants: &'a ConstantPool,
+    depth: usize,
+}
+
+impl<'a> AnnotationReader<'a> {
+    fn new(info: &'a [u8], constants: &'a ConstantPool) -> AnnotationReader<'a> {
+        AnnotationReader {
+            buffer: BufferReader::new(info),
+            constants,
+            depth: 0,
+        }
+    }
+
+    fn check_end(&self) -> Result<()> {
+        if self.buffer.remaining() > 0 {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "annotations have {} unexpected trailing bytes",
+                self.buffer.remaining()
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_annotations(&mut self) -> Result<Vec<Annotation>> {
+        let count = self.buffer.read_u16()?;
+        (0..count).map(|_| self.read_annotation()).collect()
+    }
+
+    fn read_annotation(&mut self) -> Result<Annotation> {
+        let type_descriptor = self.read_utf8()?;
+        let count = self.buffer.read_u16()?;
+        let elements = (0..count)
+            .map(|_| Ok((self.read_utf8()?, self.read_element_value()?)))
+            .collect::<Result<Vec<(String, ElementValue)>>>()?;
+        Ok(Annotation {
+            type_descriptor,
+            elements,
+        })
+    }
+
+    fn read_element_value(&mut self) -> Result<ElementValue> {
+        let tag = self.buffer.read_u8()?;
+        let value = match tag {
+            b'B' => ElementValue::Byte(self.read_int()? as i8),
+            b'C' => ElementValue::Char(self.read_int()? as u16),
+            b'D' => match self.read_constant()? {
+                ConstantPoolEntry::Double(value) => ElementValue::Double(*value),
+                _ => return Err(self.unexpected_constant("double")),
+            },
+            b'F' => match self.read_constant()? {
+                ConstantPoolEntry::Float(value) => ElementValue::Float(*value),
+                _ => return Err(self.unexpected_constant("float")),
+            },
+            b'I' => ElementValue::Int(self.read_int()?),
+            b'J' => match self.read_constant()? {
+                ConstantPoolEntry::Long(value) => ElementValue::Long(*value),
+                _ => return Err(self.unexpected_constant("long")),
+            },
+            b'S' => ElementValue::Short(self.read_int()? as i16),
+            b'Z' => ElementValue::Boolean(self.read_int()? != 0),
+            b's' => ElementValue::String(self.read_utf8()?),
+            b'e' => ElementValue::Enum {
+                type_descriptor: self.read_utf8()?,
+                const_name: self.read_utf8()?,
+            },
+            b'c' => ElementValue::Class(self.read_utf8()?),
+            b'@' => {
+                self.enter()?;
+                let annotation = self.read_annotation()?;
+                self.depth -= 1;
+                ElementValue::Annotation(annotation)
+            }
+            b'[' => {
+                self.enter()?;
+                let count = self.buffer.read_u16()?;
+                let values = (0..count)
+                    .map(|_| self.read_element_value())
+                    .collect::<Result<Vec<ElementValue>>>()?;
+                self.depth -= 1;
+                ElementValue::Array(values)
+            }
+            _ => {
+                return Err(ClassReaderError::InvalidClassData(format!(
+                    "invalid annotation element tag: {}",
+                    tag
+                )))
+            }
+        };
+        Ok(value)
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "annotation values nested more than {} levels deep",
+                MAX_NESTING
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_type_annotation(&mut self) -> Result<TypeAnnotation> {
+        let target_type = self.buffer.read_u8()?;
+        let target = match target_type {
+            0x00 | 0x01 => TypeAnnotationTarget::TypeParameter {
+                index: self.buffer.read_u8()?,
+            },
+            0x10 => TypeAnnotationTarget::Supertype {
+                index: self.buffer.read_u16()?,
+            },
+            0x11 | 0x12 => TypeAnnotationTarget::TypeParameterBound {
+                type_parameter: self.buffer.read_u8()?,
+                bound: self.buffer.read_u8()?,
+            },
+            0x13..=0x15 => TypeAnnotationTarget::Empty,
+            0x16 => TypeAnnotationTarget::FormalParameter {
+                index: self.buffer.read_u8()?,
+            },
+            0x17 => TypeAnnotationTarget::Throws {
+                index: self.buffer.read_u16()?,
+            },
+            0x40 | 0x41 => {
+                let count = self.buffer.read_u16()?;
+                let ranges = (0..count)
+                    .map(|_| {
+                        Ok(LocalVariableRange {
+                            start_pc: self.buffer.read_u16()?,
+                            length: self.buffer.read_u16()?,
+                            index: self.buffer.read_u16()?,
+                        })
+                    })
+                    .collect::<Result<Vec<LocalVariableRange>>>()?;
+                TypeAnnotationTarget::LocalVariable(ranges)
+            }
+            0x42 => TypeAnnotationTarget::Catch {
+                exception_table_index: self.buffer.read_u16()?,
+            },
+            0x43..=0x46 => TypeAnnotationTarget::Offset {
+                offset: self.buffer.read_u16()?,
+            },
+            0x47..=0x4B => TypeAnnotationTarget::TypeArgument {
+                offset: self.buffer.read_u16()?,
+                index: self.buffer.read_u8()?,
+            },
+            _ => {
+                return Err(ClassReaderError::InvalidClassData(format!(
+                    "invalid type annotation target type: 0x{:X}",
+                    target_type
+                )))
+            }
+        };
+        let path_length = self.buffer.read_u8()?;
+        let type_path = (0..path_length)
+            .map(|_| self.read_type_path_step())
+            .collect::<Result<Vec<TypePathStep>>>()?;
+        let annotation = self.read_annotation()?;
+        Ok(TypeAnnotation {
+            target_type,
+            target,
+            type_path,
+            annotation,
+        })
+    }
+
+    fn read_type_path_step(&mut self) -> Result<TypePathStep> {
+        let kind = self.buffer.read_u8()?;
+        let argument_index = self.buffer.read_u8()?;
+        // The argument index is only meaningful for type argument steps, and 0 otherwise
+        match (kind, argument_index) {
+            (0, 0) => Ok(TypePathStep::Array),
+            (1, 0) => Ok(TypePathStep::Nested),
+            (2, 0) => Ok(TypePathStep::WildcardBound),
+            (3, index) => Ok(TypePathStep::TypeArgument(index)),
+            _ => Err(ClassReaderError::InvalidClassData(format!(
+                "invalid type path step: {}, {}",
+                kind, argument_index
+            ))),
+        }
+    }
+
+    fn read_constant(&mut self) -> Result<&'a ConstantPoolEntry> {
+        let index = self.buffer.read_u16()?;
+        Ok(self.constants.get(index)?)
+    }
+
+    fn read_int(&mut self) -> Result<i32> {
+        match self.read_constant()? {
+            ConstantPoolEntry::Integer(value) => Ok(*value),
+            _ => Err(self.unexpected_constant("integer")),
+        }
+    }
+
+    fn read_utf8(&mut self) -> Result<String> {
+        let index = self.buffer.read_u16()?;
+        match self.constants.get(index)? {
+            ConstantPoolEntry::Utf8(value) => Ok(value.clone()),
+            _ => Err(ConstantPoolError::UnexpectedEntry {
+                index,
+                expected: "utf8 entry",
+            }
+            .into()),
+        }
+    }
+
+    // The index of the constant was the last thing read
+    fn unexpected_constant(&self, expected: &str) -> ClassReaderError {
+        ClassReaderError::InvalidClassData(format!(
+            "annotation value at offset {} is not a constant of type {}",
+            self.buffer.position() - 2,
+            expected
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::annotation::{read_annotations, Annotation, ElementValue};
+    use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+
+    #[test]
+    fn nested_values_are_decoded() {
+        let mut constants = ConstantPool::new();
+        constants
+            .add(ConstantPoolEntry::Utf8("LFoo;".to_string()))
+            .unwrap();
+        constants
+            .add(ConstantPoolEntry::Utf8("value".to_string()))
+            .unwrap();
+        constants.add(ConstantPoolEntry::Integer(65)).unwrap();
+        let info = vec![
+            0x00, 0x01, // one annotation
+            0x00, 0x01, 0x00, 0x01, // @Foo with one element
+            0x00, 0x02, b'[', 0x00, 0x02, // value = an array of two
+            b'C', 0x00, 0x03, // 'A'
+            b'@', 0x00, 0x01, 0x00, 0x00, // @Foo()
+        ];
+        assert_eq!(
+            Ok(vec![Annotation {
+                type_descriptor: "LFoo;".to_string(),
+                elements: vec![(
+                    "value".to_string(),
+                    ElementValue::Array(vec![
+                        ElementValue::Char(b'A' as u16),
+                        ElementValue::Annotation(Annotation {
+                            type_descriptor: "LFoo;".to_string(),
+                            elements: vec![],
+                        }),
+                    ])
+                )],
+            }]),
+            read_annotations(&info, &constants)
+        );
+
+        // Arrays nested too deeply are rejected
+        let mut info = vec![0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x02];
+        for _ in 0..300 {
+            info.extend_from_slice(&[b'[', 0x00, 0x01]);
+        }
+        assert!(read_annotations(&info, &constants).is_err());
+    }
+}
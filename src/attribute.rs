@@ -1,5 +1,7 @@
-use std::fmt;
-use std::fmt::Formatter;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
 
 #[derive(Debug, Default, PartialEq)]
 pub struct Attribute {
@@ -0,0 +1,94 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::buffer::BufferReader;
+use crate::c_pool::ConstantPool;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::instruction::{resolve_constant, ResolvedOperand};
+
+/// An entry of the BootstrapMethods attribute, by constant pool indices.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7.23
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapMethod {
+    /// Index of the MethodHandle entry of the bootstrap method.
+    pub method_ref: u16,
+    /// Indices of the static arguments passed to the bootstrap method.
+    pub arguments: Vec<u16>,
+}
+
+/// The bootstrap method of a dynamically computed call site or constant, resolved along with
+/// its static arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedBootstrap {
+    /// The name and descriptor of the call site or constant being computed.
+    pub name: String,
+    pub descriptor: String,
+    /// The method handle of the bootstrap method.
+    pub method: ResolvedOperand,
+    pub arguments: Vec<ResolvedOperand>,
+}
+
+/// Decodes the body of a BootstrapMethods attribute.
+pub fn read_bootstrap_methods(info: &[u8]) -> Result<Vec<BootstrapMethod>> {
+    let mut buffer = BufferReader::new(info);
+    let count = buffer.read_u16()?;
+    let mut methods = Vec::new();
+    for _ in 0..count {
+        let method_ref = buffer.read_u16()?;
+        let argument_count = buffer.read_u16()?;
+        let arguments = (0..argument_count)
+            .map(|_| buffer.read_u16())
+            .collect::<Result<Vec<u16>>>()?;
+        methods.push(BootstrapMethod {
+            method_ref,
+            arguments,
+        });
+    }
+    if buffer.remaining() > 0 {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "{} unexpected bytes after the bootstrap methods",
+            buffer.remaining()
+        )));
+    }
+    Ok(methods)
+}
+
+impl BootstrapMethod {
+    // Resolves the method handle and the arguments, for the call site or constant with the given
+    // name and descriptor
+    pub(crate) fn resolve(
+        &self,
+        constants: &ConstantPool,
+        name: String,
+        descriptor: String,
+    ) -> Result<ResolvedBootstrap> {
+        let method = match resolve_constant(constants, self.method_ref)? {
+            Some(handle @ ResolvedOperand::MethodHandle { .. }) => handle,
+            _ => {
+                return Err(ClassReaderError::InvalidClassData(format!(
+                    "bootstrap method {} is not a method handle",
+                    self.method_ref
+                )))
+            }
+        };
+        let arguments = self
+            .arguments
+            .iter()
+            .map(|&index| {
+                resolve_constant(constants, index)?.ok_or_else(|| {
+                    ClassReaderError::InvalidClassData(format!(
+                        "bootstrap argument {} is not a loadable constant",
+                        index
+                    ))
+                })
+            })
+            .collect::<Result<Vec<ResolvedOperand>>>()?;
+        Ok(ResolvedBootstrap {
+            name,
+            descriptor,
+            method,
+            arguments,
+        })
+    }
+}
@@ -1,88 +1,173 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
 use crate::class_reader_error::{ClassReaderError, Result};
-use cesu8::from_java_cesu8;
 
 pub struct BufferReader<'a> {
     buffer: &'a [u8],
     position: usize,
+    // Offset of the data within a larger buffer, added to the positions that are reported
+    base_offset: usize,
 }
 
 impl<'a> BufferReader<'a> {
     pub fn new(data: &'a [u8]) -> Self {
+        BufferReader::new_at(data, 0)
+    }
+
+    // A reader of data found at the given offset of a larger buffer, whose positions are offsets
+    // into that buffer
+    pub fn new_at(data: &'a [u8], base_offset: usize) -> Self {
         BufferReader {
             buffer: data,
             position: 0,
+            base_offset,
         }
     }
 
     fn advance(&mut self, size: usize) -> Result<&'a [u8]> {
-        if self.position + size > self.buffer.len() {
-            Err(ClassReaderError::InvalidClassData(
+        match self.position.checked_add(size) {
+            Some(end) if end <= self.buffer.len() => {
+                let slice = &self.buffer[self.position..end];
+                self.position = end;
+                Ok(slice)
+            }
+            _ => Err(ClassReaderError::InvalidClassData(
                 "class does not have expected length".to_string(),
-            ))
-        } else {
-            let slice = &self.buffer[self.position..self.position + size];
-            self.position += size;
-            Ok(slice)
+            )),
         }
     }
 
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.advance(N).and_then(|bytes| {
+            bytes.try_into().map_err(|_| {
+                ClassReaderError::InvalidClassData(
+                    "class does not have expected length".to_string(),
+                )
+            })
+        })
+    }
+
     pub fn read_u8(&mut self) -> Result<u8> {
-        self.advance(std::mem::size_of::<u8>())
-            .map(|bytes| u8::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(u8::from_be_bytes)
+    }
+
+    pub fn read_i8(&mut self) -> Result<i8> {
+        self.read_array().map(i8::from_be_bytes)
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16> {
+        self.read_array().map(i16::from_be_bytes)
     }
 
     pub fn read_u16(&mut self) -> Result<u16> {
-        self.advance(std::mem::size_of::<u16>())
-            .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(u16::from_be_bytes)
     }
 
     pub fn read_u32(&mut self) -> Result<u32> {
-        self.advance(std::mem::size_of::<u32>())
-            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(u32::from_be_bytes)
     }
 
     pub fn read_i32(&mut self) -> Result<i32> {
-        self.advance(std::mem::size_of::<i32>())
-            .map(|bytes| i32::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(i32::from_be_bytes)
     }
 
     pub fn read_i64(&mut self) -> Result<i64> {
-        self.advance(std::mem::size_of::<i64>())
-            .map(|bytes| i64::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(i64::from_be_bytes)
     }
 
     pub fn read_f32(&mut self) -> Result<f32> {
-        self.advance(std::mem::size_of::<f32>())
-            .map(|bytes| f32::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(f32::from_be_bytes)
     }
 
     pub fn read_f64(&mut self) -> Result<f64> {
-        self.advance(std::mem::size_of::<f64>())
-            .map(|bytes| f64::from_be_bytes(bytes.try_into().unwrap()))
+        self.read_array().map(f64::from_be_bytes)
     }
 
     pub fn read_utf8(&mut self, len: usize) -> Result<String> {
-        self.advance(len)
-            .and_then(|bytes| {
-                from_java_cesu8(bytes).map_err(|_| {
-                    ClassReaderError::InvalidClassData("invalid utf8 data".to_string())
-                })
-            })
-            .map(|cow_string| cow_string.into_owned())
+        self.advance(len).and_then(|bytes| {
+            decode_modified_utf8(bytes)
+                .ok_or_else(|| ClassReaderError::InvalidClassData("invalid utf8 data".to_string()))
+        })
+    }
+
+    // Like read_utf8, but bytes that are not valid modified UTF-8 become replacement characters
+    pub fn read_utf8_lossy(&mut self, len: usize) -> Result<String> {
+        self.advance(len).map(|bytes| {
+            decode_modified_utf8(bytes)
+                .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned())
+        })
     }
 
     pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
         self.advance(len)
     }
 
+    // The offset of the next byte to read, counted from the start of the larger buffer for new_at
+    pub fn position(&self) -> usize {
+        self.base_offset + self.position
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.position
+    }
+
+    // Lengths are compared as u64, so that u32 lengths read from the class can never wrap
+    // around on 32-bit targets
+    pub fn can_read(&self, len: u64) -> bool {
+        len <= self.remaining() as u64
+    }
+
     #[allow(dead_code)]
     fn has_more_data(&self) -> bool {
         self.position < self.buffer.len()
     }
 }
 
+// Decodes the modified UTF-8 used by class files, where the null character takes two bytes and
+// supplementary characters are written as two three-byte surrogates.
+// https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-4.html#jvms-4.4.7
+fn decode_modified_utf8(bytes: &[u8]) -> Option<String> {
+    if let Ok(s) = core::str::from_utf8(bytes) {
+        return Some(s.to_string());
+    }
+    let continuation = |byte: Option<&u8>| match byte {
+        Some(byte) if byte & 0xC0 == 0x80 => Some((byte & 0x3F) as u16),
+        _ => None,
+    };
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&first) = iter.next() {
+        let unit = match first {
+            0x01..=0x7F => first as u16,
+            0xC0..=0xDF => {
+                let unit = ((first & 0x1F) as u16) << 6 | continuation(iter.next())?;
+                // Only the null character may use an overlong encoding
+                if unit != 0 && unit < 0x80 {
+                    return None;
+                }
+                unit
+            }
+            0xE0..=0xEF => {
+                let unit = ((first & 0x0F) as u16) << 12
+                    | continuation(iter.next())? << 6
+                    | continuation(iter.next())?;
+                if unit < 0x800 {
+                    return None;
+                }
+                unit
+            }
+            _ => return None,
+        };
+        units.push(unit);
+    }
+    String::from_utf16(&units).ok()
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
+
     use crate::buffer::BufferReader;
 
     #[test]
@@ -96,4 +181,32 @@ mod tests {
 
         assert!(buffer.read_u32().is_err());
     }
+    #[test]
+    fn huge_lengths_do_not_wrap() {
+        let data = vec![0x00, 0x01, 0x02, 0x03];
+        let mut buffer = BufferReader::new(&data);
+        buffer.read_u16().unwrap();
+
+        assert!(buffer.can_read(2));
+        assert!(!buffer.can_read(3));
+        assert!(!buffer.can_read(u32::MAX as u64));
+        assert!(!buffer.can_read(u32::MAX as u64 + 3));
+        assert!(buffer.read_bytes(usize::MAX).is_err());
+    }
+    #[test]
+    fn modified_utf8_is_decoded() {
+        let data = vec![
+            0x4D, // M
+            0xC0, 0x80, // null character
+            0xE6, 0x97, 0xA5, // 日
+            0xED, 0xA0, 0x81, 0xED, 0xB0, 0x81, // U+10401, as two surrogates
+        ];
+        let mut buffer = BufferReader::new(&data);
+        assert_eq!("M\0日\u{10401}", buffer.read_utf8(data.len()).unwrap());
+
+        let mut buffer = BufferReader::new(&[0xED, 0xA0, 0x81]);
+        assert!(buffer.read_utf8(3).is_err());
+        let mut buffer = BufferReader::new(&[0xC1, 0x81]);
+        assert!(buffer.read_utf8(2).is_err());
+    }
 }
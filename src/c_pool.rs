@@ -1,10 +1,20 @@
-use std::{fmt, vec::Vec};
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Write;
+use core::hash::{Hash, Hasher};
 use thiserror::Error;
 
+use crate::field_type::FieldType;
+
 // Types of constant
 // Constant Pool Entry defined here
 // https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-4.html#jvms-4.4
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum ConstantPoolEntry {
     Utf8(String),
     Integer(i32),
@@ -17,36 +27,185 @@ pub enum ConstantPoolEntry {
     MethodReference(u16, u16),
     InterfaceMethodReference(u16, u16),
     NameAndTypeDescriptor(u16, u16),
+    // Reference kind and index of the field or method reference
+    MethodHandle(u8, u16),
+    MethodType(u16),
+    // Index of the bootstrap method and of the name and type
+    Dynamic(u16, u16),
+    InvokeDynamic(u16, u16),
+    // Index of the name of a module or package, only found in module descriptors
+    Module(u16),
+    Package(u16),
 }
 
-// Constant Pool Physics Entry is Defined here
-#[derive(Debug)]
-enum ConstantPoolPhyEntry {
-    Entry(ConstantPoolEntry),
-    MultiByteEntryTombstone(),
+// Floats and doubles are compared and hashed by their bits, so that NaN equals itself and 0.0
+// differs from -0.0, which makes entries usable as map keys.
+impl PartialEq for ConstantPoolEntry {
+    fn eq(&self, other: &Self) -> bool {
+        use ConstantPoolEntry::*;
+        match (self, other) {
+            (Utf8(a), Utf8(b)) => a == b,
+            (Integer(a), Integer(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Long(a), Long(b)) => a == b,
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (ClassReference(a), ClassReference(b))
+            | (StringReference(a), StringReference(b))
+            | (MethodType(a), MethodType(b))
+            | (Module(a), Module(b))
+            | (Package(a), Package(b)) => a == b,
+            (FieldReference(a, b), FieldReference(c, d))
+            | (MethodReference(a, b), MethodReference(c, d))
+            | (InterfaceMethodReference(a, b), InterfaceMethodReference(c, d))
+            | (NameAndTypeDescriptor(a, b), NameAndTypeDescriptor(c, d))
+            | (Dynamic(a, b), Dynamic(c, d))
+            | (InvokeDynamic(a, b), InvokeDynamic(c, d)) => a == c && b == d,
+            (MethodHandle(a, b), MethodHandle(c, d)) => a == c && b == d,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ConstantPoolEntry {}
+
+impl Hash for ConstantPoolEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            ConstantPoolEntry::Utf8(s) => s.hash(state),
+            ConstantPoolEntry::Integer(n) => n.hash(state),
+            ConstantPoolEntry::Float(n) => n.to_bits().hash(state),
+            ConstantPoolEntry::Long(n) => n.hash(state),
+            ConstantPoolEntry::Double(n) => n.to_bits().hash(state),
+            ConstantPoolEntry::ClassReference(n)
+            | ConstantPoolEntry::StringReference(n)
+            | ConstantPoolEntry::MethodType(n)
+            | ConstantPoolEntry::Module(n)
+            | ConstantPoolEntry::Package(n) => n.hash(state),
+            ConstantPoolEntry::FieldReference(i, j)
+            | ConstantPoolEntry::MethodReference(i, j)
+            | ConstantPoolEntry::InterfaceMethodReference(i, j)
+            | ConstantPoolEntry::NameAndTypeDescriptor(i, j)
+            | ConstantPoolEntry::Dynamic(i, j)
+            | ConstantPoolEntry::InvokeDynamic(i, j) => (i, j).hash(state),
+            ConstantPoolEntry::MethodHandle(kind, n) => (kind, n).hash(state),
+        }
+    }
+}
+
+impl ConstantPoolEntry {
+    // Indices of the other entries that this one refers to: none for values and Utf8 entries,
+    // one for class, string and similar references, two for member references and name and
+    // types. The bootstrap method index of dynamic entries points into the BootstrapMethods
+    // attribute, not the pool, so it is not included.
+    pub fn referenced_indices(&self) -> Vec<u16> {
+        match *self {
+            ConstantPoolEntry::ClassReference(n)
+            | ConstantPoolEntry::StringReference(n)
+            | ConstantPoolEntry::MethodType(n)
+            | ConstantPoolEntry::Module(n)
+            | ConstantPoolEntry::Package(n)
+            | ConstantPoolEntry::MethodHandle(_, n)
+            | ConstantPoolEntry::Dynamic(_, n)
+            | ConstantPoolEntry::InvokeDynamic(_, n) => vec![n],
+            ConstantPoolEntry::FieldReference(i, j)
+            | ConstantPoolEntry::MethodReference(i, j)
+            | ConstantPoolEntry::InterfaceMethodReference(i, j)
+            | ConstantPoolEntry::NameAndTypeDescriptor(i, j) => vec![i, j],
+            _ => vec![],
+        }
+    }
 }
 
 // Implementation of the constant pool of a java class.
 // Note that constants are 1-based in java.
+// Long and double entries take two slots, the second of which is unusable. Rather than storing a
+// tombstone for it, the entries are kept contiguously and the indices of the unusable slots are
+// recorded in order, so that an index can be remapped to its entry.
 #[derive(Debug, Default)]
 pub struct ConstantPool {
-    entries: Vec<ConstantPoolPhyEntry>,
+    entries: Vec<ConstantPoolEntry>,
+    tombstones: Vec<u16>,
+    // Index of each distinct entry, only built once add_or_get is first used. It is a BTreeMap,
+    // with lookups in O(log n), as HashMap is not available without std.
+    lookup: Option<BTreeMap<EntryKey, u16>>,
+    // Whether text_of shows missing entries as placeholders instead of failing
+    lenient: bool,
+    // Bytes of the Utf8 entries as they were read, only kept when the reader is asked to
+    raw_utf8: BTreeMap<u16, Vec<u8>>,
 }
 
-// Error used to signal that an attempt was made to access a non existing constant pool entry.
-#[derive(Error, Debug, PartialEq)]
-#[error("invalid constant pool index: {index}")]
-pub struct InvalidConstantPoolIndexError {
-    pub index: u16,
+// The content of an entry in a totally ordered form
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum EntryKey {
+    Utf8(String),
+    Integer(i32),
+    Float(u32),
+    Long(i64),
+    Double(u64),
+    Reference(u8, u16, u16),
 }
 
-// Implement methods for the error type
-impl InvalidConstantPoolIndexError {
-    fn new(index: u16) -> Self {
-        InvalidConstantPoolIndexError { index }
+impl From<&ConstantPoolEntry> for EntryKey {
+    fn from(entry: &ConstantPoolEntry) -> Self {
+        match *entry {
+            ConstantPoolEntry::Utf8(ref s) => EntryKey::Utf8(s.clone()),
+            ConstantPoolEntry::Integer(n) => EntryKey::Integer(n),
+            ConstantPoolEntry::Float(n) => EntryKey::Float(n.to_bits()),
+            ConstantPoolEntry::Long(n) => EntryKey::Long(n),
+            ConstantPoolEntry::Double(n) => EntryKey::Double(n.to_bits()),
+            // The other entries are only made of indices, told apart by their tag
+            ConstantPoolEntry::ClassReference(n) => EntryKey::Reference(7, n, 0),
+            ConstantPoolEntry::StringReference(n) => EntryKey::Reference(8, n, 0),
+            ConstantPoolEntry::FieldReference(i, j) => EntryKey::Reference(9, i, j),
+            ConstantPoolEntry::MethodReference(i, j) => EntryKey::Reference(10, i, j),
+            ConstantPoolEntry::InterfaceMethodReference(i, j) => EntryKey::Reference(11, i, j),
+            ConstantPoolEntry::NameAndTypeDescriptor(i, j) => EntryKey::Reference(12, i, j),
+            ConstantPoolEntry::MethodHandle(kind, n) => EntryKey::Reference(15, kind as u16, n),
+            ConstantPoolEntry::MethodType(n) => EntryKey::Reference(16, n, 0),
+            ConstantPoolEntry::Dynamic(i, j) => EntryKey::Reference(17, i, j),
+            ConstantPoolEntry::InvokeDynamic(i, j) => EntryKey::Reference(18, i, j),
+            ConstantPoolEntry::Module(n) => EntryKey::Reference(19, n, 0),
+            ConstantPoolEntry::Package(n) => EntryKey::Reference(20, n, 0),
+        }
     }
 }
 
+// Errors used to signal that an attempt was made to access a non existing constant pool entry.
+#[derive(Error, Debug, PartialEq)]
+pub enum ConstantPoolError {
+    // The index is zero or past the end of the pool
+    #[error("invalid constant pool index: {index}")]
+    InvalidIndex { index: u16 },
+
+    // The index is the unusable second slot of a Long or Double entry
+    #[error("constant pool index {index} is the second half of a long/double entry")]
+    TombstoneAccess { index: u16 },
+
+    // The entry is not of the kind that was asked for
+    #[error("constant pool entry {index} is not a {expected}")]
+    UnexpectedEntry { index: u16, expected: &'static str },
+
+    // Resolving the entry never reaches a value, i.e. the references form a cycle
+    #[error("constant pool entry {index} has cyclic references")]
+    CyclicReference { index: u16 },
+
+    // The entry refers to an index that holds no usable entry
+    #[error("constant pool entry {index} refers to missing entry {target}")]
+    DanglingReference { index: u16, target: u16 },
+
+    // There is no room left for the entry, as a pool has at most 65534 slots
+    #[error("the constant pool is full")]
+    PoolFull,
+}
+
+// Largest number of slots of a pool, as its count in the class file is a u16 that is one more
+const MAX_SLOTS: usize = 65534;
+
+// No valid entry is nested deeper than a member reference pointing at a class and a name and
+// type, so anything past this depth can only be a cycle in a malformed pool
+const MAX_REFERENCE_DEPTH: usize = 8;
+
 // Implement methods for the constant pool struct
 impl ConstantPool {
     // Constructor for creating a new constant pool
@@ -54,49 +213,438 @@ impl ConstantPool {
         Default::default()
     }
 
-    // Adds a new entry.
-    pub fn add(&mut self, entry: ConstantPoolEntry) {
+    // Creates a pool with room for the given number of entries
+    pub fn with_capacity(capacity: usize) -> ConstantPool {
+        ConstantPool {
+            entries: Vec::with_capacity(capacity),
+            tombstones: Vec::new(),
+            lookup: None,
+            lenient: false,
+            raw_utf8: BTreeMap::new(),
+        }
+    }
+
+    // In lenient mode, text_of renders references to missing entries as "<unresolved #N>"
+    // rather than returning an error. Cyclic references are still errors.
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    // Adds a new entry and returns its index. Fails if the pool has no room left for it, which
+    // a long or double in the very last slot does not have either.
+    pub fn add(&mut self, entry: ConstantPoolEntry) -> Result<u16, ConstantPoolError> {
         // Check if the entry type requires a tombstone (e.g., Long or Double)
         let add_tombstone = matches!(
             &entry,
             ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
         );
+        let slots = if add_tombstone { 2 } else { 1 };
+        if self.remaining_capacity() < slots {
+            return Err(ConstantPoolError::PoolFull);
+        }
+
+        // Keep the lookup index, if any, up to date. The first of equal entries is the one found.
+        let index = self.slot_count() as u16 + 1;
+        if let Some(lookup) = &mut self.lookup {
+            lookup.entry(EntryKey::from(&entry)).or_insert(index);
+        }
 
         // Push the entry to the constant pool
-        self.entries.push(ConstantPoolPhyEntry::Entry(entry));
+        self.entries.push(entry);
 
-        // If a tombstone is needed, add a tombstone entry
+        // If a tombstone is needed, record the index of the slot it would take
         if add_tombstone {
-            self.entries
-                .push(ConstantPoolPhyEntry::MultiByteEntryTombstone())
+            self.tombstones.push(index + 1);
+        }
+        Ok(index)
+    }
+
+    // Returns the index of an entry equal to the given one, adding it if there is none yet.
+    pub fn add_or_get(&mut self, entry: ConstantPoolEntry) -> Result<u16, ConstantPoolError> {
+        if self.lookup.is_none() {
+            let mut lookup = BTreeMap::new();
+            for (index, existing) in self.iter() {
+                lookup.entry(EntryKey::from(existing)).or_insert(index);
+            }
+            self.lookup = Some(lookup);
+        }
+        if let Some(index) = self
+            .lookup
+            .as_ref()
+            .and_then(|lookup| lookup.get(&EntryKey::from(&entry)))
+        {
+            return Ok(*index);
+        }
+        self.add(entry)
+    }
+
+    // Adds the entries of the other pool that this one does not have yet, with their references
+    // rewritten, and returns the new index of each index of the other pool. Index 0 and the
+    // unusable second slots of longs and doubles map to 0. Bootstrap method indices are kept as
+    // they are, since they point into the BootstrapMethods attribute rather than the pool.
+    pub fn merge(&mut self, other: &ConstantPool) -> Result<Vec<u16>, ConstantPoolError> {
+        let mut remap = vec![0; other.slot_count() + 1];
+        for (index, _) in other.iter() {
+            self.merge_entry(other, index, &mut remap, 0)?;
+        }
+        Ok(remap)
+    }
+
+    // Drops the entries that cannot be reached from the given indices, keeping the others in
+    // their order with their references rewritten, and returns the new index of each old index.
+    // Dropped entries, index 0 and the unusable second slots of longs and doubles map to 0.
+    // Since the order is kept, no index grows, so one byte ldc operands stay valid.
+    pub fn compact(
+        &mut self,
+        roots: impl IntoIterator<Item = u16>,
+    ) -> Result<Vec<u16>, ConstantPoolError> {
+        let mut reachable = vec![false; self.slot_count() + 1];
+        let mut pending: Vec<(u16, Option<u16>)> = roots
+            .into_iter()
+            .filter(|&index| index != 0)
+            .map(|index| (index, None))
+            .collect();
+        while let Some((index, from)) = pending.pop() {
+            let entry = match from {
+                Some(from) => self.referenced(from, index)?,
+                None => self.get(index)?,
+            };
+            if !reachable[index as usize] {
+                reachable[index as usize] = true;
+                pending.extend(
+                    entry
+                        .referenced_indices()
+                        .into_iter()
+                        .map(|target| (target, Some(index))),
+                );
+            }
+        }
+
+        let mut remap = vec![0; self.slot_count() + 1];
+        let mut next: u32 = 1;
+        for (index, entry) in self.iter() {
+            if reachable[index as usize] {
+                remap[index as usize] = next as u16;
+                next += match entry {
+                    ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_) => 2,
+                    _ => 1,
+                };
+            }
         }
+        let mut compacted = ConstantPool::with_capacity(self.entries.len());
+        compacted.lenient = self.lenient;
+        compacted.raw_utf8 = self
+            .raw_utf8
+            .iter()
+            .filter(|(&index, _)| reachable[index as usize])
+            .map(|(&index, bytes)| (remap[index as usize], bytes.clone()))
+            .collect();
+        for (index, entry) in self.iter() {
+            if reachable[index as usize] {
+                let targets: Vec<u16> = entry
+                    .referenced_indices()
+                    .into_iter()
+                    .map(|target| remap[target as usize])
+                    .collect();
+                compacted.add(with_references(entry, &targets))?;
+            }
+        }
+        *self = compacted;
+        Ok(remap)
+    }
+
+    // Merges an entry of the other pool after the entries it refers to, recording its new index
+    fn merge_entry(
+        &mut self,
+        other: &ConstantPool,
+        index: u16,
+        remap: &mut [u16],
+        depth: usize,
+    ) -> Result<u16, ConstantPoolError> {
+        if remap[index as usize] != 0 {
+            return Ok(remap[index as usize]);
+        }
+        if depth > MAX_REFERENCE_DEPTH {
+            return Err(ConstantPoolError::CyclicReference { index });
+        }
+        let entry = other.get(index)?;
+        let mut targets = Vec::new();
+        for target in entry.referenced_indices() {
+            other.referenced(index, target)?;
+            targets.push(self.merge_entry(other, target, remap, depth + 1)?);
+        }
+        let new_index = self.add_or_get(with_references(entry, &targets))?;
+        remap[index as usize] = new_index;
+        Ok(new_index)
+    }
+
+    // Number of indices used by the pool, including the unusable ones. The constant pool count
+    // of the class file is one more than this.
+    pub fn slot_count(&self) -> usize {
+        self.entries.len() + self.tombstones.len()
+    }
+
+    // Number of slots left before the pool reaches the largest size a class file can hold. Longs
+    // and doubles take two of them.
+    pub fn remaining_capacity(&self) -> usize {
+        MAX_SLOTS.saturating_sub(self.slot_count())
     }
 
     // Accesses an entry given its index. Note that it must be 1-based!
-    pub fn get(
-        &self,
-        input_index: u16,
-    ) -> Result<&ConstantPoolEntry, InvalidConstantPoolIndexError> {
+    pub fn get(&self, input_index: u16) -> Result<&ConstantPoolEntry, ConstantPoolError> {
         // Check if the index is valid
-        if input_index == 0 || input_index as usize > self.entries.len() {
-            Err(InvalidConstantPoolIndexError::new(input_index))
+        if input_index == 0 || input_index as usize > self.slot_count() {
+            Err(ConstantPoolError::InvalidIndex { index: input_index })
         } else {
-            // Adjust the index to be 0-based
-            let i = (input_index - 1) as usize;
-            let entry = &self.entries[i];
-
-            // Return the entry if it is a valid entry, otherwise return an error
-            match entry {
-                ConstantPoolPhyEntry::Entry(entry) => Ok(entry),
-                ConstantPoolPhyEntry::MultiByteEntryTombstone() => {
-                    Err(InvalidConstantPoolIndexError::new(input_index))
+            // Every tombstone before the index shifts its entry one position to the left
+            let skipped = self.tombstones.partition_point(|&t| t < input_index);
+            if self.tombstones.get(skipped) == Some(&input_index) {
+                Err(ConstantPoolError::TombstoneAccess { index: input_index })
+            } else {
+                // Adjust the index to be 0-based
+                Ok(&self.entries[input_index as usize - 1 - skipped])
+            }
+        }
+    }
+
+    // The bytes of the Utf8 entry at the index exactly as they were read, in modified UTF-8.
+    // They are only kept when the pool is read with ReaderOptions::keep_raw_utf8, and entries
+    // added or replaced since then have none.
+    pub fn raw_utf8_at(&self, idx: u16) -> Option<&[u8]> {
+        self.raw_utf8.get(&idx).map(Vec::as_slice)
+    }
+
+    // Records the bytes that the Utf8 entry at the index was read from
+    pub(crate) fn set_raw_utf8(&mut self, idx: u16, bytes: Vec<u8>) {
+        self.raw_utf8.insert(idx, bytes);
+    }
+
+    // Replaces the text of the Utf8 entry at the index. Entries of other kinds are left alone, so
+    // the layout of the pool never changes.
+    pub fn set_utf8(&mut self, idx: u16, value: String) -> Result<(), ConstantPoolError> {
+        self.get(idx)?;
+        let skipped = self.tombstones.partition_point(|&t| t < idx);
+        match &mut self.entries[idx as usize - 1 - skipped] {
+            ConstantPoolEntry::Utf8(text) => {
+                *text = value;
+                // The lookup index is rebuilt the next time it is needed
+                self.lookup = None;
+                self.raw_utf8.remove(&idx);
+                Ok(())
+            }
+            _ => Err(ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: "Utf8",
+            }),
+        }
+    }
+
+    // Replaces the entry at the index with another one of the same kind, so the layout of the
+    // pool never changes
+    pub(crate) fn replace(
+        &mut self,
+        idx: u16,
+        entry: ConstantPoolEntry,
+    ) -> Result<(), ConstantPoolError> {
+        let existing = self.get(idx)?;
+        if core::mem::discriminant(existing) != core::mem::discriminant(&entry) {
+            return Err(ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: entry_kind(&entry),
+            });
+        }
+        let skipped = self.tombstones.partition_point(|&t| t < idx);
+        self.entries[idx as usize - 1 - skipped] = entry;
+        // The lookup index is rebuilt the next time it is needed
+        self.lookup = None;
+        self.raw_utf8.remove(&idx);
+        Ok(())
+    }
+
+    // Iterates over the entries with their 1-based index, skipping the tombstones
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &ConstantPoolEntry)> {
+        self.entries.iter().scan(1u32, |next_idx, entry| {
+            let idx = *next_idx as u16;
+            *next_idx += match entry {
+                ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_) => 2,
+                _ => 1,
+            };
+            Some((idx, entry))
+        })
+    }
+
+    // Returns the entries, with their index, that match the given predicate
+    pub fn entries_of_kind<P>(&self, predicate: P) -> Vec<(u16, &ConstantPoolEntry)>
+    where
+        P: Fn(&ConstantPoolEntry) -> bool,
+    {
+        self.iter().filter(|(_, entry)| predicate(entry)).collect()
+    }
+
+    // Returns the internal name, like java/lang/String, of the class referenced at the index
+    pub fn class_name_at(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        match self.get(idx)? {
+            ConstantPoolEntry::ClassReference(n) => self.utf8_at(idx, *n).map(str::to_owned),
+            _ => Err(ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: "ClassReference",
+            }),
+        }
+    }
+
+    // Returns the name and the descriptor of a NameAndTypeDescriptor entry
+    pub fn name_and_type_at(&self, idx: u16) -> Result<(String, String), ConstantPoolError> {
+        match self.get(idx)? {
+            ConstantPoolEntry::NameAndTypeDescriptor(name, descriptor) => Ok((
+                self.utf8_at(idx, *name)?.to_owned(),
+                self.utf8_at(idx, *descriptor)?.to_owned(),
+            )),
+            _ => Err(ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: "NameAndTypeDescriptor",
+            }),
+        }
+    }
+
+    // Returns the internal name of the class owning the method named by a MethodReference or
+    // InterfaceMethodReference entry
+    pub fn method_ref_owner_at(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        let (class, _) = self.method_ref_at(idx)?;
+        self.referenced(idx, class)?;
+        self.class_name_at(class)
+    }
+
+    // Returns the name of the method named by a MethodReference or InterfaceMethodReference entry
+    pub fn method_ref_name_at(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        let (_, name_and_type) = self.method_ref_at(idx)?;
+        self.referenced(idx, name_and_type)?;
+        self.name_and_type_at(name_and_type).map(|(name, _)| name)
+    }
+
+    // Returns the descriptor, like (I)V, of the method named by a MethodReference or
+    // InterfaceMethodReference entry
+    pub fn method_ref_descriptor_at(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        let (_, name_and_type) = self.method_ref_at(idx)?;
+        self.referenced(idx, name_and_type)?;
+        self.name_and_type_at(name_and_type)
+            .map(|(_, descriptor)| descriptor)
+    }
+
+    // The class and name and type indices of a MethodReference or InterfaceMethodReference entry
+    fn method_ref_at(&self, idx: u16) -> Result<(u16, u16), ConstantPoolError> {
+        match self.get(idx)? {
+            ConstantPoolEntry::MethodReference(class, name_and_type)
+            | ConstantPoolEntry::InterfaceMethodReference(class, name_and_type) => {
+                Ok((*class, *name_and_type))
+            }
+            _ => Err(ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: "MethodReference",
+            }),
+        }
+    }
+
+    // Checks that every reference of every entry leads to an entry of the kind the JVM
+    // specification requires, like a Utf8 entry for the name of a class. The first problem
+    // found, in the order of the entries, is returned.
+    pub fn validate(&self) -> Result<(), ConstantPoolError> {
+        for (idx, entry) in self.iter() {
+            for (target, expected) in entry
+                .referenced_indices()
+                .into_iter()
+                .zip(expected_kinds(entry))
+            {
+                let kind = entry_kind(self.referenced(idx, target)?);
+                if !expected.split(" or ").any(|expected| expected == kind) {
+                    return Err(ConstantPoolError::UnexpectedEntry {
+                        index: target,
+                        expected,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Follows a reference from the entry at `from`, reporting missing targets as dangling
+    fn referenced(&self, from: u16, target: u16) -> Result<&ConstantPoolEntry, ConstantPoolError> {
+        self.get(target).map_err(|err| match err {
+            ConstantPoolError::InvalidIndex { .. } | ConstantPoolError::TombstoneAccess { .. } => {
+                ConstantPoolError::DanglingReference {
+                    index: from,
+                    target,
                 }
             }
+            other => other,
+        })
+    }
+
+    fn utf8_at(&self, from: u16, idx: u16) -> Result<&str, ConstantPoolError> {
+        match self.referenced(from, idx)? {
+            ConstantPoolEntry::Utf8(s) => Ok(s),
+            _ => Err(ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: "Utf8",
+            }),
         }
     }
 
+    // Whether the class referenced at the index is an array type, like [I, rather than a class
+    pub fn is_array_class_at(&self, idx: u16) -> Result<bool, ConstantPoolError> {
+        Ok(self.class_name_at(idx)?.starts_with('['))
+    }
+
+    // Returns the type named by the class reference at the index. Array types are given by
+    // their descriptor, and other classes by their internal name.
+    pub fn class_type_at(&self, idx: u16) -> Result<FieldType, ConstantPoolError> {
+        let name = self.class_name_at(idx)?;
+        if name.starts_with('[') {
+            FieldType::parse(&name).map_err(|_| ConstantPoolError::UnexpectedEntry {
+                index: idx,
+                expected: "array descriptor",
+            })
+        } else {
+            Ok(FieldType::Object(name))
+        }
+    }
+
+    // Returns the simple name, like String, of the class referenced at the index.
+    // Array classes are rendered as their element type followed by brackets, like String[].
+    pub fn simple_class_name_at(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        let name = self.class_name_at(idx)?;
+        let dimensions = name.chars().take_while(|c| *c == '[').count();
+        let element = &name[dimensions..];
+        let element = if dimensions == 0 {
+            element
+        } else {
+            match element {
+                "B" => "byte",
+                "C" => "char",
+                "D" => "double",
+                "F" => "float",
+                "I" => "int",
+                "J" => "long",
+                "S" => "short",
+                "Z" => "boolean",
+                _ => element
+                    .strip_prefix('L')
+                    .and_then(|e| e.strip_suffix(';'))
+                    .unwrap_or(element),
+            }
+        };
+        let simple_name = element.rsplit('/').next().unwrap_or(element);
+        Ok(format!("{}{}", simple_name, "[]".repeat(dimensions)))
+    }
+
     // Helper method for formatting an entry for display
-    fn fmt_entry(&self, idx: u16) -> Result<String, InvalidConstantPoolIndexError> {
+    fn fmt_entry(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        self.fmt_entry_nested(idx, 0)
+    }
+
+    fn fmt_entry_nested(&self, idx: u16, depth: usize) -> Result<String, ConstantPoolError> {
+        if depth > MAX_REFERENCE_DEPTH {
+            return Err(ConstantPoolError::CyclicReference { index: idx });
+        }
         let entry = self.get(idx)?;
         let text = match entry {
             // Format each type of constant pool entry
@@ -106,18 +654,26 @@ impl ConstantPool {
             ConstantPoolEntry::Long(n) => format!("Long: {}", n),
             ConstantPoolEntry::Double(n) => format!("Double: {}", n),
             ConstantPoolEntry::ClassReference(n) => {
-                format!("ClassReference: {} => ({})", n, self.fmt_entry(*n)?)
+                format!(
+                    "ClassReference: {} => ({})",
+                    n,
+                    self.fmt_reference(idx, *n, depth + 1)?
+                )
             }
             ConstantPoolEntry::StringReference(n) => {
-                format!("StringReference: {} => ({})", n, self.fmt_entry(*n)?)
+                format!(
+                    "StringReference: {} => ({})",
+                    n,
+                    self.fmt_reference(idx, *n, depth + 1)?
+                )
             }
             ConstantPoolEntry::FieldReference(i, j) => {
                 format!(
                     "FieldReference: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(*i)?,
-                    self.fmt_entry(*j)?
+                    self.fmt_reference(idx, *i, depth + 1)?,
+                    self.fmt_reference(idx, *j, depth + 1)?
                 )
             }
             ConstantPoolEntry::MethodReference(i, j) => {
@@ -125,8 +681,8 @@ impl ConstantPool {
                     "MethodReference: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(*i)?,
-                    self.fmt_entry(*j)?
+                    self.fmt_reference(idx, *i, depth + 1)?,
+                    self.fmt_reference(idx, *j, depth + 1)?
                 )
             }
             ConstantPoolEntry::InterfaceMethodReference(i, j) => {
@@ -134,8 +690,8 @@ impl ConstantPool {
                     "InterfaceMethodReference: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(*i)?,
-                    self.fmt_entry(*j)?
+                    self.fmt_reference(idx, *i, depth + 1)?,
+                    self.fmt_reference(idx, *j, depth + 1)?
                 )
             }
             &ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
@@ -143,40 +699,389 @@ impl ConstantPool {
                     "NameAndTypeDescriptor: {}, {} => ({}), ({})",
                     i,
                     j,
-                    self.fmt_entry(i)?,
-                    self.fmt_entry(j)?
+                    self.fmt_reference(idx, i, depth + 1)?,
+                    self.fmt_reference(idx, j, depth + 1)?
+                )
+            }
+            &ConstantPoolEntry::MethodHandle(kind, n) => {
+                format!(
+                    "MethodHandle: {}, {} => ({})",
+                    kind,
+                    n,
+                    self.fmt_reference(idx, n, depth + 1)?
+                )
+            }
+            &ConstantPoolEntry::MethodType(n) => {
+                format!(
+                    "MethodType: {} => ({})",
+                    n,
+                    self.fmt_reference(idx, n, depth + 1)?
+                )
+            }
+            &ConstantPoolEntry::Module(n) => {
+                format!(
+                    "Module: {} => ({})",
+                    n,
+                    self.fmt_reference(idx, n, depth + 1)?
+                )
+            }
+            &ConstantPoolEntry::Package(n) => {
+                format!(
+                    "Package: {} => ({})",
+                    n,
+                    self.fmt_reference(idx, n, depth + 1)?
+                )
+            }
+            &ConstantPoolEntry::Dynamic(bootstrap, j) => {
+                format!(
+                    "Dynamic: {}, {} => ({})",
+                    bootstrap,
+                    j,
+                    self.fmt_reference(idx, j, depth + 1)?
+                )
+            }
+            &ConstantPoolEntry::InvokeDynamic(bootstrap, j) => {
+                format!(
+                    "InvokeDynamic: {}, {} => ({})",
+                    bootstrap,
+                    j,
+                    self.fmt_reference(idx, j, depth + 1)?
                 )
             }
         };
         Ok(text)
     }
 
+    fn fmt_reference(
+        &self,
+        from: u16,
+        idx: u16,
+        depth: usize,
+    ) -> Result<String, ConstantPoolError> {
+        self.referenced(from, idx)?;
+        self.fmt_entry_nested(idx, depth)
+    }
+
     // Method for getting the textual representation of an entry
-    pub fn text_of(&self, idx: u16) -> Result<String, InvalidConstantPoolIndexError> {
+    pub fn text_of(&self, idx: u16) -> Result<String, ConstantPoolError> {
+        Ok(self.text_of_within(idx, usize::MAX)?.text)
+    }
+
+    // Same as text_of, but keeps only the first max_len characters of the text, followed by
+    // "..." when some were cut, for display code that must not be flooded by a huge entry. The
+    // text is not rendered past the limit, so references beyond it are not followed.
+    pub fn text_of_bounded(&self, idx: u16, max_len: usize) -> Result<String, ConstantPoolError> {
+        let mut text = self.text_of_within(idx, max_len)?;
+        if text.is_cut {
+            text.text.push_str("...");
+        }
+        Ok(text.text)
+    }
+
+    fn text_of_within(&self, idx: u16, max_len: usize) -> Result<BoundedText, ConstantPoolError> {
+        let mut text = BoundedText {
+            text: String::new(),
+            remaining: max_len,
+            is_cut: false,
+        };
+        match self.text_of_nested(idx, 0, &mut text) {
+            Err(
+                ConstantPoolError::InvalidIndex { .. } | ConstantPoolError::TombstoneAccess { .. },
+            ) if self.lenient => text.push(&unresolved(idx)),
+            result => result?,
+        }
+        Ok(text)
+    }
+
+    fn text_of_nested(
+        &self,
+        idx: u16,
+        depth: usize,
+        out: &mut BoundedText,
+    ) -> Result<(), ConstantPoolError> {
+        if depth > MAX_REFERENCE_DEPTH {
+            return Err(ConstantPoolError::CyclicReference { index: idx });
+        }
         let entry = self.get(idx)?;
-        let text = match entry {
+        if out.is_cut {
+            return Ok(());
+        }
+        match entry {
             // Extract text from each type of constant pool entry
-            ConstantPoolEntry::Utf8(ref s) => s.clone(),
-            ConstantPoolEntry::Integer(n) => n.to_string(),
-            ConstantPoolEntry::Float(n) => n.to_string(),
-            ConstantPoolEntry::Long(n) => n.to_string(),
-            ConstantPoolEntry::Double(n) => n.to_string(),
-            ConstantPoolEntry::ClassReference(n) => self.text_of(*n)?,
-            ConstantPoolEntry::StringReference(n) => self.text_of(*n)?,
-            ConstantPoolEntry::FieldReference(i, j) => {
-                format!("{}.{}", self.text_of(*i)?, self.text_of(*j)?)
+            ConstantPoolEntry::Utf8(ref s) => out.push(s),
+            ConstantPoolEntry::Integer(n) => out.push(&n.to_string()),
+            ConstantPoolEntry::Float(n) => out.push(&n.to_string()),
+            ConstantPoolEntry::Long(n) => out.push(&n.to_string()),
+            ConstantPoolEntry::Double(n) => out.push(&n.to_string()),
+            ConstantPoolEntry::ClassReference(n)
+            | ConstantPoolEntry::StringReference(n)
+            | ConstantPoolEntry::MethodHandle(_, n)
+            | ConstantPoolEntry::MethodType(n)
+            | ConstantPoolEntry::Module(n)
+            | ConstantPoolEntry::Package(n) => self.text_of_reference(idx, *n, depth + 1, out)?,
+            ConstantPoolEntry::FieldReference(i, j)
+            | ConstantPoolEntry::MethodReference(i, j)
+            | ConstantPoolEntry::InterfaceMethodReference(i, j) => {
+                self.text_of_reference(idx, *i, depth + 1, out)?;
+                out.push(".");
+                self.text_of_reference(idx, *j, depth + 1, out)?;
             }
-            ConstantPoolEntry::MethodReference(i, j) => {
-                format!("{}.{}", self.text_of(*i)?, self.text_of(*j)?)
+            ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
+                self.text_of_reference(idx, *i, depth + 1, out)?;
+                out.push(": ");
+                self.text_of_reference(idx, *j, depth + 1, out)?;
             }
-            ConstantPoolEntry::InterfaceMethodReference(i, j) => {
-                format!("{}.{}", self.text_of(*i)?, self.text_of(*j)?)
+            // Bootstrap methods live in a class attribute, so only their index can be shown
+            ConstantPoolEntry::Dynamic(bootstrap, j)
+            | ConstantPoolEntry::InvokeDynamic(bootstrap, j) => {
+                out.push(&format!("#{}:", bootstrap));
+                self.text_of_reference(idx, *j, depth + 1, out)?;
             }
-            ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
-                format!("{}: {}", self.text_of(*i)?, self.text_of(*j)?)
+        }
+        Ok(())
+    }
+
+    fn text_of_reference(
+        &self,
+        from: u16,
+        idx: u16,
+        depth: usize,
+        out: &mut BoundedText,
+    ) -> Result<(), ConstantPoolError> {
+        if out.is_cut {
+            return Ok(());
+        }
+        match self.referenced(from, idx) {
+            Err(ConstantPoolError::DanglingReference { .. }) if self.lenient => {
+                out.push(&unresolved(idx));
+                Ok(())
             }
-        };
-        Ok(text)
+            Err(err) => Err(err),
+            Ok(_) => self.text_of_nested(idx, depth, out),
+        }
+    }
+
+    // Renders the pool as a JSON array with an object per entry, holding its index, its tag as in
+    // the class file, the name of its kind and the fields of the entry under their names in the
+    // JVM specification. Tombstones are left out. Floats and doubles that are not finite have no
+    // JSON number, and are given as null.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        let entries = self
+            .iter()
+            .map(|(idx, entry)| {
+                let (tag, fields) = match *entry {
+                    ConstantPoolEntry::Utf8(ref s) => (1, json!({ "value": s })),
+                    ConstantPoolEntry::Integer(n) => (3, json!({ "value": n })),
+                    ConstantPoolEntry::Float(n) => (4, json!({ "value": n })),
+                    ConstantPoolEntry::Long(n) => (5, json!({ "value": n })),
+                    ConstantPoolEntry::Double(n) => (6, json!({ "value": n })),
+                    ConstantPoolEntry::ClassReference(n) => (7, json!({ "name_index": n })),
+                    ConstantPoolEntry::StringReference(n) => (8, json!({ "string_index": n })),
+                    ConstantPoolEntry::FieldReference(class, nat) => (
+                        9,
+                        json!({ "class_index": class, "name_and_type_index": nat }),
+                    ),
+                    ConstantPoolEntry::MethodReference(class, nat) => (
+                        10,
+                        json!({ "class_index": class, "name_and_type_index": nat }),
+                    ),
+                    ConstantPoolEntry::InterfaceMethodReference(class, nat) => (
+                        11,
+                        json!({ "class_index": class, "name_and_type_index": nat }),
+                    ),
+                    ConstantPoolEntry::NameAndTypeDescriptor(name, descriptor) => (
+                        12,
+                        json!({ "name_index": name, "descriptor_index": descriptor }),
+                    ),
+                    ConstantPoolEntry::MethodHandle(kind, reference) => (
+                        15,
+                        json!({ "reference_kind": kind, "reference_index": reference }),
+                    ),
+                    ConstantPoolEntry::MethodType(n) => (16, json!({ "descriptor_index": n })),
+                    ConstantPoolEntry::Dynamic(bootstrap, nat) => (
+                        17,
+                        json!({ "bootstrap_method_attr_index": bootstrap, "name_and_type_index": nat }),
+                    ),
+                    ConstantPoolEntry::InvokeDynamic(bootstrap, nat) => (
+                        18,
+                        json!({ "bootstrap_method_attr_index": bootstrap, "name_and_type_index": nat }),
+                    ),
+                    ConstantPoolEntry::Module(n) => (19, json!({ "name_index": n })),
+                    ConstantPoolEntry::Package(n) => (20, json!({ "name_index": n })),
+                };
+                let mut object = json!({ "index": idx, "tag": tag, "kind": entry_kind(entry) });
+                if let (Some(object), serde_json::Value::Object(fields)) =
+                    (object.as_object_mut(), fields)
+                {
+                    object.extend(fields);
+                }
+                object
+            })
+            .collect();
+        serde_json::Value::Array(entries)
+    }
+
+    // Renders the pool as a Graphviz DOT graph, with a node per entry and an edge per reference.
+    // Tombstones are left out, and so are references to them or past the end of the pool.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph constant_pool {\n    node [shape=box];\n");
+        for (idx, entry) in self.iter() {
+            let label = match entry {
+                ConstantPoolEntry::Utf8(s) => format!("{}: Utf8 \"{}\"", idx, s),
+                ConstantPoolEntry::Integer(n) => format!("{}: Integer {}", idx, n),
+                ConstantPoolEntry::Float(n) => format!("{}: Float {}", idx, n),
+                ConstantPoolEntry::Long(n) => format!("{}: Long {}", idx, n),
+                ConstantPoolEntry::Double(n) => format!("{}: Double {}", idx, n),
+                ConstantPoolEntry::MethodHandle(kind, _) => {
+                    format!("{}: MethodHandle {}", idx, kind)
+                }
+                ConstantPoolEntry::Dynamic(bootstrap, _)
+                | ConstantPoolEntry::InvokeDynamic(bootstrap, _) => {
+                    format!("{}: {} #{}", idx, entry_kind(entry), bootstrap)
+                }
+                _ => format!("{}: {}", idx, entry_kind(entry)),
+            };
+            let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = writeln!(dot, "    n{} [label=\"{}\"];", idx, label);
+        }
+        for (idx, entry) in self.iter() {
+            for target in entry.referenced_indices() {
+                if self.get(target).is_ok() {
+                    let _ = writeln!(dot, "    n{} -> n{};", idx, target);
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// Two pools are equal when they hold the same logical entries at the same indices; the
+// tombstones are only an artifact of the storage.
+impl PartialEq for ConstantPool {
+    fn eq(&self, other: &ConstantPool) -> bool {
+        self.iter().count() == other.iter().count()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|((i, a), (j, b))| i == j && a == b)
+    }
+}
+
+// Text rendered up to a number of characters, past which nothing more is added
+struct BoundedText {
+    text: String,
+    remaining: usize,
+    is_cut: bool,
+}
+
+impl BoundedText {
+    fn push(&mut self, s: &str) {
+        if self.is_cut {
+            return;
+        }
+        match s.char_indices().nth(self.remaining) {
+            Some((end, _)) => {
+                self.text.push_str(&s[..end]);
+                self.remaining = 0;
+                self.is_cut = true;
+            }
+            None => {
+                self.text.push_str(s);
+                self.remaining -= s.chars().count();
+            }
+        }
+    }
+}
+
+fn unresolved(idx: u16) -> String {
+    format!("<unresolved #{}>", idx)
+}
+
+fn entry_kind(entry: &ConstantPoolEntry) -> &'static str {
+    match entry {
+        ConstantPoolEntry::Utf8(_) => "Utf8",
+        ConstantPoolEntry::Integer(_) => "Integer",
+        ConstantPoolEntry::Float(_) => "Float",
+        ConstantPoolEntry::Long(_) => "Long",
+        ConstantPoolEntry::Double(_) => "Double",
+        ConstantPoolEntry::ClassReference(_) => "ClassReference",
+        ConstantPoolEntry::StringReference(_) => "StringReference",
+        ConstantPoolEntry::FieldReference(_, _) => "FieldReference",
+        ConstantPoolEntry::MethodReference(_, _) => "MethodReference",
+        ConstantPoolEntry::InterfaceMethodReference(_, _) => "InterfaceMethodReference",
+        ConstantPoolEntry::NameAndTypeDescriptor(_, _) => "NameAndTypeDescriptor",
+        ConstantPoolEntry::MethodHandle(_, _) => "MethodHandle",
+        ConstantPoolEntry::MethodType(_) => "MethodType",
+        ConstantPoolEntry::Dynamic(_, _) => "Dynamic",
+        ConstantPoolEntry::InvokeDynamic(_, _) => "InvokeDynamic",
+        ConstantPoolEntry::Module(_) => "Module",
+        ConstantPoolEntry::Package(_) => "Package",
+    }
+}
+
+// The kinds of entry that each reference of the entry may lead to, in the order given by
+// referenced_indices, with alternatives separated by " or "
+fn expected_kinds(entry: &ConstantPoolEntry) -> Vec<&'static str> {
+    match *entry {
+        ConstantPoolEntry::ClassReference(_)
+        | ConstantPoolEntry::StringReference(_)
+        | ConstantPoolEntry::MethodType(_)
+        | ConstantPoolEntry::Module(_)
+        | ConstantPoolEntry::Package(_) => vec!["Utf8"],
+        ConstantPoolEntry::FieldReference(_, _)
+        | ConstantPoolEntry::MethodReference(_, _)
+        | ConstantPoolEntry::InterfaceMethodReference(_, _) => {
+            vec!["ClassReference", "NameAndTypeDescriptor"]
+        }
+        ConstantPoolEntry::NameAndTypeDescriptor(_, _) => vec!["Utf8", "Utf8"],
+        ConstantPoolEntry::Dynamic(_, _) | ConstantPoolEntry::InvokeDynamic(_, _) => {
+            vec!["NameAndTypeDescriptor"]
+        }
+        // https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.4.8
+        ConstantPoolEntry::MethodHandle(kind, _) => vec![match kind {
+            1..=4 => "FieldReference",
+            5 | 8 => "MethodReference",
+            9 => "InterfaceMethodReference",
+            _ => "MethodReference or InterfaceMethodReference",
+        }],
+        _ => vec![],
+    }
+}
+
+// A copy of the entry that refers to the given indices, in the order given by referenced_indices
+fn with_references(entry: &ConstantPoolEntry, targets: &[u16]) -> ConstantPoolEntry {
+    match *entry {
+        ConstantPoolEntry::ClassReference(_) => ConstantPoolEntry::ClassReference(targets[0]),
+        ConstantPoolEntry::StringReference(_) => ConstantPoolEntry::StringReference(targets[0]),
+        ConstantPoolEntry::MethodType(_) => ConstantPoolEntry::MethodType(targets[0]),
+        ConstantPoolEntry::Module(_) => ConstantPoolEntry::Module(targets[0]),
+        ConstantPoolEntry::Package(_) => ConstantPoolEntry::Package(targets[0]),
+        ConstantPoolEntry::MethodHandle(kind, _) => {
+            ConstantPoolEntry::MethodHandle(kind, targets[0])
+        }
+        ConstantPoolEntry::Dynamic(bootstrap, _) => {
+            ConstantPoolEntry::Dynamic(bootstrap, targets[0])
+        }
+        ConstantPoolEntry::InvokeDynamic(bootstrap, _) => {
+            ConstantPoolEntry::InvokeDynamic(bootstrap, targets[0])
+        }
+        ConstantPoolEntry::FieldReference(_, _) => {
+            ConstantPoolEntry::FieldReference(targets[0], targets[1])
+        }
+        ConstantPoolEntry::MethodReference(_, _) => {
+            ConstantPoolEntry::MethodReference(targets[0], targets[1])
+        }
+        ConstantPoolEntry::InterfaceMethodReference(_, _) => {
+            ConstantPoolEntry::InterfaceMethodReference(targets[0], targets[1])
+        }
+        ConstantPoolEntry::NameAndTypeDescriptor(_, _) => {
+            ConstantPoolEntry::NameAndTypeDescriptor(targets[0], targets[1])
+        }
+        ref value => value.clone(),
     }
 }
 
@@ -184,20 +1089,55 @@ impl ConstantPool {
 impl fmt::Display for ConstantPool {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Display the size of the constant pool
-        writeln!(f, "Constant pool: (size: {})", self.entries.len())?;
+        writeln!(f, "Constant pool: (size: {})", self.slot_count())?;
 
-        // Display each entry in the constant pool
-        for (raw_idx, _) in self.entries.iter().enumerate() {
-            let index = (raw_idx + 1) as u16;
+        // Display each entry in the constant pool, skipping the tombstones of long/double entries
+        for (index, _) in self.iter() {
             writeln!(f, "    {}, {}", index, self.fmt_entry(index)?)?;
         }
         Ok(())
     }
 }
 
-// Implement conversion from InvalidConstantPoolIndexError to fmt::Error
-impl From<InvalidConstantPoolIndexError> for fmt::Error {
-    fn from(_: InvalidConstantPoolIndexError) -> fmt::Error {
+// Shows the raw contents of an entry, with references as unresolved "#index"
+impl fmt::Display for ConstantPoolEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstantPoolEntry::Utf8(s) => write!(f, "Utf8({:?})", s),
+            ConstantPoolEntry::Integer(n) => write!(f, "Integer({})", n),
+            ConstantPoolEntry::Float(n) => write!(f, "Float({})", n),
+            ConstantPoolEntry::Long(n) => write!(f, "Long({})", n),
+            ConstantPoolEntry::Double(n) => write!(f, "Double({})", n),
+            ConstantPoolEntry::ClassReference(n) => write!(f, "ClassReference(#{})", n),
+            ConstantPoolEntry::StringReference(n) => write!(f, "StringReference(#{})", n),
+            ConstantPoolEntry::FieldReference(i, j) => write!(f, "FieldReference(#{}, #{})", i, j),
+            ConstantPoolEntry::MethodReference(i, j) => {
+                write!(f, "MethodReference(#{}, #{})", i, j)
+            }
+            ConstantPoolEntry::InterfaceMethodReference(i, j) => {
+                write!(f, "InterfaceMethodReference(#{}, #{})", i, j)
+            }
+            ConstantPoolEntry::NameAndTypeDescriptor(i, j) => {
+                write!(f, "NameAndTypeDescriptor(#{}, #{})", i, j)
+            }
+            ConstantPoolEntry::MethodHandle(kind, n) => write!(f, "MethodHandle({}, #{})", kind, n),
+            ConstantPoolEntry::MethodType(n) => write!(f, "MethodType(#{})", n),
+            // The bootstrap index points into the BootstrapMethods attribute, not the pool
+            ConstantPoolEntry::Dynamic(bootstrap, n) => {
+                write!(f, "Dynamic(bootstrap {}, #{})", bootstrap, n)
+            }
+            ConstantPoolEntry::InvokeDynamic(bootstrap, n) => {
+                write!(f, "InvokeDynamic(bootstrap {}, #{})", bootstrap, n)
+            }
+            ConstantPoolEntry::Module(n) => write!(f, "Module(#{})", n),
+            ConstantPoolEntry::Package(n) => write!(f, "Package(#{})", n),
+        }
+    }
+}
+
+// Implement conversion from ConstantPoolError to fmt::Error
+impl From<ConstantPoolError> for fmt::Error {
+    fn from(_: ConstantPoolError) -> fmt::Error {
         fmt::Error {}
     }
 }
@@ -205,7 +1145,14 @@ impl From<InvalidConstantPoolIndexError> for fmt::Error {
 // Module for unit tests
 #[cfg(test)]
 mod tests {
-    use crate::c_pool::{ConstantPool, ConstantPoolEntry, InvalidConstantPoolIndexError};
+    use alloc::boxed::Box;
+    use alloc::format;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::c_pool::{ConstantPool, ConstantPoolEntry, ConstantPoolError};
+    use crate::field_type::FieldType;
 
     // Test the constant pool
     #[test]
@@ -214,18 +1161,20 @@ mod tests {
         let mut cp = ConstantPool::new();
 
         // Add some entries
-        cp.add(ConstantPoolEntry::Utf8("hey".to_string()));
-        cp.add(ConstantPoolEntry::Integer(1));
-        cp.add(ConstantPoolEntry::Float(2.1));
-        cp.add(ConstantPoolEntry::Long(123));
-        cp.add(ConstantPoolEntry::Double(3.56));
-        cp.add(ConstantPoolEntry::ClassReference(1));
-        cp.add(ConstantPoolEntry::StringReference(1));
-        cp.add(ConstantPoolEntry::Utf8("joe".to_string()));
-        cp.add(ConstantPoolEntry::FieldReference(1, 10));
-        cp.add(ConstantPoolEntry::MethodReference(1, 10));
-        cp.add(ConstantPoolEntry::InterfaceMethodReference(1, 10));
-        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(1, 10));
+        cp.add(ConstantPoolEntry::Utf8("hey".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::Integer(1)).unwrap();
+        cp.add(ConstantPoolEntry::Float(2.1)).unwrap();
+        cp.add(ConstantPoolEntry::Long(123)).unwrap();
+        cp.add(ConstantPoolEntry::Double(3.56)).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::StringReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("joe".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::FieldReference(1, 10)).unwrap();
+        cp.add(ConstantPoolEntry::MethodReference(1, 10)).unwrap();
+        cp.add(ConstantPoolEntry::InterfaceMethodReference(1, 10))
+            .unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(1, 10))
+            .unwrap();
 
         // Perform assertions to check the constant pool
         assert_eq!(
@@ -235,9 +1184,15 @@ mod tests {
         assert_eq!(ConstantPoolEntry::Integer(1), *cp.get(2).unwrap());
         assert_eq!(ConstantPoolEntry::Float(2.1), *cp.get(3).unwrap());
         assert_eq!(ConstantPoolEntry::Long(123i64), *cp.get(4).unwrap());
-        assert_eq!(Err(InvalidConstantPoolIndexError::new(5)), cp.get(5));
+        assert_eq!(
+            Err(ConstantPoolError::TombstoneAccess { index: 5 }),
+            cp.get(5)
+        );
         assert_eq!(ConstantPoolEntry::Double(3.56), *cp.get(6).unwrap());
-        assert_eq!(Err(InvalidConstantPoolIndexError::new(7)), cp.get(7));
+        assert_eq!(
+            Err(ConstantPoolError::TombstoneAccess { index: 7 }),
+            cp.get(7)
+        );
         assert_eq!(ConstantPoolEntry::ClassReference(1), *cp.get(8).unwrap());
         assert_eq!(ConstantPoolEntry::StringReference(1), *cp.get(9).unwrap());
         assert_eq!(
@@ -266,9 +1221,15 @@ mod tests {
         assert_eq!("1", cp.text_of(2).unwrap());
         assert_eq!("2.1", cp.text_of(3).unwrap());
         assert_eq!("123", cp.text_of(4).unwrap());
-        assert_eq!(Err(InvalidConstantPoolIndexError::new(5)), cp.text_of(5));
+        assert_eq!(
+            Err(ConstantPoolError::TombstoneAccess { index: 5 }),
+            cp.text_of(5)
+        );
         assert_eq!("3.56", cp.text_of(6).unwrap());
-        assert_eq!(Err(InvalidConstantPoolIndexError::new(7)), cp.text_of(7));
+        assert_eq!(
+            Err(ConstantPoolError::TombstoneAccess { index: 7 }),
+            cp.text_of(7)
+        );
         assert_eq!("hey", cp.text_of(8).unwrap());
         assert_eq!("hey", cp.text_of(9).unwrap());
         assert_eq!("joe", cp.text_of(10).unwrap());
@@ -277,4 +1238,539 @@ mod tests {
         assert_eq!("hey.joe", cp.text_of(13).unwrap());
         assert_eq!("hey: joe", cp.text_of(14).unwrap());
     }
+    // Test that the second slot of a long is reported as a tombstone, not as out of range
+    #[test]
+    fn tombstone_access_is_distinct_from_invalid_index() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Long(42)).unwrap();
+
+        assert_eq!(ConstantPoolEntry::Long(42), *cp.get(1).unwrap());
+        assert_eq!(
+            Err(ConstantPoolError::TombstoneAccess { index: 2 }),
+            cp.get(2)
+        );
+        assert_eq!(Err(ConstantPoolError::InvalidIndex { index: 3 }), cp.get(3));
+        assert_eq!(Err(ConstantPoolError::InvalidIndex { index: 0 }), cp.get(0));
+    }
+    // Test that longs and doubles count twice toward the capacity of the pool
+    #[test]
+    fn slots_are_counted_toward_the_capacity() {
+        let mut cp = ConstantPool::new();
+        assert_eq!((0, 65534), (cp.slot_count(), cp.remaining_capacity()));
+        cp.add(ConstantPoolEntry::Integer(1)).unwrap();
+        cp.add(ConstantPoolEntry::Long(2)).unwrap();
+        assert_eq!((3, 65531), (cp.slot_count(), cp.remaining_capacity()));
+    }
+    // Test that entries past the capacity are refused, including a long in the last slot
+    #[test]
+    fn full_pool_refuses_entries() {
+        let mut cp = ConstantPool::new();
+        for n in 0..65533 {
+            cp.add(ConstantPoolEntry::Integer(n)).unwrap();
+        }
+        assert_eq!(
+            Err(ConstantPoolError::PoolFull),
+            cp.add(ConstantPoolEntry::Long(1))
+        );
+        assert_eq!(Ok(65534), cp.add(ConstantPoolEntry::Integer(1)));
+        assert_eq!(
+            Err(ConstantPoolError::PoolFull),
+            cp.add_or_get(ConstantPoolEntry::Integer(-1))
+        );
+        assert_eq!(Ok(1), cp.add_or_get(ConstantPoolEntry::Integer(0)));
+        assert_eq!(65534, cp.slot_count());
+    }
+    // Test that walking a pool reports the slot after a double as a tombstone
+    #[test]
+    fn slot_after_double_is_a_tombstone() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Integer(7)).unwrap();
+        cp.add(ConstantPoolEntry::Double(2.5)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("after".to_string())).unwrap();
+
+        let walked: Vec<_> = (1..=cp.slot_count() as u16).map(|idx| cp.get(idx)).collect();
+        assert_eq!(
+            vec![
+                Ok(&ConstantPoolEntry::Integer(7)),
+                Ok(&ConstantPoolEntry::Double(2.5)),
+                Err(ConstantPoolError::TombstoneAccess { index: 3 }),
+                Ok(&ConstantPoolEntry::Utf8("after".to_string())),
+            ],
+            walked
+        );
+        assert_eq!(Err(ConstantPoolError::InvalidIndex { index: 5 }), cp.get(5));
+    }
+    // Test that bounded texts are cut at the limit, even when made of nested references
+    #[test]
+    fn bounded_text_is_truncated() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("é".repeat(5000))).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("x".repeat(5000))).unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(3, 3))
+            .unwrap();
+        cp.add(ConstantPoolEntry::FieldReference(2, 4)).unwrap();
+
+        assert_eq!(15003, cp.text_of(5).unwrap().chars().count());
+        assert_eq!(
+            format!("{}...", "é".repeat(10)),
+            cp.text_of_bounded(5, 10).unwrap()
+        );
+        let cut = cp.text_of_bounded(5, 5002).unwrap();
+        assert!(cut.ends_with(".x..."));
+        assert_eq!(5005, cut.chars().count());
+        assert_eq!(cp.text_of(5), cp.text_of_bounded(5, 15003));
+        assert_eq!("éé...", cp.text_of_bounded(1, 2).unwrap());
+        assert_eq!(
+            Err(ConstantPoolError::InvalidIndex { index: 6 }),
+            cp.text_of_bounded(6, 10)
+        );
+
+        // Rendering stops at the limit, before the dangling reference is reached
+        cp.add(ConstantPoolEntry::FieldReference(2, 99)).unwrap();
+        assert!(cp.text_of(6).is_err());
+        assert_eq!("ééé...", cp.text_of_bounded(6, 3).unwrap());
+    }
+    // Test that a self referencing entry is reported instead of recursing forever
+    #[test]
+    fn cyclic_references_are_detected() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::ClassReference(2)).unwrap();
+        cp.add(ConstantPoolEntry::StringReference(1)).unwrap();
+
+        assert!(matches!(
+            cp.text_of(1),
+            Err(ConstantPoolError::CyclicReference { .. })
+        ));
+    }
+    // Test that references to missing or unusable entries are reported as dangling
+    #[test]
+    fn dangling_references_are_detected() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::ClassReference(9)).unwrap();
+        cp.add(ConstantPoolEntry::Long(1)).unwrap();
+        cp.add(ConstantPoolEntry::StringReference(3)).unwrap();
+
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 1,
+                target: 9
+            }),
+            cp.text_of(1)
+        );
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 1,
+                target: 9
+            }),
+            cp.class_name_at(1)
+        );
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 4,
+                target: 3
+            }),
+            cp.text_of(4)
+        );
+        assert_eq!(
+            "constant pool entry 4 refers to missing entry 3",
+            cp.text_of(4).unwrap_err().to_string()
+        );
+    }
+    // Test that merging remaps the indices of the other pool, skipping its tombstones
+    #[test]
+    fn pools_can_be_merged() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("Foo".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+
+        let mut other = ConstantPool::new();
+        other.add(ConstantPoolEntry::Long(5)).unwrap();
+        other
+            .add(ConstantPoolEntry::Utf8("Bar".to_string()))
+            .unwrap();
+        other
+            .add(ConstantPoolEntry::Utf8("Foo".to_string()))
+            .unwrap();
+        other.add(ConstantPoolEntry::ClassReference(4)).unwrap();
+        other.add(ConstantPoolEntry::ClassReference(3)).unwrap();
+
+        assert_eq!(Ok(vec![0, 3, 0, 5, 1, 2, 6]), cp.merge(&other));
+        assert_eq!(Ok(&ConstantPoolEntry::Long(5)), cp.get(3));
+        assert_eq!(Ok(&ConstantPoolEntry::ClassReference(5)), cp.get(6));
+        assert_eq!(Ok("Bar".to_string()), cp.class_name_at(6));
+
+        let mut dangling = ConstantPool::new();
+        dangling.add(ConstantPoolEntry::ClassReference(2)).unwrap();
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 1,
+                target: 2
+            }),
+            cp.merge(&dangling)
+        );
+    }
+    // Test getting the descriptor of a method reference in one step
+    #[test]
+    fn method_reference_descriptors_are_found() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("foo".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("(IJ)V".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(1, 2))
+            .unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::MethodReference(4, 3)).unwrap();
+        cp.add(ConstantPoolEntry::FieldReference(4, 3)).unwrap();
+        cp.add(ConstantPoolEntry::InterfaceMethodReference(4, 9))
+            .unwrap();
+
+        assert_eq!(Ok("(IJ)V".to_string()), cp.method_ref_descriptor_at(5));
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 6,
+                expected: "MethodReference"
+            }),
+            cp.method_ref_descriptor_at(6)
+        );
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 7,
+                target: 9
+            }),
+            cp.method_ref_descriptor_at(7)
+        );
+    }
+    // Test getting the owner and the name of a method reference in one step
+    #[test]
+    fn method_reference_owners_and_names_are_found() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("java/util/List".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("size".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("()I".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(3, 4))
+            .unwrap();
+        cp.add(ConstantPoolEntry::InterfaceMethodReference(2, 5))
+            .unwrap();
+        cp.add(ConstantPoolEntry::MethodReference(9, 5)).unwrap();
+        cp.add(ConstantPoolEntry::FieldReference(2, 5)).unwrap();
+
+        assert_eq!(Ok("java/util/List".to_string()), cp.method_ref_owner_at(6));
+        assert_eq!(Ok("size".to_string()), cp.method_ref_name_at(6));
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 7,
+                target: 9
+            }),
+            cp.method_ref_owner_at(7)
+        );
+        assert_eq!(Ok("size".to_string()), cp.method_ref_name_at(7));
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 8,
+                expected: "MethodReference"
+            }),
+            cp.method_ref_name_at(8)
+        );
+    }
+    // Test that entries list the indices they refer to
+    #[test]
+    fn referenced_indices_are_listed() {
+        assert_eq!(
+            vec![3, 7],
+            ConstantPoolEntry::FieldReference(3, 7).referenced_indices()
+        );
+        assert_eq!(
+            vec![4],
+            ConstantPoolEntry::ClassReference(4).referenced_indices()
+        );
+        assert_eq!(
+            vec![9],
+            ConstantPoolEntry::InvokeDynamic(0, 9).referenced_indices()
+        );
+        assert!(ConstantPoolEntry::Utf8("a".to_string())
+            .referenced_indices()
+            .is_empty());
+        assert!(ConstantPoolEntry::Long(5).referenced_indices().is_empty());
+    }
+    // Test that references are validated for their targets and the kind of the targets
+    #[test]
+    fn references_are_validated() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("Foo".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("m".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(3, 3))
+            .unwrap();
+        cp.add(ConstantPoolEntry::MethodReference(2, 4)).unwrap();
+        cp.add(ConstantPoolEntry::MethodHandle(6, 5)).unwrap();
+        assert_eq!(Ok(()), cp.validate());
+
+        cp.add(ConstantPoolEntry::MethodHandle(1, 5)).unwrap();
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 5,
+                expected: "FieldReference"
+            }),
+            cp.validate()
+        );
+
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::StringReference(2)).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(9)).unwrap();
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 2,
+                expected: "Utf8"
+            }),
+            cp.validate()
+        );
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::ClassReference(9)).unwrap();
+        assert_eq!(
+            Err(ConstantPoolError::DanglingReference {
+                index: 1,
+                target: 9
+            }),
+            cp.validate()
+        );
+    }
+    // Test that entries can be displayed without a pool
+    #[test]
+    fn entries_are_displayed_raw() {
+        assert_eq!(
+            "MethodReference(#3, #4)",
+            ConstantPoolEntry::MethodReference(3, 4).to_string()
+        );
+        assert_eq!(
+            "Utf8(\"a \\\"b\\\"\")",
+            ConstantPoolEntry::Utf8("a \"b\"".to_string()).to_string()
+        );
+        assert_eq!(
+            "InvokeDynamic(bootstrap 0, #7)",
+            ConstantPoolEntry::InvokeDynamic(0, 7).to_string()
+        );
+    }
+    // Test that lenient mode shows missing entries as placeholders
+    #[test]
+    fn missing_entries_are_placeholders_when_lenient() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("foo".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(1, 9))
+            .unwrap();
+        cp.add(ConstantPoolEntry::MethodReference(7, 2)).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(4)).unwrap();
+        cp.set_lenient(true);
+
+        assert_eq!(
+            Ok("<unresolved #7>.foo: <unresolved #9>".to_string()),
+            cp.text_of(3)
+        );
+        assert_eq!(Ok("<unresolved #12>".to_string()), cp.text_of(12));
+        assert_eq!(
+            Err(ConstantPoolError::CyclicReference { index: 4 }),
+            cp.text_of(4)
+        );
+    }
+    // Test that the DOT graph has an edge for each reference, and no node for tombstones
+    #[test]
+    fn pool_can_be_rendered_as_dot() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("Foo".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Long(3)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("say \"hi\"".to_string()))
+            .unwrap();
+
+        let dot = cp.to_dot();
+        assert!(dot.starts_with("digraph constant_pool {"));
+        assert!(dot.contains(r#"    n1 [label="1: Utf8 \"Foo\""];"#));
+        assert!(dot.contains("    n2 [label=\"2: ClassReference\"];\n"));
+        assert!(dot.contains("    n2 -> n1;\n"));
+        assert!(dot.contains("    n3 [label=\"3: Long 3\"];\n"));
+        assert!(!dot.contains("n4"));
+        assert!(dot.contains(r#"    n5 [label="5: Utf8 \"say \"hi\"\""];"#));
+    }
+    // Test that class references to array types are told apart from class names
+    #[test]
+    fn array_class_references_are_detected() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("[[I".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("java/lang/String".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(3)).unwrap();
+
+        assert_eq!(Ok(true), cp.is_array_class_at(2));
+        assert_eq!(Ok(false), cp.is_array_class_at(4));
+        assert_eq!(
+            Ok(FieldType::Array(Box::new(FieldType::Array(Box::new(
+                FieldType::Int
+            ))))),
+            cp.class_type_at(2)
+        );
+        assert_eq!(
+            Ok(FieldType::Object("java/lang/String".to_string())),
+            cp.class_type_at(4)
+        );
+        assert_eq!("int[][]", cp.class_type_at(2).unwrap().to_string());
+    }
+    // Test that add_or_get reuses equal entries, comparing floats by their bits
+    #[test]
+    fn equal_entries_are_added_once() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("Foo".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::Long(1)).unwrap();
+
+        assert_eq!(
+            Ok(1),
+            cp.add_or_get(ConstantPoolEntry::Utf8("Foo".to_string()))
+        );
+        assert_eq!(Ok(2), cp.add_or_get(ConstantPoolEntry::Long(1)));
+        assert_eq!(Ok(4), cp.add_or_get(ConstantPoolEntry::ClassReference(1)));
+        assert_eq!(Ok(4), cp.add_or_get(ConstantPoolEntry::ClassReference(1)));
+        assert_eq!(Ok(5), cp.add_or_get(ConstantPoolEntry::StringReference(1)));
+        assert_eq!(Ok(6), cp.add_or_get(ConstantPoolEntry::Float(f32::NAN)));
+        assert_eq!(Ok(6), cp.add_or_get(ConstantPoolEntry::Float(f32::NAN)));
+        assert_eq!(Ok(7), cp.add_or_get(ConstantPoolEntry::Float(0.0)));
+        assert_eq!(Ok(8), cp.add_or_get(ConstantPoolEntry::Float(-0.0)));
+
+        cp.add(ConstantPoolEntry::Integer(3)).unwrap();
+        assert_eq!(Ok(9), cp.add_or_get(ConstantPoolEntry::Integer(3)));
+        assert_eq!(Ok(&ConstantPoolEntry::ClassReference(1)), cp.get(4));
+    }
+    // Test filtering entries by their kind
+    #[test]
+    fn can_filter_entries_by_kind() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("hello".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::StringReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Double(1.5)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("world".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::StringReference(5)).unwrap();
+
+        assert_eq!(
+            vec![
+                (2, &ConstantPoolEntry::StringReference(1)),
+                (6, &ConstantPoolEntry::StringReference(5))
+            ],
+            cp.entries_of_kind(|entry| matches!(entry, ConstantPoolEntry::StringReference(_)))
+        );
+        assert_eq!(5, cp.iter().count());
+    }
+    // Test that pools compare their logical entries, with floats compared by bits
+    #[test]
+    fn pools_compare_logical_entries() {
+        let build = |double: f64| {
+            let mut cp = ConstantPool::new();
+            cp.add(ConstantPoolEntry::Long(7)).unwrap();
+            cp.add(ConstantPoolEntry::Double(double)).unwrap();
+            cp.add(ConstantPoolEntry::Utf8("x".to_string())).unwrap();
+            cp
+        };
+
+        assert_eq!(build(1.5), build(1.5));
+        assert_eq!(build(f64::NAN), build(f64::NAN));
+        assert_ne!(build(0.0), build(-0.0));
+        assert_ne!(build(1.5), ConstantPool::new());
+
+        let mut shorter = build(1.5);
+        let mut longer = build(1.5);
+        longer.add(ConstantPoolEntry::Integer(1)).unwrap();
+        assert_ne!(shorter, longer);
+        shorter.add(ConstantPoolEntry::Integer(1)).unwrap();
+        assert_eq!(shorter, longer);
+    }
+    // Test resolving the names of classes, including arrays and the default package
+    #[test]
+    fn can_resolve_class_names() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8(
+            "com/example/deep/Widget".to_string(),
+        ))
+        .unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("[[Ljava/lang/String;".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(3)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("TopLevel".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(5)).unwrap();
+        cp.add(ConstantPoolEntry::Utf8("[I".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(7)).unwrap();
+
+        assert_eq!("com/example/deep/Widget", cp.class_name_at(2).unwrap());
+        assert_eq!("Widget", cp.simple_class_name_at(2).unwrap());
+        assert_eq!("[[Ljava/lang/String;", cp.class_name_at(4).unwrap());
+        assert_eq!("String[][]", cp.simple_class_name_at(4).unwrap());
+        assert_eq!("TopLevel", cp.simple_class_name_at(6).unwrap());
+        assert_eq!("int[]", cp.simple_class_name_at(8).unwrap());
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 1,
+                expected: "ClassReference"
+            }),
+            cp.class_name_at(1)
+        );
+    }
+    // Test resolving a name and type entry into its two strings
+    #[test]
+    fn can_resolve_name_and_type() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("equals".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::Utf8("(Ljava/lang/Object;)Z".to_string()))
+            .unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(1, 2))
+            .unwrap();
+        cp.add(ConstantPoolEntry::NameAndTypeDescriptor(1, 3))
+            .unwrap();
+
+        assert_eq!(
+            ("equals".to_string(), "(Ljava/lang/Object;)Z".to_string()),
+            cp.name_and_type_at(3).unwrap()
+        );
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 1,
+                expected: "NameAndTypeDescriptor"
+            }),
+            cp.name_and_type_at(1)
+        );
+        assert_eq!(
+            Err(ConstantPoolError::UnexpectedEntry {
+                index: 3,
+                expected: "Utf8"
+            }),
+            cp.name_and_type_at(4)
+        );
+    }
+    // Test that entries are hashed and compared by content, with floats by their bits
+    #[test]
+    #[cfg(feature = "std")]
+    fn entries_can_be_hashed() {
+        use std::collections::HashSet;
+
+        let entries: HashSet<ConstantPoolEntry> = [
+            ConstantPoolEntry::Utf8("foo".to_string()),
+            ConstantPoolEntry::Utf8("foo".to_string()),
+            ConstantPoolEntry::ClassReference(1),
+            ConstantPoolEntry::StringReference(1),
+            ConstantPoolEntry::Float(f32::NAN),
+            ConstantPoolEntry::Float(f32::NAN),
+            ConstantPoolEntry::Double(0.0),
+            ConstantPoolEntry::Double(-0.0),
+            ConstantPoolEntry::MethodHandle(6, 2),
+            ConstantPoolEntry::MethodHandle(6, 2),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(7, entries.len());
+        assert!(entries.contains(&ConstantPoolEntry::Float(f32::NAN)));
+        assert!(!entries.contains(&ConstantPoolEntry::FieldReference(1, 0)));
+    }
 }
@@ -0,0 +1,217 @@
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::class_file_method::ClassFileMethod;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::instruction::{decode, Instruction, Opcode, Operand};
+
+/// A straight-line sequence of instructions, entered only at its first instruction.
+#[derive(Debug, Default, PartialEq)]
+pub struct BasicBlock {
+    /// Offset of the first instruction of the block.
+    pub start: u32,
+    /// Offset just past the last instruction of the block.
+    pub end: u32,
+    pub instructions: Vec<Instruction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// Execution continues with the next block.
+    FallThrough,
+    /// A conditional branch is taken.
+    ConditionalBranch,
+    /// A goto, or a jsr into a subroutine.
+    UnconditionalBranch,
+    /// A case, or the default, of a tableswitch or lookupswitch.
+    SwitchCase,
+    /// An exception thrown in the block is caught by a handler.
+    ExceptionHandler,
+}
+
+/// An edge between two blocks, identified by their index in the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// The control flow graph of a method body. Blocks are sorted by offset, so the entry block is
+/// the first one.
+#[derive(Debug, Default, PartialEq)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    /// Index of the block starting at the given offset.
+    pub fn block_at(&self, offset: u32) -> Option<usize> {
+        self.blocks
+            .binary_search_by_key(&offset, |block| block.start)
+            .ok()
+    }
+
+    /// Edges leaving the given block.
+    pub fn successors(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == block)
+    }
+}
+
+/// Builds the control flow graph of a method. Methods without code have an empty graph.
+///
+/// A `ret` has no outgoing edges, as its target is only known at runtime; the instruction
+/// following a `jsr` is reached by falling through instead.
+pub fn build_cfg(method: &ClassFileMethod) -> Result<ControlFlowGraph> {
+    let code = match &method.code {
+        Some(code) => code,
+        None => return Ok(Default::default()),
+    };
+    let instructions = decode(&code.code)?;
+    let code_length = code.code.len() as u32;
+
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    for (i, instruction) in instructions.iter().enumerate() {
+        let targets = jump_targets(instruction)?;
+        let ends_block = !targets.is_empty() || !falls_through(instruction.opcode);
+        leaders.extend(targets);
+        if ends_block {
+            if let Some(next) = instructions.get(i + 1) {
+                leaders.insert(next.offset);
+            }
+        }
+    }
+    for handler in code.exception_table.iter() {
+        leaders.insert(handler.start_pc as u32);
+        leaders.insert(handler.handler_pc as u32);
+        if (handler.end_pc as u32) < code_length {
+            leaders.insert(handler.end_pc as u32);
+        }
+    }
+
+    let mut graph = ControlFlowGraph::default();
+    for instruction in instructions {
+        if leaders.contains(&instruction.offset) || graph.blocks.is_empty() {
+            graph.blocks.push(BasicBlock {
+                start: instruction.offset,
+                end: code_length,
+                instructions: vec![],
+            });
+        }
+        if let Some(block) = graph.blocks.last_mut() {
+            block.instructions.push(instruction);
+        }
+    }
+    for i in 1..graph.blocks.len() {
+        graph.blocks[i - 1].end = graph.blocks[i].start;
+    }
+
+    let mut edges = Vec::new();
+    for (from, block) in graph.blocks.iter().enumerate() {
+        let last = match block.instructions.last() {
+            Some(last) => last,
+            None => continue,
+        };
+        let kind = match last.opcode {
+            Opcode::Goto | Opcode::GotoW | Opcode::Jsr | Opcode::JsrW => {
+                EdgeKind::UnconditionalBranch
+            }
+            Opcode::Tableswitch | Opcode::Lookupswitch => EdgeKind::SwitchCase,
+            _ => EdgeKind::ConditionalBranch,
+        };
+        let mut targets = jump_targets(last)?;
+        targets.sort_unstable();
+        targets.dedup();
+        for target in targets {
+            edges.push(Edge {
+                from,
+                to: target_block(&graph, target)?,
+                kind,
+            });
+        }
+        if falls_through(last.opcode) && from + 1 < graph.blocks.len() {
+            edges.push(Edge {
+                from,
+                to: from + 1,
+                kind: EdgeKind::FallThrough,
+            });
+        }
+        for handler in code.exception_table.iter() {
+            let covered =
+                (handler.start_pc as u32) < block.end && block.start < handler.end_pc as u32;
+            if covered {
+                edges.push(Edge {
+                    from,
+                    to: target_block(&graph, handler.handler_pc as u32)?,
+                    kind: EdgeKind::ExceptionHandler,
+                });
+            }
+        }
+    }
+    graph.edges = edges;
+
+    Ok(graph)
+}
+
+// Whether execution can continue with the following instruction
+pub(crate) fn falls_through(opcode: Opcode) -> bool {
+    !matches!(
+        opcode,
+        Opcode::Goto
+            | Opcode::GotoW
+            | Opcode::Tableswitch
+            | Opcode::Lookupswitch
+            | Opcode::Ireturn
+            | Opcode::Lreturn
+            | Opcode::Freturn
+            | Opcode::Dreturn
+            | Opcode::Areturn
+            | Opcode::Return
+            | Opcode::Athrow
+            | Opcode::Ret
+    )
+}
+
+// Absolute offsets that an instruction can jump to, other than the next instruction
+fn jump_targets(instruction: &Instruction) -> Result<Vec<u32>> {
+    let relative_offsets = match &instruction.operand {
+        Operand::Branch(offset) => vec![*offset],
+        Operand::TableSwitch {
+            default, offsets, ..
+        } => {
+            let mut all = offsets.clone();
+            all.push(*default);
+            all
+        }
+        Operand::LookupSwitch { default, pairs } => {
+            let mut all: Vec<i32> = pairs.iter().map(|(_, offset)| *offset).collect();
+            all.push(*default);
+            all
+        }
+        _ => vec![],
+    };
+    relative_offsets
+        .into_iter()
+        .map(|relative| {
+            u32::try_from(instruction.offset as i64 + relative as i64).map_err(|_| {
+                ClassReaderError::InvalidClassData(format!(
+                    "invalid jump target at offset {}: {}",
+                    instruction.offset, relative
+                ))
+            })
+        })
+        .collect()
+}
+
+fn target_block(graph: &ControlFlowGraph, offset: u32) -> Result<usize> {
+    graph.block_at(offset).ok_or_else(|| {
+        ClassReaderError::InvalidClassData(format!(
+            "jump to offset {} which is not the start of an instruction",
+            offset
+        ))
+    })
+}
@@ -1,13 +1,27 @@
+use core::fmt;
+use core::fmt::Formatter;
+
 bitflags! {
     pub struct ClassAccessFlags: u16 {
         const PUBLIC = 0x0001;
         const FINAL = 0x0010;
+        // Modern invokespecial semantics, set by every compiler since JDK 1.0.2. Same value as
+        // MethodFlags::SYNCHRONIZED
         const SUPER = 0x0020;
         const INTERFACE = 0x0200;
         const ABSTRACT = 0x0400;
         const SYNTHETIC = 0x1000;
         const ANNOTATION = 0x2000;
         const ENUM = 0x4000;
+        // Set on module descriptors, module-info.class, and on nothing else
+        const MODULE = 0x8000;
+    }
+}
+
+impl ClassAccessFlags {
+    // Whether the ACC_SUPER flag is set
+    pub fn is_super(&self) -> bool {
+        self.contains(ClassAccessFlags::SUPER)
     }
 }
 
@@ -16,3 +30,23 @@ impl Default for ClassAccessFlags {
         ClassAccessFlags::empty()
     }
 }
+
+// Renders the flags that are source keywords, like "public abstract interface"
+impl fmt::Display for ClassAccessFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let keywords = [
+            (ClassAccessFlags::PUBLIC, "public"),
+            (ClassAccessFlags::ABSTRACT, "abstract"),
+            (ClassAccessFlags::FINAL, "final"),
+            (ClassAccessFlags::INTERFACE, "interface"),
+        ];
+        let mut separator = "";
+        for (flag, keyword) in keywords {
+            if self.contains(flag) {
+                write!(f, "{}{}", separator, keyword)?;
+                separator = " ";
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,23 +1,651 @@
-use std::fmt;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
 
+use crate::annotation::{Annotation, TypeAnnotation};
+use crate::attribute::Attribute;
+use crate::bootstrap_method::{read_bootstrap_methods, BootstrapMethod, ResolvedBootstrap};
+use crate::buffer::BufferReader;
 use crate::class_file_field::ClassFileField;
 use crate::class_file_method::ClassFileMethod;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::compaction;
+use crate::equivalence;
+use crate::field_type::FieldType;
+use crate::inner_class::{read_inner_classes, InnerClass};
+use crate::member_ref::{MemberRef, MemberRefKind};
+use crate::nest_role::{read_nest_host, read_nest_members, NestRole};
+use crate::raw_flags::RawFlags;
+use crate::signature::ClassSignature;
 use crate::{
-    c_pool::ConstantPool, class_access_flags::ClassAccessFlags,
+    c_pool::{ConstantPool, ConstantPoolEntry, ConstantPoolError},
+    class_access_flags::ClassAccessFlags,
     class_file_version::ClassFileVersion,
 };
 
+// Attributes that javac emits for debugging unless given -g:none
+const DEBUG_INFO_ATTRIBUTES: [&str; 4] = [
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "SourceFile",
+];
+
+/// Where an attribute appears in a class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeLocation<'a> {
+    Class,
+    Field(&'a ClassFileField),
+    Method(&'a ClassFileMethod),
+    // Nested in the Code attribute of the method
+    Code(&'a ClassFileMethod),
+}
+
 /// Represents the content of a .class file.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct ClassFile {
     pub version: ClassFileVersion,
+    pub minor_version: u16,
     pub constants: ConstantPool,
     pub flags: ClassAccessFlags,
+    // Access flag bits that no flag is defined for, see `RawFlags`
+    pub reserved_flags: u16,
     pub name: String,
     pub superclass: String,
     pub interfaces: Vec<String>,
     pub fields: Vec<ClassFileField>,
     pub methods: Vec<ClassFileMethod>,
+    pub attributes: Vec<Attribute>,
+    // Content of the SourceDebugExtension attribute, as used by JSR-45
+    pub source_debug_extension: Option<String>,
+    // Content of the RuntimeVisibleAnnotations attribute, the package ones for package-info
+    pub visible_annotations: Vec<Annotation>,
+    // Content of the RuntimeInvisibleAnnotations attribute
+    pub invisible_annotations: Vec<Annotation>,
+    // Content of the RuntimeVisibleTypeAnnotations attribute
+    pub visible_type_annotations: Vec<TypeAnnotation>,
+    // Content of the RuntimeInvisibleTypeAnnotations attribute
+    pub invisible_type_annotations: Vec<TypeAnnotation>,
+}
+
+impl RawFlags for ClassFile {
+    fn flag_bits(&self) -> u16 {
+        self.flags.bits()
+    }
+
+    fn reserved_bits(&self) -> u16 {
+        self.reserved_flags
+    }
+}
+
+impl ClassFile {
+    // Iterates over the methods declared in the source, skipping bridge and synthetic ones
+    pub fn declared_methods(&self) -> impl Iterator<Item = &ClassFileMethod> {
+        self.methods
+            .iter()
+            .filter(|method| !method.is_bridge() && !method.is_synthetic())
+    }
+
+    // The methods grouped by name, each group in declaration order
+    pub fn methods_grouped_by_name(&self) -> impl Iterator<Item = (&str, Vec<&ClassFileMethod>)> {
+        let mut groups: BTreeMap<&str, Vec<&ClassFileMethod>> = BTreeMap::new();
+        for method in &self.methods {
+            groups.entry(method.name.as_str()).or_default().push(method);
+        }
+        groups.into_iter()
+    }
+
+    // Returns the string literals of the constant pool
+    pub fn string_constants(&self) -> Vec<&str> {
+        self.constants
+            .entries_of_kind(|entry| matches!(entry, ConstantPoolEntry::StringReference(_)))
+            .into_iter()
+            .filter_map(|(_, entry)| match entry {
+                ConstantPoolEntry::StringReference(index) => match self.constants.get(*index) {
+                    Ok(ConstantPoolEntry::Utf8(s)) => Some(s.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Fields and methods of other classes referenced from the constant pool, skipping those that
+    // cannot be resolved
+    pub fn external_member_references(&self) -> Vec<MemberRef> {
+        self.constants
+            .iter()
+            .filter_map(|(_, entry)| {
+                let (kind, class, name_and_type) = match entry {
+                    ConstantPoolEntry::FieldReference(c, n) => (MemberRefKind::Field, c, n),
+                    ConstantPoolEntry::MethodReference(c, n) => (MemberRefKind::Method, c, n),
+                    ConstantPoolEntry::InterfaceMethodReference(c, n) => {
+                        (MemberRefKind::InterfaceMethod, c, n)
+                    }
+                    _ => return None,
+                };
+                let owner = self.constants.class_name_at(*class).ok()?;
+                let (name, descriptor) = self.constants.name_and_type_at(*name_and_type).ok()?;
+                Some(MemberRef {
+                    kind,
+                    owner,
+                    name,
+                    descriptor,
+                })
+            })
+            .filter(|reference| reference.owner != self.name)
+            .collect()
+    }
+
+    // Types in the descriptors of the fields, methods and NameAndType entries, without duplicates.
+    // Invalid descriptors are skipped
+    pub fn referenced_types(&self) -> Vec<FieldType> {
+        let member_descriptors = self
+            .fields
+            .iter()
+            .map(|field| field.type_descriptor.clone())
+            .chain(
+                self.methods
+                    .iter()
+                    .map(|method| method.type_descriptor.clone()),
+            );
+        let pool_descriptors = self.constants.iter().filter_map(|(_, entry)| match entry {
+            ConstantPoolEntry::NameAndTypeDescriptor(_, descriptor) => {
+                self.constants.text_of(*descriptor).ok()
+            }
+            _ => None,
+        });
+        let mut types = Vec::new();
+        for descriptor in member_descriptors.chain(pool_descriptors) {
+            for field_type in descriptor_types(&descriptor) {
+                if !types.contains(&field_type) {
+                    types.push(field_type);
+                }
+            }
+        }
+        types
+    }
+
+    // Decodes the BootstrapMethods attribute, or gives no methods if there is none
+    pub fn bootstrap_methods(&self) -> Result<Vec<BootstrapMethod>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == "BootstrapMethods")
+            .map(|attr| read_bootstrap_methods(&attr.info))
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    // Resolves the bootstrap method of the InvokeDynamic or Dynamic entry at the index, with its
+    // static arguments
+    pub fn bootstrap_method_for(&self, index: u16) -> Result<ResolvedBootstrap> {
+        let (bootstrap, name_and_type) = match self.constants.get(index)? {
+            ConstantPoolEntry::InvokeDynamic(bootstrap, name_and_type)
+            | ConstantPoolEntry::Dynamic(bootstrap, name_and_type) => (*bootstrap, *name_and_type),
+            _ => {
+                return Err(ConstantPoolError::UnexpectedEntry {
+                    index,
+                    expected: "InvokeDynamic",
+                }
+                .into())
+            }
+        };
+        let (name, descriptor) = self.constants.name_and_type_at(name_and_type)?;
+        let methods = self.bootstrap_methods()?;
+        let method = methods.get(bootstrap as usize).ok_or_else(|| {
+            ClassReaderError::InvalidClassData(format!(
+                "bootstrap method {} is missing, the class has {}",
+                bootstrap,
+                methods.len()
+            ))
+        })?;
+        method.resolve(&self.constants, name, descriptor)
+    }
+
+    // Whether the other class only differs in debug information and constant pool layout, see
+    // equivalence::equivalent_ignoring_debug
+    pub fn equivalent_ignoring_debug(&self, other: &ClassFile) -> bool {
+        equivalence::equivalent_ignoring_debug(self, other)
+    }
+
+    // Whether the class has any of the debug attributes that javac leaves out with -g:none
+    pub fn has_debug_info(&self) -> bool {
+        self.all_attributes()
+            .any(|(_, attr)| DEBUG_INFO_ATTRIBUTES.contains(&attr.name.as_str()))
+    }
+
+    // Drops the unused constant pool entries, see compaction::compact_constant_pool
+    pub fn compact_constant_pool(&mut self) -> Result<()> {
+        compaction::compact_constant_pool(self)
+    }
+
+    // Renames the class `old` to `new` in the names, descriptors and constant pool of this class.
+    // Signatures and strings are left as they are, along with the Utf8 entries they share. Fails if
+    // the pool is full
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<()> {
+        // The pool entries holding the descriptors of the members are only known by their text
+        let member_descriptors: BTreeSet<String> = self
+            .fields
+            .iter()
+            .map(|field| field.type_descriptor.clone())
+            .chain(
+                self.methods
+                    .iter()
+                    .map(|method| method.type_descriptor.clone()),
+            )
+            .collect();
+        // Names are only kept as text too, and are never renamed
+        let names: BTreeSet<String> = self
+            .fields
+            .iter()
+            .map(|field| field.name.clone())
+            .chain(self.methods.iter().map(|method| method.name.clone()))
+            .chain(self.all_attributes().map(|(_, attr)| attr.name.clone()))
+            .collect();
+        // Without knowing what the attributes refer to, every entry has to be taken as shared
+        let attribute_indices = compaction::referenced_indices(self);
+
+        self.name = rename_class(&self.name, old, new);
+        self.superclass = rename_class(&self.superclass, old, new);
+        for interface in &mut self.interfaces {
+            *interface = rename_class(interface, old, new);
+        }
+        for field in &mut self.fields {
+            field.type_descriptor = rename_in_descriptor(&field.type_descriptor, old, new);
+        }
+        for method in &mut self.methods {
+            method.type_descriptor = rename_in_descriptor(&method.type_descriptor, old, new);
+        }
+
+        // The entries referring to each Utf8 entry to rewrite, with whether they use it as a
+        // class name or else a descriptor. Members use their descriptors by text, from index 0.
+        let mut targets: BTreeMap<u16, Vec<(u16, bool)>> = BTreeMap::new();
+        let mut shared = BTreeSet::new();
+        for (index, entry) in self.constants.iter() {
+            match entry {
+                ConstantPoolEntry::ClassReference(name) => {
+                    targets.entry(*name).or_default().push((index, true));
+                }
+                ConstantPoolEntry::NameAndTypeDescriptor(name, descriptor) => {
+                    targets.entry(*descriptor).or_default().push((index, false));
+                    shared.insert(*name);
+                }
+                ConstantPoolEntry::MethodType(descriptor) => {
+                    targets.entry(*descriptor).or_default().push((index, false));
+                }
+                ConstantPoolEntry::Utf8(text) => {
+                    if member_descriptors.contains(text) {
+                        targets.entry(index).or_default().push((0, false));
+                    }
+                    if names.contains(text) {
+                        shared.insert(index);
+                    }
+                }
+                other => shared.extend(other.referenced_indices()),
+            }
+        }
+
+        // Entries used only where they are renamed, and the same way, are rewritten in place
+        let mut added = Vec::new();
+        for (index, referrers) in targets {
+            let text = match self.constants.get(index) {
+                Ok(ConstantPoolEntry::Utf8(text)) => text.clone(),
+                _ => continue,
+            };
+            let renamed: Vec<(u16, bool, String)> = referrers
+                .into_iter()
+                .map(|(referrer, is_class_name)| {
+                    let renamed = match is_class_name {
+                        true => rename_class(&text, old, new),
+                        false => rename_in_descriptor(&text, old, new),
+                    };
+                    (referrer, is_class_name, renamed)
+                })
+                .filter(|(_, _, renamed)| *renamed != text)
+                .collect();
+            let is_shared = shared.contains(&index)
+                || attribute_indices
+                    .as_ref()
+                    .is_none_or(|indices| indices.contains(&index))
+                || renamed.iter().any(|(_, _, other)| *other != renamed[0].2);
+            match renamed.first() {
+                None => {}
+                Some((_, _, first)) if !is_shared => {
+                    self.constants.set_utf8(index, first.clone())?;
+                }
+                Some(_) => added.extend(renamed),
+            }
+        }
+        // Entries that are also used elsewhere are kept, and the renamed uses get their own
+        for (referrer, is_class_name, renamed) in added {
+            let index = self
+                .constants
+                .add_or_get(ConstantPoolEntry::Utf8(renamed))?;
+            let entry = match self.constants.get(referrer) {
+                Ok(ConstantPoolEntry::NameAndTypeDescriptor(name, _)) => {
+                    ConstantPoolEntry::NameAndTypeDescriptor(*name, index)
+                }
+                Ok(ConstantPoolEntry::MethodType(_)) => ConstantPoolEntry::MethodType(index),
+                Ok(_) if is_class_name => ConstantPoolEntry::ClassReference(index),
+                _ => continue,
+            };
+            self.constants.replace(referrer, entry)?;
+        }
+        Ok(())
+    }
+
+    // Renders the class, its members and their attributes as a tree drawn with box characters
+    pub fn tree_view(&self) -> String {
+        let leaf = |label: String| TreeNode {
+            label,
+            children: Vec::new(),
+        };
+        let attributes = |attributes: &[Attribute]| {
+            attributes
+                .iter()
+                .map(|attribute| leaf(attribute.to_string()))
+                .collect()
+        };
+        let branch = |label: &str, children: Vec<TreeNode>| TreeNode {
+            label: label.to_string(),
+            children,
+        };
+        let root = TreeNode {
+            label: self.name.clone(),
+            children: vec![
+                leaf(format!("version: {}", self.version)),
+                leaf(format!("flags: {}", self.flags)),
+                branch(
+                    "interfaces",
+                    self.interfaces
+                        .iter()
+                        .map(|name| leaf(name.clone()))
+                        .collect(),
+                ),
+                branch(
+                    "fields",
+                    self.fields
+                        .iter()
+                        .map(|field| TreeNode {
+                            label: format!("{}: {}", field.name, field.type_descriptor),
+                            children: attributes(&field.attributes),
+                        })
+                        .collect(),
+                ),
+                branch(
+                    "methods",
+                    self.methods
+                        .iter()
+                        .map(|method| TreeNode {
+                            label: format!("{}{}", method.name, method.type_descriptor),
+                            children: attributes(&method.attributes),
+                        })
+                        .collect(),
+                ),
+                branch("attributes", attributes(&self.attributes)),
+            ],
+        };
+        let mut tree = String::new();
+        root.render(&mut tree, "");
+        tree
+    }
+
+    // Iterates over the attributes of the class, its fields, its methods and their code
+    pub fn all_attributes(&self) -> impl Iterator<Item = (AttributeLocation<'_>, &Attribute)> {
+        let class = self
+            .attributes
+            .iter()
+            .map(|attribute| (AttributeLocation::Class, attribute));
+        let fields = self.fields.iter().flat_map(|field| {
+            field
+                .attributes
+                .iter()
+                .map(move |attribute| (AttributeLocation::Field(field), attribute))
+        });
+        let methods = self.methods.iter().flat_map(|method| {
+            let code = method.code.iter().flat_map(move |code| {
+                code.attributes
+                    .iter()
+                    .map(move |attribute| (AttributeLocation::Code(method), attribute))
+            });
+            method
+                .attributes
+                .iter()
+                .map(move |attribute| (AttributeLocation::Method(method), attribute))
+                .chain(code)
+        });
+        class.chain(fields).chain(methods)
+    }
+
+    // Internal names of the superclass, if the class has one, followed by the interfaces
+    pub fn direct_supertypes(&self) -> Vec<String> {
+        let mut supertypes = Vec::with_capacity(self.interfaces.len() + 1);
+        if !self.superclass.is_empty() {
+            supertypes.push(self.superclass.clone());
+        }
+        supertypes.extend(self.interfaces.iter().cloned());
+        supertypes
+    }
+
+    // Whether this class is, extends or implements the given class. Supertypes are loaded through
+    // the resolver, and those it cannot find are not searched
+    pub fn is_subtype_of(
+        &self,
+        target: &str,
+        resolver: &mut dyn FnMut(&str) -> Option<ClassFile>,
+    ) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![self.name.clone()];
+        // Visited classes are never loaded twice, so a cyclic hierarchy terminates
+        while let Some(name) = pending.pop() {
+            if name == target {
+                return true;
+            }
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            let supertypes = if name == self.name {
+                self.direct_supertypes()
+            } else {
+                match resolver(&name) {
+                    Some(class) => class.direct_supertypes(),
+                    None => continue,
+                }
+            };
+            pending.extend(supertypes);
+        }
+        false
+    }
+
+    // Internal name of the top-level class enclosing this one, following InnerClasses and
+    // EnclosingMethod. Without them, the name is cut at the first `$`, which is only a heuristic
+    pub fn top_level_name(&self) -> String {
+        let has_nesting_info = self
+            .attributes
+            .iter()
+            .any(|attr| attr.name == "InnerClasses" || attr.name == "EnclosingMethod");
+        if !has_nesting_info {
+            return match self.name.split_once('$') {
+                Some((top_level, _)) => top_level.to_string(),
+                None => self.name.clone(),
+            };
+        }
+
+        // An InnerClasses attribute that cannot be decoded leaves only EnclosingMethod to follow
+        let inner_classes = self.inner_classes().unwrap_or_default();
+        let mut name = self.name.clone();
+        // Each step goes one class outwards, so a well-formed chain is never longer than this
+        for _ in 0..=inner_classes.len() {
+            match self.outer_class_of(&inner_classes, &name) {
+                Some(outer) => name = outer,
+                None => break,
+            }
+        }
+        name
+    }
+
+    // Parses the generic signature of the class, if it has a Signature attribute
+    pub fn signature(&self) -> Result<Option<ClassSignature>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == "Signature")
+            .map(|attr| {
+                let index = BufferReader::new(&attr.info).read_u16()?;
+                ClassSignature::parse(&self.constants.text_of(index)?)
+            })
+            .transpose()
+    }
+
+    // Decodes the InnerClasses attribute, or gives no classes if there is none
+    pub fn inner_classes(&self) -> Result<Vec<InnerClass>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == "InnerClasses")
+            .map(|attr| read_inner_classes(&attr.info, &self.constants))
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    // Decodes the NestHost attribute, if there is one
+    pub fn nest_host(&self) -> Result<Option<String>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == "NestHost")
+            .map(|attr| read_nest_host(&attr.info, &self.constants))
+            .transpose()
+    }
+
+    // Decodes the NestMembers attribute, or gives no members if there is none
+    pub fn nest_members(&self) -> Result<Vec<String>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.name == "NestMembers")
+            .map(|attr| read_nest_members(&attr.info, &self.constants))
+            .unwrap_or_else(|| Ok(Vec::new()))
+    }
+
+    // Whether the class hosts a nest or is a member of one. A class cannot be both
+    pub fn nest_relationship(&self) -> Result<NestRole> {
+        let has_members = self
+            .attributes
+            .iter()
+            .any(|attr| attr.name == "NestMembers");
+        match (self.nest_host()?, has_members) {
+            (Some(_), true) => Err(ClassReaderError::InvalidClassData(String::from(
+                "a class cannot have both NestHost and NestMembers attributes",
+            ))),
+            (Some(host), false) => Ok(NestRole::Member(host)),
+            (None, true) => Ok(NestRole::Host(self.nest_members()?)),
+            (None, false) => Ok(NestRole::None),
+        }
+    }
+
+    // Whether this is a package-info class, which holds the annotations of a package
+    pub fn is_package_info(&self) -> bool {
+        let simple_name = self.name.rsplit('/').next().unwrap_or_default();
+        simple_name == "package-info"
+            && self
+                .flags
+                .contains(ClassAccessFlags::INTERFACE | ClassAccessFlags::SYNTHETIC)
+    }
+
+    // Whether the class needs preview features, marked by a minor version of 65535 since Java 12
+    pub fn requires_preview_features(&self) -> bool {
+        self.minor_version == 0xFFFF && self.version >= ClassFileVersion::Jdk12
+    }
+
+    // The class directly enclosing the given one, according to the attributes of this class
+    fn outer_class_of(&self, inner_classes: &[InnerClass], name: &str) -> Option<String> {
+        let outer = inner_classes
+            .iter()
+            .find(|class| class.inner == name)
+            .and_then(|class| class.outer.clone());
+        if outer.is_some() || name != self.name {
+            return outer;
+        }
+        // Local and anonymous classes have no outer class in InnerClasses
+        let enclosing_method = self
+            .attributes
+            .iter()
+            .find(|attr| attr.name == "EnclosingMethod")?;
+        let index = BufferReader::new(&enclosing_method.info).read_u16().ok()?;
+        self.constants.class_name_at(index).ok()
+    }
+}
+
+// The given class name with `old` renamed to `new`. Array classes are named by their descriptor.
+fn rename_class(name: &str, old: &str, new: &str) -> String {
+    if name == old {
+        new.to_string()
+    } else if name.starts_with('[') {
+        rename_in_descriptor(name, old, new)
+    } else {
+        name.to_string()
+    }
+}
+
+// The given field or method descriptor with every `Lold;` replaced by `Lnew;`
+fn rename_in_descriptor(descriptor: &str, old: &str, new: &str) -> String {
+    let mut renamed = String::with_capacity(descriptor.len());
+    let mut rest = descriptor;
+    // Class names run from an L to the next semicolon, and may themselves contain an L
+    while let Some(start) = rest.find('L') {
+        renamed.push_str(&rest[..=start]);
+        rest = &rest[start + 1..];
+        let end = rest.find(';').unwrap_or(rest.len());
+        let name = &rest[..end];
+        renamed.push_str(if name == old { new } else { name });
+        rest = &rest[end..];
+    }
+    renamed.push_str(rest);
+    renamed
+}
+
+// The types in a field or method descriptor, or none if it is invalid. Void is not a type.
+fn descriptor_types(descriptor: &str) -> Vec<FieldType> {
+    let mut rest = match descriptor.strip_prefix('(') {
+        Some(rest) => rest,
+        None => return FieldType::parse(descriptor).into_iter().collect(),
+    };
+    let mut types = Vec::new();
+    while let Some((field_type, tail)) = FieldType::parse_prefix(rest) {
+        types.push(field_type);
+        rest = tail;
+    }
+    match rest.strip_prefix(')') {
+        Some("V") => types,
+        Some(return_type) => match FieldType::parse(return_type) {
+            Ok(field_type) => {
+                types.push(field_type);
+                types
+            }
+            Err(_) => Vec::new(),
+        },
+        None => Vec::new(),
+    }
+}
+
+// A labelled node of the tree view, drawn with its children below it
+struct TreeNode {
+    label: String,
+    children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    // The label goes on the current line; the children are drawn after the given prefix, which
+    // continues the vertical lines of the ancestors
+    fn render(&self, out: &mut String, prefix: &str) {
+        out.push_str(&self.label);
+        out.push('\n');
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            out.push_str(prefix);
+            out.push_str(if last { "└── " } else { "├── " });
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            child.render(out, &child_prefix);
+        }
+    }
 }
 
 impl fmt::Display for ClassFile {
@@ -38,6 +666,135 @@ impl fmt::Display for ClassFile {
         for method in self.methods.iter() {
             writeln!(f, "  - {}", method)?;
         }
+        writeln!(f, "attributes:")?;
+        for attribute in self.attributes.iter() {
+            writeln!(f, "  - {}", attribute)?;
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::attribute::Attribute;
+    use crate::c_pool::ConstantPoolEntry;
+    use crate::class_file::ClassFile;
+
+    fn class(name: &str, superclass: &str, interfaces: &[&str]) -> ClassFile {
+        ClassFile {
+            name: name.to_string(),
+            superclass: superclass.to_string(),
+            interfaces: interfaces.iter().map(|i| i.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn subtypes_are_found_through_the_resolver() {
+        let child = class("Child", "Base", &[]);
+        let mut resolver = |name: &str| match name {
+            "Base" => Some(class("Base", "java/lang/Object", &["java/io/Serializable"])),
+            _ => None,
+        };
+
+        assert!(child.is_subtype_of("Child", &mut resolver));
+        assert!(child.is_subtype_of("Base", &mut resolver));
+        assert!(child.is_subtype_of("java/io/Serializable", &mut resolver));
+        assert!(child.is_subtype_of("java/lang/Object", &mut resolver));
+        assert!(!child.is_subtype_of("java/lang/Cloneable", &mut resolver));
+    }
+
+    #[test]
+    fn cyclic_hierarchies_terminate() {
+        let a = class("A", "B", &[]);
+        let mut resolver = |name: &str| match name {
+            "A" => Some(class("A", "B", &[])),
+            "B" => Some(class("B", "A", &[])),
+            _ => None,
+        };
+
+        assert!(!a.is_subtype_of("C", &mut resolver));
+    }
+    #[test]
+    fn top_level_name_follows_inner_classes() {
+        assert_eq!(
+            "com/foo/Outer",
+            class("com/foo/Outer$Inner", "", &[]).top_level_name()
+        );
+
+        // A top-level class with a $ in its name, enclosing a local class
+        let mut local = class("a/My$Class$1Local", "", &[]);
+        local
+            .constants
+            .add(ConstantPoolEntry::Utf8("a/My$Class$1Local".to_string()))
+            .unwrap();
+        local
+            .constants
+            .add(ConstantPoolEntry::ClassReference(1))
+            .unwrap();
+        local
+            .constants
+            .add(ConstantPoolEntry::Utf8("a/My$Class".to_string()))
+            .unwrap();
+        local
+            .constants
+            .add(ConstantPoolEntry::ClassReference(3))
+            .unwrap();
+        local.attributes = vec![
+            Attribute {
+                name: "InnerClasses".to_string(),
+                info: vec![0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+            },
+            Attribute {
+                name: "EnclosingMethod".to_string(),
+                info: vec![0x00, 0x04, 0x00, 0x00],
+            },
+        ];
+        assert_eq!("a/My$Class", local.top_level_name());
+    }
+
+    #[test]
+    fn shared_entries_are_kept_when_renaming() {
+        let mut class = class("A", "java/lang/Object", &[]);
+        for entry in [
+            ConstantPoolEntry::Utf8("java/lang/Object".to_string()),
+            ConstantPoolEntry::ClassReference(1),
+            ConstantPoolEntry::StringReference(1),
+            ConstantPoolEntry::Utf8("Ljava/lang/Object;".to_string()),
+            ConstantPoolEntry::Utf8("f".to_string()),
+            ConstantPoolEntry::NameAndTypeDescriptor(5, 4),
+        ] {
+            class.constants.add(entry).unwrap();
+        }
+        // The generic signature of the class uses the descriptor of the field as well
+        class.attributes = vec![Attribute {
+            name: "Signature".to_string(),
+            info: vec![0x00, 0x04],
+        }];
+
+        class.rename("java/lang/Object", "shaded/Object").unwrap();
+
+        assert_eq!(Ok("shaded/Object".to_string()), class.constants.text_of(2));
+        assert_eq!(
+            Ok("java/lang/Object".to_string()),
+            class.constants.text_of(3)
+        );
+        assert_eq!(
+            Ok("f: Lshaded/Object;".to_string()),
+            class.constants.text_of(6)
+        );
+        assert_eq!(
+            Ok("Ljava/lang/Object;".to_string()),
+            class.constants.text_of(4)
+        );
+        assert_eq!(8, class.constants.slot_count());
+
+        // The new entries are only used where they are renamed, so they are rewritten in place
+        class.rename("shaded/Object", "other/Object").unwrap();
+        assert_eq!(Ok("other/Object".to_string()), class.constants.text_of(2));
+        assert_eq!(8, class.constants.slot_count());
+    }
+}
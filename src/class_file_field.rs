@@ -1,17 +1,140 @@
-use std::fmt;
-use std::fmt::Formatter;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
 
+use crate::annotation::TypeAnnotation;
+use crate::attribute::Attribute;
+use crate::c_pool::{ConstantPool, ConstantPoolEntry, ConstantPoolError};
 use crate::field_flags::FieldFlags;
+use crate::raw_flags::RawFlags;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub struct ClassFileField {
     pub flags: FieldFlags,
+    // Access flag bits that no flag is defined for, see `RawFlags`
+    pub reserved_flags: u16,
     pub name: String,
     pub type_descriptor: String,
     pub constant_value: Option<FieldConstantValue>,
+    pub attributes: Vec<Attribute>,
+    // Start and end offsets of the field in the bytes it was read from, if any
+    pub byte_range: Option<(usize, usize)>,
+    // Content of the RuntimeVisibleTypeAnnotations attribute
+    pub visible_type_annotations: Vec<TypeAnnotation>,
+    // Content of the RuntimeInvisibleTypeAnnotations attribute
+    pub invisible_type_annotations: Vec<TypeAnnotation>,
 }
 
-#[derive(Debug, PartialEq, strum_macros::Display)]
+impl RawFlags for ClassFileField {
+    fn flag_bits(&self) -> u16 {
+        self.flags.bits()
+    }
+
+    fn reserved_bits(&self) -> u16 {
+        self.reserved_flags
+    }
+}
+
+impl ClassFileField {
+    // A public static final int field whose ConstantValue refers to an Integer entry of the pool,
+    // reusing an existing one. Fails if the pool is full
+    pub fn from_i32(
+        name: &str,
+        value: i32,
+        constants: &mut ConstantPool,
+    ) -> Result<ClassFileField, ConstantPoolError> {
+        let index = constants.add_or_get(ConstantPoolEntry::Integer(value))?;
+        ClassFileField::constant(name, "I", FieldConstantValue::Int(value), index, constants)
+    }
+
+    // A public static final long field with the given value, like `from_i32`
+    pub fn from_i64(
+        name: &str,
+        value: i64,
+        constants: &mut ConstantPool,
+    ) -> Result<ClassFileField, ConstantPoolError> {
+        let index = constants.add_or_get(ConstantPoolEntry::Long(value))?;
+        ClassFileField::constant(name, "J", FieldConstantValue::Long(value), index, constants)
+    }
+
+    // A public static final float field with the given value, like `from_i32`
+    pub fn from_f32(
+        name: &str,
+        value: f32,
+        constants: &mut ConstantPool,
+    ) -> Result<ClassFileField, ConstantPoolError> {
+        let index = constants.add_or_get(ConstantPoolEntry::Float(value))?;
+        ClassFileField::constant(
+            name,
+            "F",
+            FieldConstantValue::Float(value),
+            index,
+            constants,
+        )
+    }
+
+    // A public static final double field with the given value, like `from_i32`
+    pub fn from_f64(
+        name: &str,
+        value: f64,
+        constants: &mut ConstantPool,
+    ) -> Result<ClassFileField, ConstantPoolError> {
+        let index = constants.add_or_get(ConstantPoolEntry::Double(value))?;
+        ClassFileField::constant(
+            name,
+            "D",
+            FieldConstantValue::Double(value),
+            index,
+            constants,
+        )
+    }
+
+    // A public static final String field with the given value, like from_i32
+    pub fn from_str(
+        name: &str,
+        value: &str,
+        constants: &mut ConstantPool,
+    ) -> Result<ClassFileField, ConstantPoolError> {
+        let text = constants.add_or_get(ConstantPoolEntry::Utf8(value.to_string()))?;
+        let index = constants.add_or_get(ConstantPoolEntry::StringReference(text))?;
+        ClassFileField::constant(
+            name,
+            "Ljava/lang/String;",
+            FieldConstantValue::String(value.to_string()),
+            index,
+            constants,
+        )
+    }
+
+    // A constant field whose value is at the given index, with its name, descriptor and the name
+    // of the attribute in the pool as well
+    fn constant(
+        name: &str,
+        descriptor: &str,
+        value: FieldConstantValue,
+        index: u16,
+        constants: &mut ConstantPool,
+    ) -> Result<ClassFileField, ConstantPoolError> {
+        for text in [name, descriptor, "ConstantValue"] {
+            constants.add_or_get(ConstantPoolEntry::Utf8(text.to_string()))?;
+        }
+        Ok(ClassFileField {
+            flags: FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL,
+            name: name.to_string(),
+            type_descriptor: descriptor.to_string(),
+            constant_value: Some(value),
+            attributes: vec![Attribute {
+                name: "ConstantValue".to_string(),
+                info: index.to_be_bytes().to_vec(),
+            }],
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Debug, strum_macros::Display)]
 pub enum FieldConstantValue {
     Int(i32),
     Float(f32),
@@ -20,6 +143,20 @@ pub enum FieldConstantValue {
     String(String),
 }
 
+impl PartialEq for FieldConstantValue {
+    fn eq(&self, other: &Self) -> bool {
+        use FieldConstantValue::*;
+        match (self, other) {
+            (Int(a), Int(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Long(a), Long(b)) => a == b,
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (String(a), String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for ClassFileField {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
@@ -28,4 +165,4 @@ impl fmt::Display for ClassFileField {
             self.flags, self.name, self.type_descriptor, self.constant_value,
         )
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,239 @@
-use std::fmt;
-use std::fmt::Formatter;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
 
+use crate::annotation::{Annotation, DeprecationInfo, TypeAnnotation};
 use crate::attribute::Attribute;
+use crate::c_pool::ConstantPool;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::code_attribute::CodeAttribute;
+use crate::field_type::FieldType;
+use crate::instruction::{decode, Opcode, Operand};
+use crate::invocation_kind::InvocationKind;
 use crate::method_flags::MethodFlags;
+use crate::raw_flags::RawFlags;
 
 #[derive(Debug, Default, PartialEq)]
 pub struct ClassFileMethod {
     pub flags: MethodFlags,
+    // Access flag bits that no flag is defined for, see `RawFlags`
+    pub reserved_flags: u16,
     pub name: String,
     pub type_descriptor: String,
     pub attributes: Vec<Attribute>,
+    pub code: Option<CodeAttribute>,
+    // Start and end offsets of the method in the bytes it was read from, if any
+    pub byte_range: Option<(usize, usize)>,
+    // Content of the RuntimeVisibleAnnotations attribute
+    pub visible_annotations: Vec<Annotation>,
+    // Content of the RuntimeInvisibleAnnotations attribute
+    pub invisible_annotations: Vec<Annotation>,
+    // Content of the RuntimeVisibleParameterAnnotations attribute, for each parameter
+    pub visible_parameter_annotations: Vec<Vec<Annotation>>,
+    // Content of the RuntimeInvisibleParameterAnnotations attribute, for each parameter
+    pub invisible_parameter_annotations: Vec<Vec<Annotation>>,
+    // Content of the RuntimeVisibleTypeAnnotations attribute
+    pub visible_type_annotations: Vec<TypeAnnotation>,
+    // Content of the RuntimeInvisibleTypeAnnotations attribute
+    pub invisible_type_annotations: Vec<TypeAnnotation>,
+}
+
+impl RawFlags for ClassFileMethod {
+    fn flag_bits(&self) -> u16 {
+        self.flags.bits()
+    }
+
+    fn reserved_bits(&self) -> u16 {
+        self.reserved_flags
+    }
+}
+
+impl ClassFileMethod {
+    // Whether the method has no body, to be implemented by subclasses
+    pub fn is_abstract(&self) -> bool {
+        self.flags.contains(MethodFlags::ABSTRACT)
+    }
+
+    // Whether the method is implemented outside of Java, and so has no code
+    pub fn is_native(&self) -> bool {
+        self.flags.contains(MethodFlags::NATIVE)
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.flags.contains(MethodFlags::STATIC)
+    }
+
+    pub fn is_synchronized(&self) -> bool {
+        self.flags.contains(MethodFlags::SYNCHRONIZED)
+    }
+
+    // Whether the last parameter takes a variable number of arguments
+    pub fn is_varargs(&self) -> bool {
+        self.flags.contains(MethodFlags::VARARGS)
+    }
+
+    // Whether this is a bridge method generated by the compiler, e.g. for generic overrides.
+    pub fn is_bridge(&self) -> bool {
+        self.flags.contains(MethodFlags::BRIDGE)
+    }
+
+    // Whether this method does not appear in the source code
+    pub fn is_synthetic(&self) -> bool {
+        self.flags.contains(MethodFlags::SYNTHETIC)
+    }
+
+    // The instruction that calls this method, given whether its class is an interface
+    pub fn expected_invocation(&self, owner_is_interface: bool) -> InvocationKind {
+        if self.is_static() {
+            InvocationKind::Static
+        } else if self.name == "<init>" || self.flags.contains(MethodFlags::PRIVATE) {
+            InvocationKind::Special
+        } else if owner_is_interface {
+            InvocationKind::Interface
+        } else {
+            InvocationKind::Virtual
+        }
+    }
+
+    // The details of the @Deprecated annotation of the method, if it has one
+    pub fn deprecation_info(&self) -> Option<DeprecationInfo> {
+        DeprecationInfo::from_annotations(
+            self.visible_annotations
+                .iter()
+                .chain(&self.invisible_annotations),
+        )
+    }
+
+    // Number of parameters declared by the descriptor. Longs and doubles count once.
+    pub fn parameter_count(&self) -> Result<usize> {
+        self.parameter_types().map(|parameters| parameters.len())
+    }
+
+    // Number of local variable slots of the arguments, including `this`. Longs and doubles take two
+    pub fn argument_slot_count(&self) -> Result<usize> {
+        let receiver = if self.is_static() { 0 } else { 1 };
+        let parameters: usize = self
+            .parameter_types()?
+            .iter()
+            .map(|parameter| if parameter.is_wide() { 2 } else { 1 })
+            .sum();
+        Ok(receiver + parameters)
+    }
+
+    // Whether the descriptor has a void return type
+    pub fn returns_void(&self) -> Result<bool> {
+        let (_, return_type) = self.split_descriptor()?;
+        Ok(return_type == "V")
+    }
+
+    // The method as declared in Java source without parameter names, like `public static int
+    // add(int, int)`
+    pub fn display_signature(&self) -> Result<String> {
+        let mut parameters: Vec<String> = self
+            .parameter_types()?
+            .iter()
+            .map(|parameter| parameter.to_string())
+            .collect();
+        if self.is_varargs() {
+            if let Some(last) = parameters.last_mut() {
+                if let Some(element) = last.strip_suffix("[]") {
+                    *last = format!("{}...", element);
+                }
+            }
+        }
+
+        let mut signature = self.flags.to_string();
+        if !signature.is_empty() {
+            signature.push(' ');
+        }
+        if self.name != "<init>" && self.name != "<clinit>" {
+            let (_, return_type) = self.split_descriptor()?;
+            match return_type {
+                "V" => signature.push_str("void"),
+                _ => signature.push_str(&FieldType::parse(return_type)?.to_string()),
+            }
+            signature.push(' ');
+        }
+        signature.push_str(&format!("{}({})", self.name, parameters.join(", ")));
+        Ok(signature)
+    }
+
+    // Number of instructions in the code of the method, or 0 if it has none
+    pub fn instruction_count(&self) -> Result<usize> {
+        match &self.code {
+            Some(code) => decode(&code.code).map(|instructions| instructions.len()),
+            None => Ok(0),
+        }
+    }
+
+    // The class caught by each exception handler, in the order of the table, or None for those
+    // catching everything
+    pub fn caught_exception_types(&self, constants: &ConstantPool) -> Result<Vec<Option<String>>> {
+        let handlers = match &self.code {
+            Some(code) => &code.exception_table,
+            None => return Ok(Vec::new()),
+        };
+        handlers
+            .iter()
+            .map(|handler| match handler.catch_type {
+                0 => Ok(None),
+                index => Ok(Some(constants.class_name_at(index)?)),
+            })
+            .collect()
+    }
+
+    // Cyclomatic complexity: one, plus one per conditional branch, non-default switch case and
+    // exception handler
+    pub fn cyclomatic_complexity(&self) -> Result<usize> {
+        let code = match &self.code {
+            Some(code) => code,
+            None => return Ok(1),
+        };
+        let decisions: usize = decode(&code.code)?
+            .iter()
+            .map(|instruction| match &instruction.operand {
+                Operand::TableSwitch { offsets, .. } => offsets.len(),
+                Operand::LookupSwitch { pairs, .. } => pairs.len(),
+                Operand::Branch(_) => match instruction.opcode {
+                    Opcode::Goto | Opcode::GotoW | Opcode::Jsr | Opcode::JsrW => 0,
+                    _ => 1,
+                },
+                _ => 0,
+            })
+            .sum();
+        Ok(1 + decisions + code.exception_table.len())
+    }
+
+    // Splits the descriptor into the text between the parentheses and the return type
+    fn split_descriptor(&self) -> Result<(&str, &str)> {
+        self.type_descriptor
+            .strip_prefix('(')
+            .and_then(|rest| rest.split_once(')'))
+            .filter(|(_, return_type)| !return_type.is_empty())
+            .ok_or_else(|| self.invalid_descriptor())
+    }
+
+    // The type of each parameter declared by the descriptor
+    fn parameter_types(&self) -> Result<Vec<FieldType>> {
+        let (mut parameters, _) = self.split_descriptor()?;
+        let mut types = Vec::new();
+        while !parameters.is_empty() {
+            let (field_type, rest) =
+                FieldType::parse_prefix(parameters).ok_or_else(|| self.invalid_descriptor())?;
+            types.push(field_type);
+            parameters = rest;
+        }
+        Ok(types)
+    }
+
+    fn invalid_descriptor(&self) -> ClassReaderError {
+        ClassReaderError::InvalidClassData(format!(
+            "invalid method descriptor: {}",
+            self.type_descriptor
+        ))
+    }
 }
 
 impl fmt::Display for ClassFileMethod {
@@ -20,4 +244,73 @@ impl fmt::Display for ClassFileMethod {
             self.flags, self.name, self.type_descriptor, self.attributes,
         )
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::class_file_method::ClassFileMethod;
+    use crate::method_flags::MethodFlags;
+
+    fn method(descriptor: &str) -> ClassFileMethod {
+        ClassFileMethod {
+            type_descriptor: descriptor.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn descriptors_give_parameter_count_and_return_kind() {
+        assert_eq!(Ok(2), method("(JD)V").parameter_count());
+        assert_eq!(Ok(true), method("(JD)V").returns_void());
+        assert_eq!(
+            Ok(3),
+            method("([[ILjava/lang/String;Z)[Ljava/lang/Object;").parameter_count()
+        );
+        assert_eq!(Ok(false), method("()I").returns_void());
+        assert!(method("(Ljava/lang/String)V").parameter_count().is_err());
+        assert!(method("(I)").returns_void().is_err());
+    }
+    #[test]
+    fn wide_arguments_and_the_receiver_take_slots() {
+        assert_eq!(Ok(4), method("(JI)V").argument_slot_count());
+        let static_method = ClassFileMethod {
+            flags: MethodFlags::STATIC,
+            ..method("(JI)V")
+        };
+        assert_eq!(Ok(3), static_method.argument_slot_count());
+        assert_eq!(Ok(2), method("([D)V").argument_slot_count());
+    }
+    #[test]
+    fn signatures_are_rendered_as_in_source() {
+        let add = ClassFileMethod {
+            flags: MethodFlags::PUBLIC | MethodFlags::STATIC,
+            name: "add".to_string(),
+            ..method("(II)I")
+        };
+        assert_eq!(
+            Ok("public static int add(int, int)".to_string()),
+            add.display_signature()
+        );
+        let init = ClassFileMethod {
+            flags: MethodFlags::PROTECTED,
+            name: "<init>".to_string(),
+            ..method("([[JLjava/lang/String;)V")
+        };
+        assert_eq!(
+            Ok("protected <init>(long[][], java.lang.String)".to_string()),
+            init.display_signature()
+        );
+        let format = ClassFileMethod {
+            flags: MethodFlags::STATIC | MethodFlags::VARARGS | MethodFlags::SYNTHETIC,
+            name: "format".to_string(),
+            ..method("([Ljava/lang/Object;)V")
+        };
+        assert_eq!(
+            Ok("static void format(java.lang.Object...)".to_string()),
+            format.display_signature()
+        );
+        assert!(method("(Q)V").display_signature().is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::class_reader_error::ClassReaderError;
 use crate::class_reader_error::Result;
 
-#[derive(Debug, PartialEq, Default, strum_macros::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, strum_macros::Display)]
 #[allow(dead_code)]
 pub enum ClassFileVersion {
     Jdk1_1,
@@ -12,6 +12,16 @@ pub enum ClassFileVersion {
     Jdk6,
     #[default]
     Jdk7,
+    Jdk8,
+    Jdk9,
+    Jdk10,
+    Jdk11,
+    Jdk12,
+    Jdk13,
+    Jdk14,
+    Jdk15,
+    Jdk16,
+    Jdk17,
 }
 
 impl ClassFileVersion {
@@ -24,6 +34,16 @@ impl ClassFileVersion {
             49 => Ok(ClassFileVersion::Jdk1_5),
             50 => Ok(ClassFileVersion::Jdk6),
             51 => Ok(ClassFileVersion::Jdk7),
+            52 => Ok(ClassFileVersion::Jdk8),
+            53 => Ok(ClassFileVersion::Jdk9),
+            54 => Ok(ClassFileVersion::Jdk10),
+            55 => Ok(ClassFileVersion::Jdk11),
+            56 => Ok(ClassFileVersion::Jdk12),
+            57 => Ok(ClassFileVersion::Jdk13),
+            58 => Ok(ClassFileVersion::Jdk14),
+            59 => Ok(ClassFileVersion::Jdk15),
+            60 => Ok(ClassFileVersion::Jdk16),
+            61 => Ok(ClassFileVersion::Jdk17),
             _ => Err(ClassReaderError::UnsupportedVersion(major, minor)),
         }
     }
@@ -1,52 +1,174 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "zip")]
+use core::ops::ControlFlow;
+#[cfg(feature = "std")]
 use std::{fs::File, io::Read, path::Path};
 
-use result::prelude::*;
-
+use crate::annotation::{read_annotations, read_parameter_annotations, read_type_annotations};
 use crate::attribute::Attribute;
 use crate::class_file_field::{ClassFileField, FieldConstantValue};
 use crate::class_file_method::ClassFileMethod;
 use crate::class_reader_error::ClassReaderError::InvalidClassData;
+use crate::code_attribute::{CodeAttribute, ExceptionTableEntry};
 use crate::field_flags::FieldFlags;
 use crate::method_flags::MethodFlags;
+use crate::parse_warning::{self, ReadOutcome};
+use crate::reader_options::ReaderOptions;
 use crate::{
     buffer::BufferReader,
+    c_pool::{ConstantPool, ConstantPoolEntry, ConstantPoolError},
     class_access_flags::ClassAccessFlags,
     class_file::ClassFile,
     class_file_version::ClassFileVersion,
     class_reader_error::{ClassReaderError, Result},
-    c_pool::ConstantPoolEntry,
 };
 
 struct ClassFileReader<'a> {
     buffer: BufferReader<'a>,
+    options: ReaderOptions,
     class_file: ClassFile,
+    // Whether Code attributes are left undecoded, in their raw form only
+    skip_code: bool,
+    // Byte ranges read so far with what they hold, only collected for annotated dumps
+    labels: Option<Vec<(usize, usize, String)>>,
 }
 
 impl<'a> ClassFileReader<'a> {
-    fn new(data: &[u8]) -> ClassFileReader {
+    fn new(data: &'a [u8], options: ReaderOptions) -> ClassFileReader<'a> {
+        ClassFileReader::new_at(data, 0, options)
+    }
+
+    fn new_at(data: &'a [u8], base_offset: usize, options: ReaderOptions) -> ClassFileReader<'a> {
         ClassFileReader {
-            buffer: BufferReader::new(data),
+            buffer: BufferReader::new_at(data, base_offset),
+            options,
             class_file: Default::default(),
+            skip_code: false,
+            labels: None,
         }
     }
 
     fn read(mut self) -> Result<ClassFile> {
+        self.parse()?;
+        Ok(self.class_file)
+    }
+
+    fn parse(&mut self) -> Result<()> {
+        self.labelled("magic", Self::check_magic_number)?;
+        self.labelled("version", Self::read_version)?;
+        self.labelled("constant pool", Self::read_constants)?;
+        self.labelled("access flags", Self::read_access_flags)?;
+        self.class_file.name =
+            self.labelled("this_class", |r| r.read_class_reference("this_class"))?;
+        self.class_file.superclass =
+            self.labelled("super_class", |r| r.read_class_reference("super_class"))?;
+        self.labelled("interfaces", Self::read_interfaces)?;
+        self.labelled("fields", Self::read_fields)?;
+        self.labelled("methods", Self::read_methods)?;
+        self.class_file.attributes = self.labelled("attributes", Self::read_raw_attributes)?;
+        self.read_source_debug_extension()?;
+        self.class_file.visible_annotations = self.decode_attribute(
+            &self.class_file.attributes,
+            "RuntimeVisibleAnnotations",
+            read_annotations,
+        )?;
+        self.class_file.invisible_annotations = self.decode_attribute(
+            &self.class_file.attributes,
+            "RuntimeInvisibleAnnotations",
+            read_annotations,
+        )?;
+        self.class_file.visible_type_annotations = self.decode_attribute(
+            &self.class_file.attributes,
+            "RuntimeVisibleTypeAnnotations",
+            read_type_annotations,
+        )?;
+        self.class_file.invisible_type_annotations = self.decode_attribute(
+            &self.class_file.attributes,
+            "RuntimeInvisibleTypeAnnotations",
+            read_type_annotations,
+        )?;
+        self.check_trailing_data()
+    }
+
+    // Runs one step of the parsing, labelling the bytes it consumed when they are collected
+    fn labelled<T>(&mut self, label: &str, step: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let start = self.buffer.position();
+        let value = step(self)?;
+        self.mark(start, || label.to_string());
+        Ok(value)
+    }
+
+    fn mark(&mut self, start: usize, label: impl FnOnce() -> String) {
+        let end = self.buffer.position();
+        if let Some(labels) = &mut self.labels {
+            labels.push((start, end, label()));
+        }
+    }
+
+    fn read_with_warnings(mut self) -> Result<ReadOutcome> {
+        self.parse()?;
+        let warnings = parse_warning::check(&self.class_file, self.buffer.remaining());
+        Ok(ReadOutcome {
+            class: self.class_file,
+            warnings,
+        })
+    }
+
+    fn read_constants_only(mut self) -> Result<(ClassFileVersion, ConstantPool)> {
+        self.check_magic_number()?;
+        self.read_version()?;
+        self.read_constants()?;
+        Ok((self.class_file.version, self.class_file.constants))
+    }
+
+    fn read_name(mut self) -> Result<String> {
         self.check_magic_number()?;
         self.read_version()?;
         self.read_constants()?;
         self.read_access_flags()?;
-        self.class_file.name = self.read_class_reference()?;
-        self.class_file.superclass = self.read_class_reference()?;
-        self.read_interfaces()?;
-        self.read_fields()?;
-        self.read_methods()?;
+        self.read_class_reference("this_class")
+    }
 
-        Ok(self.class_file)
+    // The attribute has no length prefix of its own: the whole body is the modified UTF-8 text
+    fn read_source_debug_extension(&mut self) -> Result<()> {
+        let lossy = self.options.lossy_strings;
+        self.class_file.source_debug_extension = self
+            .class_file
+            .attributes
+            .iter()
+            .find(|attr| attr.name == "SourceDebugExtension")
+            .map(|attr| {
+                let mut text = BufferReader::new(&attr.info);
+                if lossy {
+                    text.read_utf8_lossy(attr.info.len())
+                } else {
+                    text.read_utf8(attr.info.len())
+                }
+            })
+            .transpose()?;
+        Ok(())
+    }
+
+    fn check_trailing_data(&self) -> Result<()> {
+        if !self.options.allow_trailing_data && self.buffer.remaining() > 0 {
+            Err(ClassReaderError::InvalidClassData(format!(
+                "{} unexpected bytes after the end of the class",
+                self.buffer.remaining()
+            )))
+        } else {
+            Ok(())
+        }
     }
 
     fn check_magic_number(&mut self) -> Result<()> {
         match self.buffer.read_u32() {
             Ok(0xCAFEBABE) => Ok(()),
+            Ok(0xBEBAFECA) => Err(ClassReaderError::InvalidClassData(
+                "invalid magic number 0xBEBAFECA, bytes may be byte-swapped".to_owned(),
+            )),
             Ok(_) => Err(ClassReaderError::InvalidClassData(
                 "invalid magic number".to_owned(),
             )),
@@ -59,16 +181,46 @@ impl<'a> ClassFileReader<'a> {
         let major_version = self.buffer.read_u16()?;
 
         self.class_file.version = ClassFileVersion::from(major_version, minor_version)?;
-        Ok(())
+        self.class_file.minor_version = minor_version;
+        match self.options.current_version {
+            Some(current)
+                if self.options.strict
+                    && self.class_file.requires_preview_features()
+                    && current != self.class_file.version =>
+            {
+                Err(ClassReaderError::UnsupportedVersion(
+                    major_version,
+                    minor_version,
+                ))
+            }
+            _ => Ok(()),
+        }
     }
 
     fn read_constants(&mut self) -> Result<()> {
-        let constants_count = self.buffer.read_u16()? - 1;
+        let constants_count = match self.buffer.read_u16()?.checked_sub(1) {
+            Some(count) => count,
+            None => {
+                return Err(ClassReaderError::InvalidClassData(
+                    "invalid constant pool count: 0".to_string(),
+                ))
+            }
+        };
+        if constants_count > self.options.max_constant_pool_entries {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "constant pool has {} entries, more than the limit of {}",
+                constants_count, self.options.max_constant_pool_entries
+            )));
+        }
+        // Every constant takes at least a tag and a two bytes payload
+        check_count_fits(&self.buffer, "constants", constants_count, 3)?;
+        self.class_file.constants = ConstantPool::with_capacity(constants_count as usize);
         let mut i = 0;
         while i < constants_count {
+            let (start, index) = (self.buffer.position(), i + 1);
             let tag = self.buffer.read_u8()?;
             let constant = match tag {
-                1 => self.read_utf8_constant()?,
+                1 => self.read_utf8_constant(index)?,
                 3 => self.read_int_constant()?,
                 4 => self.read_float_constant()?,
                 5 => {
@@ -85,27 +237,53 @@ impl<'a> ClassFileReader<'a> {
                 10 => self.read_method_reference_constant()?,
                 11 => self.read_interface_method_reference_constant()?,
                 12 => self.read_name_and_type_constant()?,
+                15 => self.read_method_handle_constant()?,
+                16 => self.read_method_type_constant()?,
+                17 => self.read_dynamic_constant()?,
+                18 => self.read_invoke_dynamic_constant()?,
+                19 => ConstantPoolEntry::Module(self.buffer.read_u16()?),
+                20 => ConstantPoolEntry::Package(self.buffer.read_u16()?),
                 _ => {
-                    println!("Constant {} is of type {}", i, tag);
                     return Err(ClassReaderError::InvalidClassData(format!(
                         "Unknown constant type: 0x{:X}",
                         tag
                     )));
                 }
             };
-            self.class_file.constants.add(constant);
+            self.class_file.constants.add(constant)?;
+            self.mark(start, || format!("constant #{}", index));
 
             i += 1;
         }
+        self.class_file
+            .constants
+            .set_lenient(self.options.lenient_references);
 
         Ok(())
     }
 
-    fn read_utf8_constant(&mut self) -> Result<ConstantPoolEntry> {
-        let len = self.buffer.read_u16()?;
-        self.buffer
-            .read_utf8(len as usize)
-            .map(ConstantPoolEntry::Utf8)
+    fn read_utf8_constant(&mut self, index: u16) -> Result<ConstantPoolEntry> {
+        let len = self.buffer.read_u16()? as usize;
+        if !self.buffer.can_read(len as u64) {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "utf8 constant has length {}, but only {} bytes remain",
+                len,
+                self.buffer.remaining()
+            )));
+        }
+        let bytes = self.buffer.read_bytes(len)?;
+        let mut text = BufferReader::new(bytes);
+        let text = if self.options.lossy_strings {
+            text.read_utf8_lossy(len)?
+        } else {
+            text.read_utf8(len)?
+        };
+        if self.options.keep_raw_utf8 {
+            self.class_file
+                .constants
+                .set_raw_utf8(index, Vec::from(bytes));
+        }
+        Ok(ConstantPoolEntry::Utf8(text))
     }
 
     fn read_int_constant(&mut self) -> Result<ConstantPoolEntry> {
@@ -170,26 +348,59 @@ impl<'a> ClassFileReader<'a> {
         ))
     }
 
+    fn read_method_handle_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let reference_kind = self.buffer.read_u8()?;
+        let reference = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::MethodHandle(reference_kind, reference))
+    }
+
+    fn read_method_type_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let descriptor = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::MethodType(descriptor))
+    }
+
+    fn read_dynamic_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let bootstrap_method = self.buffer.read_u16()?;
+        let name_and_type = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::Dynamic(bootstrap_method, name_and_type))
+    }
+
+    fn read_invoke_dynamic_constant(&mut self) -> Result<ConstantPoolEntry> {
+        let bootstrap_method = self.buffer.read_u16()?;
+        let name_and_type = self.buffer.read_u16()?;
+        Ok(ConstantPoolEntry::InvokeDynamic(
+            bootstrap_method,
+            name_and_type,
+        ))
+    }
+
+    // Bits that no flag is defined for are reserved. They are kept apart rather than rejected,
+    // as the JVM ignores them, and obfuscators sometimes set them.
     fn read_access_flags(&mut self) -> Result<()> {
-        let num = self.buffer.read_u16()?;
-        match ClassAccessFlags::from_bits(num) {
-            Some(flags) => {
-                self.class_file.flags = flags;
-                Ok(())
-            }
-            None => Err(ClassReaderError::InvalidClassData(format!(
-                "invalid class flags: {}",
-                num
-            ))),
-        }
+        let bits = self.buffer.read_u16()?;
+        self.class_file.flags = ClassAccessFlags::from_bits_truncate(bits);
+        self.class_file.reserved_flags = bits & !ClassAccessFlags::all().bits();
+        Ok(())
     }
 
-    fn read_class_reference(&mut self) -> Result<String> {
-        let super_constant_idx = self.buffer.read_u16()?;
-        if super_constant_idx == 0 {
-            Ok(String::from(""))
-        } else {
-            self.read_string_reference(super_constant_idx)
+    // Reads the index of a class reference and resolves it to the class name. The name of the
+    // item being read, like this_class, is only used in the error message.
+    fn read_class_reference(&mut self, what: &str) -> Result<String> {
+        let class_constant_idx = self.buffer.read_u16()?;
+        if class_constant_idx == 0 {
+            return Ok(String::from(""));
+        }
+        match self.class_file.constants.class_name_at(class_constant_idx) {
+            Ok(name) => Ok(name),
+            Err(ConstantPoolError::UnexpectedEntry { index, .. })
+                if index == class_constant_idx =>
+            {
+                Err(ClassReaderError::InvalidClassData(format!(
+                    "{} refers to constant pool entry {}, which is not a class",
+                    what, class_constant_idx
+                )))
+            }
+            Err(err) => Err(err.into()),
         }
     }
 
@@ -202,72 +413,109 @@ impl<'a> ClassFileReader<'a> {
 
     fn read_interfaces(&mut self) -> Result<()> {
         let interfaces_count = self.buffer.read_u16()?;
-        self.class_file.interfaces = (0..interfaces_count)
-            .map(|_| self.read_class_reference())
-            .collect::<Result<Vec<String>>>()?;
+        check_count_fits(&self.buffer, "interfaces", interfaces_count, 2)?;
+        let mut interfaces = Vec::with_capacity(interfaces_count as usize);
+        for _ in 0..interfaces_count {
+            interfaces.push(self.read_class_reference("interface")?);
+        }
+        self.class_file.interfaces = interfaces;
         Ok(())
     }
 
     fn read_fields(&mut self) -> Result<()> {
         let fields_count = self.buffer.read_u16()?;
-        self.class_file.fields = (0..fields_count)
-            .map(|_| self.read_field())
-            .collect::<Result<Vec<ClassFileField>>>()?;
+        check_count_fits(&self.buffer, "fields", fields_count, 8)?;
+        let mut fields = Vec::with_capacity(fields_count as usize);
+        for index in 0..fields_count {
+            check_member_fits(&self.buffer, "fields", fields_count, index as usize)?;
+            let start = self.buffer.position();
+            if let Some(field) = self.read_field()? {
+                fields.push(field);
+            }
+            self.mark(start, || format!("field #{}", index));
+        }
+        self.class_file.fields = fields;
         Ok(())
     }
 
-    fn read_field(&mut self) -> Result<ClassFileField> {
-        let flags = self.read_field_flags()?;
+    // Reads the next field, or skips it when only the public API is wanted and it is not part
+    // of it
+    fn read_field(&mut self) -> Result<Option<ClassFileField>> {
+        let start = self.buffer.position();
+        let (flags, reserved_flags) = self.read_field_flags()?;
         let name_constant_index = self.buffer.read_u16()?;
         let name = self.read_string_reference(name_constant_index)?;
         let type_constant_index = self.buffer.read_u16()?;
         let type_descriptor = self.read_string_reference(type_constant_index)?;
+        if self.options.public_api_only
+            && !flags.intersects(FieldFlags::PUBLIC | FieldFlags::PROTECTED)
+        {
+            skip_raw_attributes(&mut self.buffer)?;
+            return Ok(None);
+        }
 
-        let raw_attributes = self.read_raw_attributes()?;
-        let constant_value = self.extract_constant_value(raw_attributes)?;
+        let attributes = self.read_raw_attributes()?;
+        let constant_value = self.extract_constant_value(&attributes)?;
+        let visible_type_annotations = self.decode_attribute(
+            &attributes,
+            "RuntimeVisibleTypeAnnotations",
+            read_type_annotations,
+        )?;
+        let invisible_type_annotations = self.decode_attribute(
+            &attributes,
+            "RuntimeInvisibleTypeAnnotations",
+            read_type_annotations,
+        )?;
 
-        Ok(ClassFileField {
+        Ok(Some(ClassFileField {
             flags,
+            reserved_flags,
             name,
             type_descriptor,
             constant_value,
-        })
+            attributes,
+            byte_range: Some((start, self.buffer.position())),
+            visible_type_annotations,
+            invisible_type_annotations,
+        }))
     }
 
-    fn read_field_flags(&mut self) -> Result<FieldFlags> {
-        let field_flags_bits = self.buffer.read_u16()?;
-        match FieldFlags::from_bits(field_flags_bits) {
-            Some(flags) => Ok(flags),
-            None => Err(ClassReaderError::InvalidClassData(format!(
-                "invalid field flags: {}",
-                field_flags_bits
-            ))),
-        }
+    fn read_field_flags(&mut self) -> Result<(FieldFlags, u16)> {
+        let bits = self.buffer.read_u16()?;
+        Ok((
+            FieldFlags::from_bits_truncate(bits),
+            bits & !FieldFlags::all().bits(),
+        ))
     }
 
     fn extract_constant_value(
         &self,
-        raw_attributes: Vec<Attribute>,
+        raw_attributes: &[Attribute],
     ) -> Result<Option<FieldConstantValue>> {
         raw_attributes
             .iter()
             .filter(|attr| attr.name == "ConstantValue")
             .map(|attr| {
-                if attr.info.len() != std::mem::size_of::<u16>() {
-                    Err(InvalidClassData(
-                        "invalid attribute of type ConstantValue".to_string(),
-                    ))
-                } else {
-                    let attribute_bytes: &[u8] = &attr.info;
-                    let constant_index = u16::from_be_bytes(attribute_bytes.try_into().unwrap());
+                let attribute_bytes: &[u8] = &attr.info;
+                if let Ok(index_bytes) = attribute_bytes.try_into() {
+                    let constant_index = u16::from_be_bytes(index_bytes);
                     self.class_file
                         .constants
                         .get(constant_index)
                         .map_err(|err| err.into())
                         .and_then(|entry| match entry {
+                            // The value of a String constant is the Utf8 entry it refers to
                             ConstantPoolEntry::StringReference(v) => {
-                                let referred_string = self.read_string_reference(*v)?;
-                                Ok(FieldConstantValue::String(referred_string))
+                                match self.class_file.constants.get(*v)? {
+                                    ConstantPoolEntry::Utf8(s) => {
+                                        Ok(FieldConstantValue::String(s.clone()))
+                                    }
+                                    _ => Err(ConstantPoolError::UnexpectedEntry {
+                                        index: *v,
+                                        expected: "Utf8",
+                                    }
+                                    .into()),
+                                }
                             }
                             ConstantPoolEntry::Integer(v) => Ok(FieldConstantValue::Int(*v)),
                             ConstantPoolEntry::Float(v) => Ok(FieldConstantValue::Float(*v)),
@@ -278,84 +526,507 @@ impl<'a> ClassFileReader<'a> {
                                 v
                             ))),
                         })
+                } else {
+                    Err(InvalidClassData(
+                        "invalid attribute of type ConstantValue".to_string(),
+                    ))
                 }
             })
             .next()
-            .invert()
+            .transpose()
     }
 
     fn read_methods(&mut self) -> Result<()> {
         let methods_count = self.buffer.read_u16()?;
-        self.class_file.methods = (0..methods_count)
-            .map(|_| self.read_method())
-            .collect::<Result<Vec<ClassFileMethod>>>()?;
+        if methods_count > self.options.max_method_count {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "class has {} methods, more than the limit of {}",
+                methods_count, self.options.max_method_count
+            )));
+        }
+        check_count_fits(&self.buffer, "methods", methods_count, 8)?;
+        let mut methods = Vec::with_capacity(methods_count as usize);
+        for index in 0..methods_count {
+            check_member_fits(&self.buffer, "methods", methods_count, index as usize)?;
+            let start = self.buffer.position();
+            if let Some(method) = self.read_method()? {
+                methods.push(method);
+            }
+            self.mark(start, || format!("method #{}", index));
+        }
+        self.class_file.methods = methods;
         Ok(())
     }
 
-    fn read_method(&mut self) -> Result<ClassFileMethod> {
-        let flags = self.read_method_flags()?;
+    // Reads the next method, or skips it when only the public API is wanted and it is not part
+    // of it
+    fn read_method(&mut self) -> Result<Option<ClassFileMethod>> {
+        let start = self.buffer.position();
+        let (flags, reserved_flags) = self.read_method_flags()?;
         let name_constant_index = self.buffer.read_u16()?;
         let name = self.read_string_reference(name_constant_index)?;
         let type_constant_index = self.buffer.read_u16()?;
         let type_descriptor = self.read_string_reference(type_constant_index)?;
+        if self.options.strict {
+            check_special_method(flags, &name, &type_descriptor)?;
+        }
+        if self.options.public_api_only
+            && !flags.intersects(MethodFlags::PUBLIC | MethodFlags::PROTECTED)
+        {
+            skip_raw_attributes(&mut self.buffer)?;
+            return Ok(None);
+        }
         let attributes = self.read_raw_attributes()?;
+        let code = attributes
+            .iter()
+            .find(|attr| attr.name == "Code" && !self.skip_code)
+            .map(|attr| self.read_code_attribute(&attr.info))
+            .transpose()?;
+        let visible_annotations =
+            self.decode_attribute(&attributes, "RuntimeVisibleAnnotations", read_annotations)?;
+        let invisible_annotations =
+            self.decode_attribute(&attributes, "RuntimeInvisibleAnnotations", read_annotations)?;
+        let visible_parameter_annotations = self.decode_attribute(
+            &attributes,
+            "RuntimeVisibleParameterAnnotations",
+            read_parameter_annotations,
+        )?;
+        let invisible_parameter_annotations = self.decode_attribute(
+            &attributes,
+            "RuntimeInvisibleParameterAnnotations",
+            read_parameter_annotations,
+        )?;
+        let visible_type_annotations = self.decode_attribute(
+            &attributes,
+            "RuntimeVisibleTypeAnnotations",
+            read_type_annotations,
+        )?;
+        let invisible_type_annotations = self.decode_attribute(
+            &attributes,
+            "RuntimeInvisibleTypeAnnotations",
+            read_type_annotations,
+        )?;
 
-        Ok(ClassFileMethod {
+        Ok(Some(ClassFileMethod {
             flags,
+            reserved_flags,
             name,
             type_descriptor,
             attributes,
-        })
+            code,
+            byte_range: Some((start, self.buffer.position())),
+            visible_annotations,
+            invisible_annotations,
+            visible_parameter_annotations,
+            invisible_parameter_annotations,
+            visible_type_annotations,
+            invisible_type_annotations,
+        }))
     }
 
-    fn read_method_flags(&mut self) -> Result<MethodFlags> {
-        let method_flags_bits = self.buffer.read_u16()?;
-        match MethodFlags::from_bits(method_flags_bits) {
-            Some(flags) => Ok(flags),
-            None => Err(ClassReaderError::InvalidClassData(format!(
-                "invalid method flags: {}",
-                method_flags_bits
-            ))),
-        }
+    fn read_method_flags(&mut self) -> Result<(MethodFlags, u16)> {
+        let bits = self.buffer.read_u16()?;
+        Ok((
+            MethodFlags::from_bits_truncate(bits),
+            bits & !MethodFlags::all().bits(),
+        ))
+    }
+
+    // Decodes the attribute with the given name, or gives the default value if there is none.
+    // Visible and invisible variants of an attribute share the same decoder.
+    fn decode_attribute<T: Default>(
+        &self,
+        attributes: &[Attribute],
+        name: &str,
+        decode: fn(&[u8], &ConstantPool) -> Result<T>,
+    ) -> Result<T> {
+        attributes
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| decode(&attr.info, &self.class_file.constants))
+            .transpose()
+            .map(Option::unwrap_or_default)
     }
 
     fn read_raw_attributes(&mut self) -> Result<Vec<Attribute>> {
-        let attributes_count = self.buffer.read_u16()?;
-        (0..attributes_count)
-            .map(|_| self.read_raw_attribute())
-            .collect::<Result<Vec<Attribute>>>()
+        read_raw_attributes(&mut self.buffer, &self.class_file.constants, &self.options)
     }
 
-    fn read_raw_attribute(&mut self) -> Result<Attribute> {
-        let name_constant_index = self.buffer.read_u16()?;
-        let name = self.read_string_reference(name_constant_index)?;
-        let len = self.buffer.read_u32()?;
-        let bytes = self
-            .buffer
-            .read_bytes(usize::try_from(len).expect("usize should have at least 32 bits"))?;
-        Ok(Attribute {
-            name,
-            info: Vec::from(bytes),
+    fn read_code_attribute(&self, info: &[u8]) -> Result<CodeAttribute> {
+        read_code_attribute(info, &self.class_file, &self.options)
+    }
+}
+
+// Rejects instance and class initialization methods whose flags or descriptor the JVM
+// specification does not allow
+// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-2.html#jvms-2.9
+fn check_special_method(flags: MethodFlags, name: &str, descriptor: &str) -> Result<()> {
+    let problem = match name {
+        "<init>" if !descriptor.ends_with(")V") => "must return void",
+        "<clinit>" if descriptor != "()V" => "must have the descriptor ()V",
+        "<clinit>" if !flags.contains(MethodFlags::STATIC) => "must be static",
+        _ => return Ok(()),
+    };
+    Err(ClassReaderError::InvalidClassData(format!(
+        "method {}{} {}",
+        name, descriptor, problem
+    )))
+}
+
+fn is_pre_45_3(class_file: &ClassFile) -> bool {
+    class_file.version == ClassFileVersion::Jdk1_1 && class_file.minor_version < 3
+}
+
+pub(crate) fn read_code_attribute(
+    info: &[u8],
+    class_file: &ClassFile,
+    options: &ReaderOptions,
+) -> Result<CodeAttribute> {
+    let mut buffer = BufferReader::new(info);
+    // Before version 45.3 the sizes were narrower, as in the class files of JDK 1.0.2
+    let (max_stack, max_locals, code_length) = if is_pre_45_3(class_file) {
+        (
+            buffer.read_u8()? as u16,
+            buffer.read_u8()? as u16,
+            buffer.read_u16()? as u32,
+        )
+    } else {
+        (buffer.read_u16()?, buffer.read_u16()?, buffer.read_u32()?)
+    };
+    if !buffer.can_read(code_length as u64) {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "code has length {}, but only {} bytes remain",
+            code_length,
+            buffer.remaining()
+        )));
+    }
+    let code = Vec::from(buffer.read_bytes(code_length as usize)?);
+
+    let exception_table_length = buffer.read_u16()?;
+    check_count_fits(&buffer, "exception handlers", exception_table_length, 8)?;
+    let exception_table = (0..exception_table_length)
+        .map(|_| {
+            Ok(ExceptionTableEntry {
+                start_pc: buffer.read_u16()?,
+                end_pc: buffer.read_u16()?,
+                handler_pc: buffer.read_u16()?,
+                catch_type: buffer.read_u16()?,
+            })
         })
+        .collect::<Result<Vec<ExceptionTableEntry>>>()?;
+
+    let attributes = read_raw_attributes(&mut buffer, &class_file.constants, options)?;
+    // The nested attributes must end exactly where the Code attribute does
+    if buffer.remaining() > 0 {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "Code attribute has {} unexpected trailing bytes",
+            buffer.remaining()
+        )));
     }
+
+    Ok(CodeAttribute {
+        max_stack,
+        max_locals,
+        code,
+        exception_table,
+        attributes,
+    })
 }
 
+// Rejects a declared count that could not possibly fit in what is left of the buffer,
+// before anything gets allocated for it
+fn check_count_fits(
+    buffer: &BufferReader,
+    what: &str,
+    count: u16,
+    min_entry_size: u8,
+) -> Result<()> {
+    let required = count as u64 * min_entry_size as u64;
+    if !buffer.can_read(required) {
+        Err(ClassReaderError::InvalidClassData(format!(
+            "{} {} need at least {} bytes, but only {} remain",
+            count,
+            what,
+            required,
+            buffer.remaining()
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+// Fields and methods take at least 8 bytes each; a class whose data ends before all the declared
+// members have been read reports how many were actually found
+fn check_member_fits(buffer: &BufferReader, what: &str, declared: u16, found: usize) -> Result<()> {
+    if buffer.can_read(8) {
+        Ok(())
+    } else {
+        Err(ClassReaderError::InvalidClassData(format!(
+            "expected {} {}, found {}",
+            declared, what, found
+        )))
+    }
+}
+
+fn read_raw_attributes(
+    buffer: &mut BufferReader,
+    constants: &ConstantPool,
+    options: &ReaderOptions,
+) -> Result<Vec<Attribute>> {
+    let attributes_count = buffer.read_u16()?;
+    check_count_fits(buffer, "attributes", attributes_count, 6)?;
+    (0..attributes_count)
+        .map(|_| read_raw_attribute(buffer, constants, options))
+        .collect::<Result<Vec<Attribute>>>()
+}
+
+// Moves past a list of attributes without decoding them, as for members that are dropped
+fn skip_raw_attributes(buffer: &mut BufferReader) -> Result<()> {
+    let attributes_count = buffer.read_u16()?;
+    check_count_fits(buffer, "attributes", attributes_count, 6)?;
+    for _ in 0..attributes_count {
+        buffer.read_u16()?;
+        let len = buffer.read_u32()?;
+        if !buffer.can_read(len as u64) {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "attribute has length {}, but only {} bytes remain",
+                len,
+                buffer.remaining()
+            )));
+        }
+        buffer.read_bytes(len as usize)?;
+    }
+    Ok(())
+}
+
+fn read_raw_attribute(
+    buffer: &mut BufferReader,
+    constants: &ConstantPool,
+    options: &ReaderOptions,
+) -> Result<Attribute> {
+    let name_constant_index = buffer.read_u16()?;
+    let name = constants.text_of(name_constant_index)?;
+    let len = buffer.read_u32()?;
+    if len > options.max_attribute_length {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "attribute {} has length {}, more than the limit of {}",
+            name, len, options.max_attribute_length
+        )));
+    }
+    if !buffer.can_read(len as u64) {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "attribute {} has length {}, but only {} bytes remain",
+            name,
+            len,
+            buffer.remaining()
+        )));
+    }
+    let bytes = buffer.read_bytes(len as usize)?;
+    Ok(Attribute {
+        name,
+        info: Vec::from(bytes),
+    })
+}
+
+#[cfg(feature = "std")]
 pub fn read(path: &Path) -> Result<ClassFile> {
+    read_with_options(path, &ReaderOptions::default())
+}
+
+#[cfg(feature = "std")]
+pub fn read_with_options(path: &Path, options: &ReaderOptions) -> Result<ClassFile> {
+    let mut file = File::open(path)?;
+    let mut buf: Vec<u8> = Vec::new();
+    file.read_to_end(&mut buf)?;
+
+    read_buffer_with_options(&buf, options)
+}
+
+// Reads a class like read_with_options, also reporting the issues that do not prevent reading it
+#[cfg(feature = "std")]
+pub fn read_with_warnings(path: &Path, options: &ReaderOptions) -> Result<ReadOutcome> {
     let mut file = File::open(path)?;
     let mut buf: Vec<u8> = Vec::new();
     file.read_to_end(&mut buf)?;
 
-    read_buffer(&buf)
+    read_buffer_with_warnings(&buf, options)
+}
+
+// Reads every .class file under a directory, named by its path like com.foo.Bar, in the order of
+// the paths
+#[cfg(feature = "std")]
+pub fn read_dir(root: &Path) -> Result<Vec<(String, ClassFile)>> {
+    let mut classes = Vec::new();
+    read_dir_into(root, "", &mut classes)?;
+    Ok(classes)
+}
+
+// Reads the classes of a directory and its subdirectories, whose binary names start with the
+// given package prefix
+#[cfg(feature = "std")]
+fn read_dir_into(dir: &Path, package: &str, classes: &mut Vec<(String, ClassFile)>) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            read_dir_into(&path, &format!("{}{}.", package, file_name), classes)?;
+        } else if let Some(simple_name) = file_name.strip_suffix(".class") {
+            classes.push((format!("{}{}", package, simple_name), read(&path)?));
+        }
+    }
+    Ok(())
 }
 
 pub fn read_buffer(buf: &[u8]) -> Result<ClassFile> {
-    ClassFileReader::new(buf).read()
+    read_buffer_with_options(buf, &ReaderOptions::default())
+}
+
+// Reads a class, then checks every reference of its constant pool
+pub fn read_validated(data: &[u8]) -> Result<ClassFile> {
+    let class = read_buffer(data)?;
+    class.constants.validate()?;
+    Ok(class)
+}
+
+pub fn read_buffer_with_options(buf: &[u8], options: &ReaderOptions) -> Result<ClassFile> {
+    ClassFileReader::new(buf, options.clone()).read()
+}
+
+// Reads a class stored at the given offset of a larger buffer, whose byte ranges are offsets into
+// that buffer
+pub fn read_buffer_at(
+    buf: &[u8],
+    base_offset: usize,
+    options: &ReaderOptions,
+) -> Result<ClassFile> {
+    ClassFileReader::new_at(buf, base_offset, options.clone()).read()
+}
+
+// Reads a class like read_buffer_with_options, also reporting the issues that do not prevent
+// reading it
+pub fn read_buffer_with_warnings(buf: &[u8], options: &ReaderOptions) -> Result<ReadOutcome> {
+    ClassFileReader::new(buf, options.clone()).read_with_warnings()
+}
+
+// Reads a class without decoding the Code attributes of its methods
+pub(crate) fn read_buffer_without_code(buf: &[u8], options: &ReaderOptions) -> Result<ClassFile> {
+    let mut reader = ClassFileReader::new(buf, options.clone());
+    reader.skip_code = true;
+    reader.read()
+}
+
+// Describes the structure of a class, one labelled byte range per line. For invalid data, the dump
+// stops at the error and names it
+pub fn annotated_dump(data: &[u8]) -> String {
+    let mut reader = ClassFileReader::new(data, ReaderOptions::default());
+    reader.labels = Some(Vec::new());
+    let result = reader.parse();
+    let mut labels = reader.labels.take().unwrap_or_default();
+    labels.sort_by_key(|&(start, end, _)| (start, core::cmp::Reverse(end)));
+
+    let mut dump = String::new();
+    for (start, end, label) in labels {
+        let bytes = &data[start..end];
+        let hex = bytes
+            .iter()
+            .take(16)
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ellipsis = if bytes.len() > 16 { " ..." } else { "" };
+        dump.push_str(&format!(
+            "{:06x}..{:06x} {}: {}{}\n",
+            start, end, label, hex, ellipsis
+        ));
+    }
+    if let Err(err) = result {
+        dump.push_str(&format!(
+            "{:06x} error: {}\n",
+            reader.buffer.position(),
+            err
+        ));
+    }
+    dump
+}
+
+// Reads only the name of a class, skipping its fields, methods and attributes
+pub fn read_name_only(data: &[u8]) -> Result<String> {
+    ClassFileReader::new(data, ReaderOptions::default()).read_name()
+}
+
+// Reads only the version and constant pool of a class, skipping everything after the pool
+pub fn read_constants_only(data: &[u8]) -> Result<(ClassFileVersion, ConstantPool)> {
+    ClassFileReader::new(data, ReaderOptions::default()).read_constants_only()
+}
+
+// Reads the classes of a jar one at a time, passing each to the callback with its entry name. The
+// callback can break to stop early
+#[cfg(feature = "zip")]
+pub fn for_each_class_in_jar<F>(path: &Path, mut callback: F) -> Result<()>
+where
+    F: FnMut(&str, ClassFile) -> ControlFlow<()>,
+{
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut buf: Vec<u8> = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if !entry.is_file() || !entry.name().ends_with(".class") {
+            continue;
+        }
+        buf.clear();
+        entry.read_to_end(&mut buf)?;
+        let class = read_buffer(&buf)?;
+        if callback(entry.name(), class).is_break() {
+            break;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::class_reader::read_buffer;
+    use alloc::format;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::c_pool::ConstantPoolEntry;
+    use crate::class_file_field::FieldConstantValue;
+    use crate::class_reader::{
+        read_buffer, read_buffer_with_options, read_buffer_with_warnings, read_validated,
+    };
     use crate::class_reader_error::ClassReaderError;
+    use crate::parse_warning::ParseWarning;
+    use crate::reader_options::ReaderOptions;
+
+    fn utf8_constant(s: &str) -> Vec<u8> {
+        let mut bytes = vec![1];
+        bytes.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(s.as_bytes());
+        bytes
+    }
+
+    // Builds a class named Foo, with no superclass, whose pool holds Utf8 "Foo", the class
+    // reference to it, then the given constants. The body starts at the interfaces count.
+    fn class_bytes(constants: &[Vec<u8>], body: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x32];
+        // Long and Double constants take two slots
+        let slots: usize = constants
+            .iter()
+            .map(|constant| if matches!(constant[0], 5 | 6) { 2 } else { 1 })
+            .sum();
+        bytes.extend_from_slice(&(slots as u16 + 3).to_be_bytes());
+        bytes.extend(utf8_constant("Foo"));
+        bytes.extend([7, 0x00, 0x01]);
+        for constant in constants {
+            bytes.extend(constant);
+        }
+        bytes.extend([0x00, 0x21, 0x00, 0x02, 0x00, 0x00]);
+        bytes.extend_from_slice(body);
+        bytes
+    }
 
     #[test]
     fn magic_number_is_required() {
@@ -365,4 +1036,548 @@ mod tests {
             Err(ClassReaderError::InvalidClassData(s)) if s == "invalid magic number"
         ));
     }
+    #[test]
+    fn byte_swapped_magic_number_is_pointed_out() {
+        let data = vec![0xBE, 0xBA, 0xFE, 0xCA, 0x00, 0x00, 0x00, 0x34];
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s)) if s.contains("bytes may be byte-swapped")
+        ));
+    }
+    #[test]
+    fn string_constant_values_follow_the_string_entry() {
+        // static final String S = "hello", with the ConstantValue at the given index
+        let string_field = |constant_index: u8| {
+            class_bytes(
+                &[
+                    utf8_constant("S"),
+                    utf8_constant("Ljava/lang/String;"),
+                    utf8_constant("ConstantValue"),
+                    utf8_constant("hello"),
+                    vec![8, 0x00, 0x06],
+                    vec![8, 0x00, 0x02],
+                ],
+                &[
+                    0x00,
+                    0x00, // interfaces
+                    0x00,
+                    0x01, // fields
+                    0x00,
+                    0x18,
+                    0x00,
+                    0x03,
+                    0x00,
+                    0x04, // static final S
+                    0x00,
+                    0x01, // attributes
+                    0x00,
+                    0x05,
+                    0x00,
+                    0x00,
+                    0x00,
+                    0x02,
+                    0x00,
+                    constant_index, // ConstantValue
+                    0x00,
+                    0x00, // methods
+                    0x00,
+                    0x00, // attributes
+                ],
+            )
+        };
+        let class = read_buffer(&string_field(7)).unwrap();
+        assert_eq!(
+            Some(FieldConstantValue::String("hello".to_string())),
+            class.fields[0].constant_value
+        );
+        // A String entry referring to a class, rather than to a Utf8 entry
+        assert!(matches!(
+            read_buffer(&string_field(8)),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "constant pool entry 2 is not a Utf8"
+        ));
+        // The index of the Utf8 entry itself
+        assert!(read_buffer(&string_field(6)).is_err());
+    }
+    #[test]
+    fn many_interfaces_can_be_read() {
+        let count = 5000u16;
+        let mut constants = Vec::new();
+        let mut body = count.to_be_bytes().to_vec();
+        for n in 0..count {
+            constants.push(utf8_constant(&format!("I{}", n)));
+            constants.push([&[7u8][..], &(2 * n + 3).to_be_bytes()].concat());
+            body.extend_from_slice(&(2 * n + 4).to_be_bytes());
+        }
+        body.extend([0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        let class = read_buffer(&class_bytes(&constants, &body)).unwrap();
+        assert_eq!(count as usize, class.interfaces.len());
+        assert_eq!("I0", class.interfaces[0]);
+        assert_eq!("I4999", class.interfaces[4999]);
+    }
+    #[test]
+    fn raw_utf8_bytes_can_be_kept() {
+        // U+1F600 as a surrogate pair of three bytes each, then NUL in its two bytes form
+        let raw = [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80, 0xC0, 0x80];
+        let mut constant = vec![1, 0x00, raw.len() as u8];
+        constant.extend_from_slice(&raw);
+        let data = class_bytes(&[constant], &[0x00; 8]);
+
+        let options = ReaderOptions {
+            keep_raw_utf8: true,
+            ..Default::default()
+        };
+        let mut class = read_buffer_with_options(&data, &options).unwrap();
+        assert_eq!(Ok("\u{1F600}\0".to_string()), class.constants.text_of(3));
+        assert_eq!(Some(&raw[..]), class.constants.raw_utf8_at(3));
+        assert_eq!(Some(&b"Foo"[..]), class.constants.raw_utf8_at(1));
+        assert_eq!(None, class.constants.raw_utf8_at(2));
+        // Replaced text no longer has the bytes it was read from
+        class.constants.set_utf8(3, "x".to_string()).unwrap();
+        assert_eq!(None, class.constants.raw_utf8_at(3));
+
+        let class = read_buffer(&data).unwrap();
+        assert_eq!(None, class.constants.raw_utf8_at(3));
+    }
+    #[test]
+    fn floating_point_constants_keep_their_bits() {
+        let float_bits = [0x8000_0000u32, 0x7FC0_0001, 0x0000_0001];
+        let double_bits = [0x8000_0000_0000_0000u64, 0x7FF8_0000_0000_0001];
+        let mut constants: Vec<Vec<u8>> = float_bits
+            .iter()
+            .map(|bits| [&[4u8][..], &bits.to_be_bytes()].concat())
+            .collect();
+        constants.extend(
+            double_bits
+                .iter()
+                .map(|bits| [&[6u8][..], &bits.to_be_bytes()].concat()),
+        );
+        constants.push(utf8_constant("F"));
+        constants.push(utf8_constant("ConstantValue"));
+        // static final float F, whose value is the NaN at #4
+        let data = class_bytes(
+            &constants,
+            &[
+                0x00, 0x00, // interfaces
+                0x00, 0x01, // fields
+                0x00, 0x18, 0x00, 0x0A, 0x00, 0x0A, // static final F
+                0x00, 0x01, // attributes
+                0x00, 0x0B, 0x00, 0x00, 0x00, 0x02, 0x00, 0x04, // ConstantValue
+                0x00, 0x00, // methods
+                0x00, 0x00, // attributes
+            ],
+        );
+        let class = read_buffer(&data).unwrap();
+
+        for (index, bits) in [3, 4, 5].into_iter().zip(float_bits) {
+            assert!(matches!(
+                class.constants.get(index),
+                Ok(ConstantPoolEntry::Float(n)) if n.to_bits() == bits
+            ));
+        }
+        for (index, bits) in [6, 8].into_iter().zip(double_bits) {
+            assert!(matches!(
+                class.constants.get(index),
+                Ok(ConstantPoolEntry::Double(n)) if n.to_bits() == bits
+            ));
+        }
+        assert_ne!(
+            ConstantPoolEntry::Float(0.0),
+            *class.constants.get(3).unwrap()
+        );
+
+        assert_eq!(
+            Some(FieldConstantValue::Float(f32::from_bits(0x7FC0_0001))),
+            class.fields[0].constant_value
+        );
+        assert_ne!(
+            FieldConstantValue::Float(-0.0),
+            FieldConstantValue::Float(0.0)
+        );
+        assert!(class.equivalent_ignoring_debug(&read_buffer(&data).unwrap()));
+    }
+    #[test]
+    fn huge_attribute_length_is_rejected() {
+        let data = class_bytes(
+            &[
+                utf8_constant("m"),
+                utf8_constant("()V"),
+                utf8_constant("Code"),
+            ],
+            &[
+                0x00, 0x00, // interfaces
+                0x00, 0x00, // fields
+                0x00, 0x01, // methods
+                0x00, 0x01, 0x00, 0x03, 0x00, 0x04, // public m()V
+                0x00, 0x01, // attributes
+                0x00, 0x05, 0xFF, 0xFF, 0xFF, 0xFF, // Code, 4GB long
+                0x00, 0x00,
+            ],
+        );
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "attribute Code has length 4294967295, but only 2 bytes remain"
+        ));
+
+        let options = ReaderOptions {
+            max_attribute_length: 1024,
+            ..Default::default()
+        };
+        assert!(matches!(
+            read_buffer_with_options(&data, &options),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "attribute Code has length 4294967295, more than the limit of 1024"
+        ));
+    }
+
+    #[test]
+    fn special_methods_are_checked_when_strict() {
+        // A method without attributes, with the given flags, name and descriptor
+        let method = |flags: u8, name: &str, descriptor: &str| {
+            class_bytes(
+                &[utf8_constant(name), utf8_constant(descriptor)],
+                &[
+                    0x00, 0x00, // interfaces
+                    0x00, 0x00, // fields
+                    0x00, 0x01, // methods
+                    0x00, flags, 0x00, 0x03, 0x00, 0x04, 0x00, 0x00, // no attributes
+                    0x00, 0x00,
+                ],
+            )
+        };
+        let strict = ReaderOptions {
+            strict: true,
+            ..Default::default()
+        };
+
+        let init = method(0x01, "<init>", "(I)I");
+        assert!(read_buffer(&init).is_ok());
+        assert!(matches!(
+            read_buffer_with_options(&init, &strict),
+            Err(ClassReaderError::InvalidClassData(s)) if s == "method <init>(I)I must return void"
+        ));
+        assert!(matches!(
+            read_buffer_with_options(&method(0x00, "<clinit>", "()V"), &strict),
+            Err(ClassReaderError::InvalidClassData(s)) if s == "method <clinit>()V must be static"
+        ));
+        assert!(matches!(
+            read_buffer_with_options(&method(0x08, "<clinit>", "(I)V"), &strict),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "method <clinit>(I)V must have the descriptor ()V"
+        ));
+        assert!(read_buffer_with_options(&method(0x01, "<init>", "(I)V"), &strict).is_ok());
+        assert!(read_buffer_with_options(&method(0x08, "<clinit>", "()V"), &strict).is_ok());
+    }
+
+    #[test]
+    fn constant_pool_references_can_be_validated_eagerly() {
+        // A String entry referring past the end of the pool, which nothing resolves
+        let data = class_bytes(&[vec![8, 0x00, 0x63]], &[0x00; 8]);
+        assert!(read_buffer(&data).is_ok());
+        assert_eq!(
+            Err(ClassReaderError::InvalidClassData(
+                "constant pool entry 3 refers to missing entry 99".to_string()
+            )),
+            read_validated(&data)
+        );
+        assert!(read_validated(&class_bytes(&[], &[0x00; 8])).is_ok());
+    }
+
+    #[test]
+    fn constant_pool_entries_can_refer_forward() {
+        let mut data = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x32, 0x00, 0x08];
+        data.extend([7, 0x00, 0x05]); // #1 class Foo, named by a later entry
+        data.extend([7, 0x00, 0x04]); // #2 class java/lang/Object
+        data.extend([12, 0x00, 0x06, 0x00, 0x07]); // #3 m:()V
+        data.extend(utf8_constant("java/lang/Object"));
+        data.extend(utf8_constant("Foo"));
+        data.extend(utf8_constant("m"));
+        data.extend(utf8_constant("()V"));
+        data.extend([
+            0x00, 0x21, 0x00, 0x01, 0x00, 0x02, // Foo extends java/lang/Object
+            0x00, 0x00, // interfaces
+            0x00, 0x00, // fields
+            0x00, 0x01, // methods
+            0x04, 0x01, 0x00, 0x06, 0x00, 0x07, 0x00, 0x00, // public abstract m()V
+            0x00, 0x00, // attributes
+        ]);
+        let class = read_validated(&data).unwrap();
+        assert_eq!("Foo", class.name);
+        assert_eq!("java/lang/Object", class.superclass);
+        assert_eq!(
+            ("m", "()V"),
+            (
+                class.methods[0].name.as_str(),
+                class.methods[0].type_descriptor.as_str()
+            )
+        );
+        assert_eq!(Ok("m: ()V".to_string()), class.constants.text_of(3));
+    }
+
+    #[test]
+    fn counts_larger_than_the_data_are_rejected() {
+        let data = class_bytes(&[], &[0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF]);
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "65535 methods need at least 524280 bytes, but only 0 remain"
+        ));
+
+        let options = ReaderOptions {
+            max_method_count: 100,
+            ..Default::default()
+        };
+        assert!(matches!(
+            read_buffer_with_options(&data, &options),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "class has 65535 methods, more than the limit of 100"
+        ));
+    }
+
+    #[test]
+    fn invalid_strings_can_be_decoded_lossily() {
+        // A surrogate cut short after its second byte
+        let constant = vec![1, 0x00, 0x03, b'a', 0xED, 0xA0];
+        let data = class_bytes(
+            &[constant],
+            &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        );
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s)) if s == "invalid utf8 data"
+        ));
+
+        let options = ReaderOptions {
+            lossy_strings: true,
+            ..Default::default()
+        };
+        let class = read_buffer_with_options(&data, &options).unwrap();
+        assert_eq!(
+            Ok(&ConstantPoolEntry::Utf8("a\u{FFFD}\u{FFFD}".to_string())),
+            class.constants.get(3)
+        );
+    }
+
+    #[test]
+    fn missing_names_can_be_placeholders() {
+        let body = [
+            0x00, 0x00, // interfaces
+            0x00, 0x01, // fields
+            0x00, 0x01, 0x00, 0x09, 0x00, 0x01, 0x00, 0x00, // public, name #9, descriptor #1
+            0x00, 0x00, // methods
+            0x00, 0x00, // attributes
+        ];
+        let data = class_bytes(&[], &body);
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s)) if s == "invalid constant pool index: 9"
+        ));
+
+        let options = ReaderOptions {
+            lenient_references: true,
+            ..Default::default()
+        };
+        let class = read_buffer_with_options(&data, &options).unwrap();
+        assert_eq!("<unresolved #9>", class.fields[0].name);
+    }
+
+    #[test]
+    fn source_debug_extension_is_decoded() {
+        let smap = "SMAP\nhello.jsp\nJSP\n*E\n";
+        let mut body = vec![
+            0x00, 0x00, // interfaces
+            0x00, 0x00, // fields
+            0x00, 0x00, // methods
+            0x00, 0x01, // attributes
+            0x00, 0x03, // SourceDebugExtension
+        ];
+        body.extend_from_slice(&(smap.len() as u32).to_be_bytes());
+        body.extend_from_slice(smap.as_bytes());
+        let data = class_bytes(&[utf8_constant("SourceDebugExtension")], &body);
+
+        let class = read_buffer(&data).unwrap();
+        assert_eq!(Some(smap.to_string()), class.source_debug_extension);
+    }
+
+    #[test]
+    fn invalid_source_debug_extension_can_be_decoded_lossily() {
+        let body = [
+            0x00, 0x00, // interfaces
+            0x00, 0x00, // fields
+            0x00, 0x00, // methods
+            0x00, 0x01, // attributes
+            0x00, 0x03, 0x00, 0x00, 0x00, 0x03, // SourceDebugExtension, 3 bytes
+            b'a', 0xED, 0xA0, // a surrogate cut short after its second byte
+        ];
+        let data = class_bytes(&[utf8_constant("SourceDebugExtension")], &body);
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s)) if s == "invalid utf8 data"
+        ));
+
+        let options = ReaderOptions {
+            lossy_strings: true,
+            ..Default::default()
+        };
+        let class = read_buffer_with_options(&data, &options).unwrap();
+        assert_eq!(
+            Some("a\u{FFFD}\u{FFFD}".to_string()),
+            class.source_debug_extension
+        );
+    }
+    #[test]
+    fn truncated_utf8_constants_are_rejected() {
+        let data = vec![
+            0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x32, // header
+            0x00, 0x02, // 1 constant
+            0x01, 0x03, 0xE8, b'a', b'b', b'c', // Utf8 of 1000 bytes, with 3
+        ];
+        for options in [
+            ReaderOptions::default(),
+            ReaderOptions {
+                lossy_strings: true,
+                ..Default::default()
+            },
+        ] {
+            assert_eq!(
+                Err(ClassReaderError::InvalidClassData(
+                    "utf8 constant has length 1000, but only 3 bytes remain".to_string()
+                )),
+                read_buffer_with_options(&data, &options)
+            );
+        }
+    }
+    #[test]
+    fn unknown_attributes_are_warned_about() {
+        let data = class_bytes(
+            &[utf8_constant("Custom")],
+            &[
+                0x00, 0x00, // interfaces
+                0x00, 0x00, // fields
+                0x00, 0x00, // methods
+                0x00, 0x01, // attributes
+                0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x2A, // Custom, 1 byte
+            ],
+        );
+        let outcome = read_buffer_with_warnings(&data, &ReaderOptions::default()).unwrap();
+        assert_eq!(read_buffer(&data).unwrap(), outcome.class);
+        assert_eq!(
+            vec![ParseWarning::UnknownAttribute {
+                name: "Custom".to_string(),
+                location: "the class".to_string(),
+            }],
+            outcome.warnings
+        );
+
+        // Flag issues and trailing data are reported as well
+        let mut data = data;
+        data[29] = 0x23; // public super, and reserved 0x0002
+        data.push(0);
+        let warnings = read_buffer_with_warnings(&data, &ReaderOptions::default())
+            .unwrap()
+            .warnings;
+        assert_eq!(3, warnings.len());
+        assert_eq!(
+            "reserved flags 0x0002 set on the class",
+            warnings[1].to_string()
+        );
+        assert_eq!(ParseWarning::TrailingData { length: 1 }, warnings[2]);
+    }
+    #[test]
+    fn missing_members_are_reported_with_their_count() {
+        let data = class_bytes(
+            &[
+                utf8_constant("m"),
+                utf8_constant("()V"),
+                utf8_constant("Custom"),
+            ],
+            &[
+                0x00, 0x00, // interfaces
+                0x00, 0x00, // fields
+                0x00, 0x02, // methods, but only one follows
+                0x00, 0x01, 0x00, 0x03, 0x00, 0x04, 0x00, 0x01, // public m()V, 1 attribute
+                0x00, 0x05, 0x00, 0x00, 0x00, 0x0A, // Custom, 10 bytes
+                0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+            ],
+        );
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s)) if s == "expected 2 methods, found 1"
+        ));
+    }
+    #[test]
+    fn class_indices_must_refer_to_classes() {
+        let mut data = class_bytes(&[vec![3, 0x00, 0x00, 0x00, 0x2A]], &[0x00, 0x00]);
+        // Point this_class at the integer constant #3
+        let this_class = data.len() - 6;
+        data[this_class] = 0x00;
+        data[this_class + 1] = 0x03;
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "this_class refers to constant pool entry 3, which is not a class"
+        ));
+
+        let data = class_bytes(&[], &[0x00, 0x01, 0x00, 0x01]);
+        assert!(matches!(
+            read_buffer(&data),
+            Err(ClassReaderError::InvalidClassData(s))
+                if s == "interface refers to constant pool entry 1, which is not a class"
+        ));
+    }
+    #[test]
+    fn abstract_and_native_methods_have_no_code() {
+        let data = class_bytes(
+            &[utf8_constant("m"), utf8_constant("()V")],
+            &[
+                0x00, 0x00, // interfaces
+                0x00, 0x00, // fields
+                0x00, 0x02, // methods
+                0x04, 0x01, 0x00, 0x03, 0x00, 0x04, 0x00, 0x00, // public abstract m()V
+                0x01, 0x09, 0x00, 0x03, 0x00, 0x04, 0x00, 0x00, // public static native m()V
+                0x00, 0x00, // class attributes
+            ],
+        );
+        let class = read_buffer(&data).unwrap();
+        let (abstract_method, native_method) = (&class.methods[0], &class.methods[1]);
+        assert!(abstract_method.is_abstract() && !abstract_method.is_native());
+        assert!(native_method.is_native() && native_method.is_static());
+        assert!(!native_method.is_synchronized() && !native_method.is_varargs());
+        assert!(abstract_method.code.is_none() && native_method.code.is_none());
+    }
+    #[test]
+    fn code_of_pre_45_3_classes_uses_narrow_sizes() {
+        let mut data = class_bytes(
+            &[
+                utf8_constant("m"),
+                utf8_constant("()V"),
+                utf8_constant("Code"),
+            ],
+            &[
+                0x00, 0x00, // interfaces
+                0x00, 0x00, // fields
+                0x00, 0x01, // methods
+                0x00, 0x01, 0x00, 0x03, 0x00, 0x04, 0x00, 0x01, // public m()V, 1 attribute
+                0x00, 0x05, 0x00, 0x00, 0x00, 0x09, // Code, 9 bytes
+                0x01, 0x02, // max_stack and max_locals
+                0x00, 0x01, 0xB1, // 1 byte of code: return
+                0x00, 0x00, // exception handlers
+                0x00, 0x00, // attributes
+                0x00, 0x00, // class attributes
+            ],
+        );
+        // Version 45.0
+        data[4..8].copy_from_slice(&[0x00, 0x00, 0x00, 0x2D]);
+
+        let class = read_buffer(&data).unwrap();
+        let code = class.methods[0].code.as_ref().unwrap();
+        assert_eq!(1, code.max_stack);
+        assert_eq!(2, code.max_locals);
+        assert_eq!(vec![0xB1], code.code);
+
+        // The same layout is invalid from 45.3 on
+        data[4..8].copy_from_slice(&[0x00, 0x03, 0x00, 0x2D]);
+        assert!(read_buffer(&data).is_err());
+    }
 }
@@ -1,10 +1,14 @@
-use crate::c_pool::InvalidConstantPoolIndexError;
+use alloc::string::{String, ToString};
+
+use crate::c_pool::ConstantPoolError;
 use thiserror::Error;
 
-#[derive(Error, Debug, PartialEq)]
+#[derive(Error, Debug)]
 pub enum ClassReaderError {
-    #[error("generic I/O error: {0}")]
-    IoError(String),
+    // The kind of the error, by which it is compared, and its message
+    #[cfg(feature = "std")]
+    #[error("generic I/O error: {1}")]
+    IoError(std::io::ErrorKind, String),
 
     #[error("invalid class file: {0}")]
     InvalidClassData(String),
@@ -13,16 +17,86 @@ pub enum ClassReaderError {
     UnsupportedVersion(u16, u16),
 }
 
-pub type Result<T> = std::result::Result<T, ClassReaderError>;
+// I/O errors are compared by their kind only, as their messages name paths and the like
+impl PartialEq for ClassReaderError {
+    fn eq(&self, other: &Self) -> bool {
+        use ClassReaderError::*;
+        match (self, other) {
+            #[cfg(feature = "std")]
+            (IoError(a, _), IoError(b, _)) => a == b,
+            (InvalidClassData(a), InvalidClassData(b)) => a == b,
+            (UnsupportedVersion(a, b), UnsupportedVersion(c, d)) => a == c && b == d,
+            _ => false,
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, ClassReaderError>;
 
-impl From<InvalidConstantPoolIndexError> for ClassReaderError {
-    fn from(value: InvalidConstantPoolIndexError) -> Self {
+impl From<ConstantPoolError> for ClassReaderError {
+    fn from(value: ConstantPoolError) -> Self {
         Self::InvalidClassData(value.to_string())
     }
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for ClassReaderError {
     fn from(err: std::io::Error) -> Self {
-        Self::IoError(format!("{}", err))
+        Self::IoError(err.kind(), format!("{}", err))
+    }
+}
+
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for ClassReaderError {
+    fn from(err: zip::result::ZipError) -> Self {
+        let kind = match &err {
+            zip::result::ZipError::Io(err) => err.kind(),
+            zip::result::ZipError::FileNotFound => std::io::ErrorKind::NotFound,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        Self::IoError(kind, format!("{}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::c_pool::ConstantPoolError;
+    use crate::class_reader_error::ClassReaderError;
+
+    #[test]
+    fn errors_can_be_compared() {
+        assert_eq!(
+            ClassReaderError::UnsupportedVersion(62, 0),
+            ClassReaderError::UnsupportedVersion(62, 0)
+        );
+        assert_ne!(
+            ClassReaderError::UnsupportedVersion(62, 0),
+            ClassReaderError::UnsupportedVersion(62, 65535)
+        );
+        assert_eq!(
+            ClassReaderError::InvalidClassData("invalid constant pool index: 3".to_string()),
+            ClassReaderError::from(ConstantPoolError::InvalidIndex { index: 3 })
+        );
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_errors_are_compared_by_kind() {
+        use std::io::{Error, ErrorKind};
+
+        assert_eq!(
+            ClassReaderError::from(Error::new(ErrorKind::NotFound, "a.class")),
+            ClassReaderError::from(Error::new(ErrorKind::NotFound, "b.class"))
+        );
+        assert_ne!(
+            ClassReaderError::from(Error::new(ErrorKind::NotFound, "a.class")),
+            ClassReaderError::from(Error::new(ErrorKind::PermissionDenied, "a.class"))
+        );
+        assert_ne!(
+            ClassReaderError::from(Error::new(ErrorKind::InvalidData, "bad")),
+            ClassReaderError::InvalidClassData("bad".to_string())
+        );
+    }
+}
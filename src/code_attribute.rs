@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+
+use crate::attribute::Attribute;
+use crate::c_pool::ConstantPool;
+use crate::class_reader_error::Result;
+use crate::verifier;
+
+/// The parsed content of the Code attribute of a method.
+/// https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-4.html#jvms-4.7.3
+#[derive(Debug, Default, PartialEq)]
+pub struct CodeAttribute {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub code: Vec<u8>,
+    pub exception_table: Vec<ExceptionTableEntry>,
+    pub attributes: Vec<Attribute>,
+}
+
+/// An exception handler of a method body. A `catch_type` of 0 catches everything.
+#[derive(Debug, Default, PartialEq)]
+pub struct ExceptionTableEntry {
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    pub catch_type: u16,
+}
+
+impl CodeAttribute {
+    /// Computes the max_stack and max_locals that the code needs, for when the declared ones
+    /// cannot be trusted. The method is described by its descriptor and whether it is static,
+    /// and the constant pool gives the stack effect of field accesses and calls.
+    ///
+    /// Stack depths are followed through the branches and exception handlers, taking the first
+    /// depth that reaches each instruction, which is exact for verifiable code. Every local
+    /// variable access counts, even in unreachable code.
+    pub fn recompute_maxes(
+        &self,
+        descriptor: &str,
+        is_static: bool,
+        constants: &ConstantPool,
+    ) -> Result<(u16, u16)> {
+        verifier::compute_maxes(self, descriptor, is_static, constants)
+    }
+}
+
+impl fmt::Display for CodeAttribute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "max_stack = {}, max_locals = {}, code = {} bytes, exception handlers = {} ({:?})",
+            self.max_stack,
+            self.max_locals,
+            self.code.len(),
+            self.exception_table.len(),
+            self.attributes,
+        )
+    }
+}
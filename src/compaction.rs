@@ -0,0 +1,445 @@
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+use crate::class_file::ClassFile;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::code_attribute::CodeAttribute;
+use crate::instruction::{decode, Opcode};
+
+// Attributes whose references to the constant pool are known, so that they can be rewritten
+const SUPPORTED_ATTRIBUTES: [&str; 19] = [
+    "BootstrapMethods",
+    "Code",
+    "ConstantValue",
+    "Deprecated",
+    "EnclosingMethod",
+    "Exceptions",
+    "InnerClasses",
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "MethodParameters",
+    "NestHost",
+    "NestMembers",
+    "PermittedSubclasses",
+    "Signature",
+    "SourceDebugExtension",
+    "SourceFile",
+    "StackMapTable",
+    "Synthetic",
+];
+
+/// Drops the constant pool entries that the class does not use, and rewrites the indices of the
+/// others wherever they appear: in the entries themselves, the bytecode, the exception tables
+/// and the attributes. Entries keep their order, so no index grows.
+///
+/// Attributes are only understood in the formats the JVM specification gives for them, so
+/// classes with annotations, records, modules or attributes of other tools are rejected, and
+/// left untouched.
+pub fn compact_constant_pool(class: &mut ClassFile) -> Result<()> {
+    if let Some((_, attribute)) = class
+        .all_attributes()
+        .find(|(_, attribute)| !SUPPORTED_ATTRIBUTES.contains(&attribute.name.as_str()))
+    {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "cannot compact the constant pool of a class with a {} attribute",
+            attribute.name
+        )));
+    }
+
+    // Names and descriptors are only kept as text, and so are the names of the attributes
+    let mut texts: BTreeSet<&str> = BTreeSet::new();
+    for field in &class.fields {
+        texts.extend([field.name.as_str(), field.type_descriptor.as_str()]);
+    }
+    for method in &class.methods {
+        texts.extend([method.name.as_str(), method.type_descriptor.as_str()]);
+    }
+    texts.extend(
+        class
+            .all_attributes()
+            .map(|(_, attribute)| attribute.name.as_str()),
+    );
+    let mut classes: BTreeSet<&str> = class.interfaces.iter().map(String::as_str).collect();
+    classes.extend([class.name.as_str(), class.superclass.as_str()]);
+
+    let mut roots: Vec<u16> = class
+        .constants
+        .iter()
+        .filter(|(index, entry)| match entry {
+            ConstantPoolEntry::Utf8(text) => texts.contains(text.as_str()),
+            ConstantPoolEntry::ClassReference(_) => class
+                .constants
+                .class_name_at(*index)
+                .is_ok_and(|name| classes.contains(name.as_str())),
+            _ => false,
+        })
+        .map(|(index, _)| index)
+        .collect();
+    rewrite_class(class, &mut |index| {
+        roots.push(index);
+        index
+    })?;
+
+    let remap = class.constants.compact(roots)?;
+    rewrite_class(class, &mut |index| remap[index as usize])
+}
+
+// The constant pool indices that the attributes and the code of the class refer to, or none if
+// it has an attribute whose references are not known
+pub(crate) fn referenced_indices(class: &mut ClassFile) -> Option<BTreeSet<u16>> {
+    if class
+        .all_attributes()
+        .any(|(_, attribute)| !SUPPORTED_ATTRIBUTES.contains(&attribute.name.as_str()))
+    {
+        return None;
+    }
+    let mut indices = BTreeSet::new();
+    rewrite_class(class, &mut |index| {
+        indices.insert(index);
+        index
+    })
+    .ok()?;
+    Some(indices)
+}
+
+// Replaces every constant pool index in the attributes and the decoded code of the class
+fn rewrite_class(class: &mut ClassFile, f: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+    let constants = &class.constants;
+    let attributes = class
+        .attributes
+        .iter_mut()
+        .chain(
+            class
+                .fields
+                .iter_mut()
+                .flat_map(|field| field.attributes.iter_mut()),
+        )
+        .chain(
+            class
+                .methods
+                .iter_mut()
+                .flat_map(|method| method.attributes.iter_mut()),
+        );
+    for attribute in attributes {
+        rewrite_attribute(&attribute.name, &mut attribute.info, constants, f)?;
+    }
+    for code in class
+        .methods
+        .iter_mut()
+        .filter_map(|method| method.code.as_mut())
+    {
+        rewrite_code(code, constants, f)?;
+    }
+    Ok(())
+}
+
+fn rewrite_code(
+    code: &mut CodeAttribute,
+    constants: &ConstantPool,
+    f: &mut dyn FnMut(u16) -> u16,
+) -> Result<()> {
+    rewrite_instructions(&mut code.code, f)?;
+    for handler in code.exception_table.iter_mut() {
+        handler.catch_type = f(handler.catch_type);
+    }
+    for attribute in code.attributes.iter_mut() {
+        rewrite_attribute(&attribute.name, &mut attribute.info, constants, f)?;
+    }
+    Ok(())
+}
+
+fn rewrite_instructions(code: &mut [u8], f: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+    for instruction in decode(code)? {
+        if let Some(index) = instruction.constant_index() {
+            // The index always follows the opcode, and only ldc has a single byte one
+            let at = instruction.offset as usize + 1;
+            let index = f(index);
+            if instruction.opcode == Opcode::Ldc {
+                code[at] = u8::try_from(index).map_err(|_| {
+                    ClassReaderError::InvalidClassData(format!(
+                        "ldc at offset {} cannot refer to constant {}",
+                        instruction.offset, index
+                    ))
+                })?;
+            } else {
+                code[at..at + 2].copy_from_slice(&index.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+// Replaces every constant pool index in the body of an attribute, which must be supported. The
+// pool must match the indices that f returns, as the names of nested attributes are looked up.
+// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7
+fn rewrite_attribute(
+    name: &str,
+    info: &mut [u8],
+    constants: &ConstantPool,
+    f: &mut dyn FnMut(u16) -> u16,
+) -> Result<()> {
+    let mut cursor = IndexCursor {
+        name,
+        data: info,
+        position: 0,
+    };
+    match name {
+        "ConstantValue" | "Signature" | "SourceFile" | "NestHost" => {
+            cursor.index(f)?;
+        }
+        "Exceptions" | "NestMembers" | "PermittedSubclasses" => {
+            for _ in 0..cursor.u16()? {
+                cursor.index(f)?;
+            }
+        }
+        "EnclosingMethod" => {
+            cursor.index(f)?;
+            cursor.index(f)?;
+        }
+        "InnerClasses" => {
+            for _ in 0..cursor.u16()? {
+                cursor.index(f)?;
+                cursor.index(f)?;
+                cursor.index(f)?;
+                cursor.skip(2)?;
+            }
+        }
+        "BootstrapMethods" => {
+            for _ in 0..cursor.u16()? {
+                cursor.index(f)?;
+                for _ in 0..cursor.u16()? {
+                    cursor.index(f)?;
+                }
+            }
+        }
+        "MethodParameters" => {
+            for _ in 0..cursor.u8()? {
+                cursor.index(f)?;
+                cursor.skip(2)?;
+            }
+        }
+        "LocalVariableTable" | "LocalVariableTypeTable" => {
+            for _ in 0..cursor.u16()? {
+                cursor.skip(4)?;
+                cursor.index(f)?;
+                cursor.index(f)?;
+                cursor.skip(2)?;
+            }
+        }
+        "StackMapTable" => {
+            for _ in 0..cursor.u16()? {
+                rewrite_frame(&mut cursor, f)?;
+            }
+        }
+        "Code" => {
+            cursor.skip(4)?;
+            let length = cursor.u32()? as usize;
+            let start = cursor.position;
+            cursor.skip(length)?;
+            rewrite_instructions(&mut cursor.data[start..start + length], f)?;
+            for _ in 0..cursor.u16()? {
+                cursor.skip(6)?;
+                cursor.index(f)?;
+            }
+            for _ in 0..cursor.u16()? {
+                let name = constants.text_of(cursor.index(f)?)?;
+                let length = cursor.u32()? as usize;
+                let start = cursor.position;
+                cursor.skip(length)?;
+                rewrite_attribute(&name, &mut cursor.data[start..start + length], constants, f)?;
+            }
+        }
+        // Attributes without any index
+        _ => {}
+    }
+    Ok(())
+}
+
+// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7.4
+fn rewrite_frame(cursor: &mut IndexCursor, f: &mut dyn FnMut(u16) -> u16) -> Result<()> {
+    let frame_type = cursor.u8()?;
+    let (has_offset, types) = match frame_type {
+        0..=63 => (false, 0),
+        64..=127 => (false, 1),
+        247 => (true, 1),
+        248..=251 => (true, 0),
+        252..=254 => (true, frame_type - 251),
+        255 => {
+            cursor.skip(2)?;
+            for _ in 0..2 {
+                for _ in 0..cursor.u16()? {
+                    rewrite_verification_type(cursor, f)?;
+                }
+            }
+            return Ok(());
+        }
+        _ => {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "invalid stack map frame type {}",
+                frame_type
+            )))
+        }
+    };
+    if has_offset {
+        cursor.skip(2)?;
+    }
+    for _ in 0..types {
+        rewrite_verification_type(cursor, f)?;
+    }
+    Ok(())
+}
+
+fn rewrite_verification_type(
+    cursor: &mut IndexCursor,
+    f: &mut dyn FnMut(u16) -> u16,
+) -> Result<()> {
+    match cursor.u8()? {
+        // Object, by the index of its class
+        7 => {
+            cursor.index(f)?;
+        }
+        // Uninitialized, by the offset of its new instruction
+        8 => cursor.skip(2)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+// Reads the body of an attribute, writing back the indices it replaces
+struct IndexCursor<'a> {
+    name: &'a str,
+    data: &'a mut [u8],
+    position: usize,
+}
+
+impl IndexCursor<'_> {
+    fn skip(&mut self, count: usize) -> Result<()> {
+        if self.data.len() - self.position < count {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "{} attribute is truncated",
+                self.name
+            )));
+        }
+        self.position += count;
+        Ok(())
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let start = self.position;
+        self.skip(N)?;
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(&self.data[start..self.position]);
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes()?))
+    }
+
+    // Replaces the index at the cursor, returning the new one
+    fn index(&mut self, f: &mut dyn FnMut(u16) -> u16) -> Result<u16> {
+        let index = f(self.u16()?);
+        self.data[self.position - 2..self.position].copy_from_slice(&index.to_be_bytes());
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::attribute::Attribute;
+    use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+    use crate::class_file::ClassFile;
+    use crate::class_file_method::ClassFileMethod;
+    use crate::code_attribute::CodeAttribute;
+    use crate::instruction::{decode, ResolvedOperand};
+    use crate::method_flags::MethodFlags;
+
+    #[test]
+    fn indices_after_dead_entries_are_shifted() {
+        let mut constants = ConstantPool::new();
+        constants
+            .add(ConstantPoolEntry::Utf8("dead".to_string()))
+            .unwrap();
+        constants.add(ConstantPoolEntry::Double(2.5)).unwrap();
+        constants
+            .add(ConstantPoolEntry::Utf8("Foo".to_string()))
+            .unwrap();
+        constants.add(ConstantPoolEntry::ClassReference(4)).unwrap();
+        constants
+            .add(ConstantPoolEntry::Utf8("m".to_string()))
+            .unwrap();
+        constants
+            .add(ConstantPoolEntry::Utf8("()V".to_string()))
+            .unwrap();
+        constants
+            .add(ConstantPoolEntry::Utf8("Code".to_string()))
+            .unwrap();
+        constants.add(ConstantPoolEntry::Long(7)).unwrap();
+
+        // static void m() { long x = 7; }, with ldc2_w #9
+        let code = vec![0x14, 0x00, 0x09, 0x58, 0xB1];
+        let mut info = vec![0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05];
+        info.extend(&code);
+        info.extend([0x00, 0x00, 0x00, 0x00]);
+        let mut class = ClassFile {
+            constants,
+            name: "Foo".to_string(),
+            methods: vec![ClassFileMethod {
+                flags: MethodFlags::STATIC,
+                name: "m".to_string(),
+                type_descriptor: "()V".to_string(),
+                attributes: vec![Attribute {
+                    name: "Code".to_string(),
+                    info,
+                }],
+                code: Some(CodeAttribute {
+                    max_stack: 2,
+                    code,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        class.compact_constant_pool().unwrap();
+        assert_eq!(
+            vec![
+                (1, &ConstantPoolEntry::Utf8("Foo".to_string())),
+                (2, &ConstantPoolEntry::ClassReference(1)),
+                (3, &ConstantPoolEntry::Utf8("m".to_string())),
+                (4, &ConstantPoolEntry::Utf8("()V".to_string())),
+                (5, &ConstantPoolEntry::Utf8("Code".to_string())),
+                (6, &ConstantPoolEntry::Long(7)),
+            ],
+            class.constants.iter().collect::<Vec<_>>()
+        );
+        let method = &class.methods[0];
+        assert_eq!(
+            vec![0x14, 0x00, 0x06, 0x58, 0xB1],
+            method.code.as_ref().unwrap().code
+        );
+        assert_eq!(&[0x14, 0x00, 0x06], &method.attributes[0].info[8..11]);
+        let ldc = &decode(&method.code.as_ref().unwrap().code).unwrap()[0];
+        assert_eq!(
+            Ok(Some(ResolvedOperand::Long(7))),
+            ldc.resolve(&class.constants)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::attribute::Attribute;
+use crate::buffer::BufferReader;
+use crate::c_pool::ConstantPool;
+use crate::class_file::ClassFile;
+use crate::class_file_field::ClassFileField;
+use crate::class_file_method::ClassFileMethod;
+use crate::code_attribute::CodeAttribute;
+use crate::instruction::{decode, Operand};
+use crate::raw_flags::RawFlags;
+
+// Attributes that only carry debug information, which javac leaves out with -g:none
+const DEBUG_ATTRIBUTES: [&str; 5] = [
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "SourceFile",
+    "SourceDebugExtension",
+];
+
+/// Whether two classes are the same apart from their debug information and the layout of their
+/// constant pools. Constant pool indices are compared by what they refer to wherever the format
+/// of the attribute is known: in the code, the exception tables, ConstantValue, Signature and
+/// Exceptions attributes. Other attributes are compared byte for byte, so the StackMapTable,
+/// InnerClasses, BootstrapMethods and annotation attributes of two classes only match when the
+/// entries they refer to are at the same indices.
+pub fn equivalent_ignoring_debug(a: &ClassFile, b: &ClassFile) -> bool {
+    let pools = (&a.constants, &b.constants);
+    a.version == b.version
+        && a.minor_version == b.minor_version
+        && a.raw_flags() == b.raw_flags()
+        && a.name == b.name
+        && a.superclass == b.superclass
+        && a.interfaces == b.interfaces
+        && all_equivalent(&a.fields, &b.fields, |x, y| fields_equivalent(x, y, pools))
+        && all_equivalent(&a.methods, &b.methods, |x, y| {
+            methods_equivalent(x, y, pools)
+        })
+        && attributes_equivalent(&a.attributes, &b.attributes, pools)
+}
+
+fn all_equivalent<T>(a: &[T], b: &[T], equivalent: impl Fn(&T, &T) -> bool) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| equivalent(x, y))
+}
+
+fn fields_equivalent(
+    a: &ClassFileField,
+    b: &ClassFileField,
+    pools: (&ConstantPool, &ConstantPool),
+) -> bool {
+    a.raw_flags() == b.raw_flags()
+        && a.name == b.name
+        && a.type_descriptor == b.type_descriptor
+        && a.constant_value == b.constant_value
+        && attributes_equivalent(&a.attributes, &b.attributes, pools)
+}
+
+fn methods_equivalent(
+    a: &ClassFileMethod,
+    b: &ClassFileMethod,
+    pools: (&ConstantPool, &ConstantPool),
+) -> bool {
+    let same_code = match (&a.code, &b.code) {
+        (Some(x), Some(y)) => code_equivalent(x, y, pools),
+        (None, None) => true,
+        _ => false,
+    };
+    a.raw_flags() == b.raw_flags()
+        && a.name == b.name
+        && a.type_descriptor == b.type_descriptor
+        && same_code
+        && attributes_equivalent(&a.attributes, &b.attributes, pools)
+}
+
+fn code_equivalent(
+    a: &CodeAttribute,
+    b: &CodeAttribute,
+    pools: (&ConstantPool, &ConstantPool),
+) -> bool {
+    let (instructions_a, instructions_b) = match (decode(&a.code), decode(&b.code)) {
+        (Ok(x), Ok(y)) => (x, y),
+        _ => return false,
+    };
+    let handlers_equivalent = all_equivalent(&a.exception_table, &b.exception_table, |x, y| {
+        (x.start_pc, x.end_pc, x.handler_pc) == (y.start_pc, y.end_pc, y.handler_pc)
+            && class_name(pools.0, x.catch_type) == class_name(pools.1, y.catch_type)
+    });
+    a.max_stack == b.max_stack
+        && a.max_locals == b.max_locals
+        && all_equivalent(&instructions_a, &instructions_b, |x, y| {
+            (x.offset, x.opcode, x.wide) == (y.offset, y.opcode, y.wide)
+                && without_index(&x.operand) == without_index(&y.operand)
+                && matches!(
+                    (x.resolve(pools.0), y.resolve(pools.1)),
+                    (Ok(p), Ok(q)) if p == q
+                )
+        })
+        && handlers_equivalent
+        && attributes_equivalent(&a.attributes, &b.attributes, pools)
+}
+
+// The operand with its constant pool index, if any, left out
+fn without_index(operand: &Operand) -> Operand {
+    match *operand {
+        Operand::Constant(_) => Operand::Constant(0),
+        Operand::InterfaceCall { count, .. } => Operand::InterfaceCall { index: 0, count },
+        Operand::MultiArray { dimensions, .. } => Operand::MultiArray {
+            index: 0,
+            dimensions,
+        },
+        ref operand => operand.clone(),
+    }
+}
+
+fn attributes_equivalent(
+    a: &[Attribute],
+    b: &[Attribute],
+    pools: (&ConstantPool, &ConstantPool),
+) -> bool {
+    all_equivalent(&comparable(a), &comparable(b), |x, y| {
+        x.name == y.name
+            && match x.name.as_str() {
+                "Signature" => {
+                    let text = |info: &[u8], pool: &ConstantPool| {
+                        let index = BufferReader::new(info).read_u16().ok()?;
+                        pool.text_of(index).ok()
+                    };
+                    text(&x.info, pools.0).is_some()
+                        && text(&x.info, pools.0) == text(&y.info, pools.1)
+                }
+                "Exceptions" => {
+                    let classes = |info: &[u8], pool: &ConstantPool| {
+                        let mut buffer = BufferReader::new(info);
+                        let count = buffer.read_u16().ok()?;
+                        (0..count)
+                            .map(|_| class_name(pool, buffer.read_u16().ok()?))
+                            .collect::<Option<Vec<String>>>()
+                    };
+                    classes(&x.info, pools.0).is_some()
+                        && classes(&x.info, pools.0) == classes(&y.info, pools.1)
+                }
+                _ => x.info == y.info,
+            }
+    })
+}
+
+// The attributes that are compared as such: the code is compared on its own, and the constant
+// value of fields as decoded
+fn comparable(attributes: &[Attribute]) -> Vec<&Attribute> {
+    attributes
+        .iter()
+        .filter(|attr| {
+            !DEBUG_ATTRIBUTES.contains(&attr.name.as_str())
+                && attr.name != "Code"
+                && attr.name != "ConstantValue"
+        })
+        .collect()
+}
+
+// The name of the class at the given index, with 0 standing for no class
+fn class_name(pool: &ConstantPool, index: u16) -> Option<String> {
+    match index {
+        0 => Some(String::new()),
+        _ => pool.class_name_at(index).ok(),
+    }
+}
@@ -0,0 +1,118 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::fmt::Formatter;
+
+use crate::class_reader_error::{ClassReaderError, Result};
+
+/// The type of a field, parameter or return value, as given by a field descriptor.
+/// https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-4.html#jvms-4.3.2
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+    /// A class or interface, by its internal name like java/lang/String.
+    Object(String),
+    Array(Box<FieldType>),
+}
+
+impl FieldType {
+    /// Parses a complete field descriptor, like I or [Ljava/lang/String;.
+    pub fn parse(descriptor: &str) -> Result<FieldType> {
+        match FieldType::parse_prefix(descriptor) {
+            Some((field_type, "")) => Ok(field_type),
+            _ => Err(ClassReaderError::InvalidClassData(format!(
+                "invalid field descriptor: {}",
+                descriptor
+            ))),
+        }
+    }
+
+    // Parses the type at the start of the descriptor, returning it with the rest of the text
+    pub(crate) fn parse_prefix(descriptor: &str) -> Option<(FieldType, &str)> {
+        let mut chars = descriptor.chars();
+        let field_type = match chars.next()? {
+            'B' => FieldType::Byte,
+            'C' => FieldType::Char,
+            'D' => FieldType::Double,
+            'F' => FieldType::Float,
+            'I' => FieldType::Int,
+            'J' => FieldType::Long,
+            'S' => FieldType::Short,
+            'Z' => FieldType::Boolean,
+            'L' => {
+                let (name, rest) = descriptor[1..].split_once(';')?;
+                if name.is_empty() {
+                    return None;
+                }
+                return Some((FieldType::Object(name.to_string()), rest));
+            }
+            '[' => {
+                // Arrays have at most 255 dimensions
+                let element = descriptor.trim_start_matches('[');
+                let dimensions = descriptor.len() - element.len();
+                if dimensions > 255 {
+                    return None;
+                }
+                let (mut field_type, rest) = FieldType::parse_prefix(element)?;
+                for _ in 0..dimensions {
+                    field_type = FieldType::Array(Box::new(field_type));
+                }
+                return Some((field_type, rest));
+            }
+            _ => return None,
+        };
+        Some((field_type, chars.as_str()))
+    }
+
+    /// Whether values of this type take two slots in the local variables and operand stack.
+    pub fn is_wide(&self) -> bool {
+        matches!(self, FieldType::Long | FieldType::Double)
+    }
+}
+
+/// Renders the type as in Java source, like int[] or java.lang.String.
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Byte => write!(f, "byte"),
+            FieldType::Char => write!(f, "char"),
+            FieldType::Double => write!(f, "double"),
+            FieldType::Float => write!(f, "float"),
+            FieldType::Int => write!(f, "int"),
+            FieldType::Long => write!(f, "long"),
+            FieldType::Short => write!(f, "short"),
+            FieldType::Boolean => write!(f, "boolean"),
+            FieldType::Object(name) => write!(f, "{}", name.replace('/', ".")),
+            FieldType::Array(element) => write!(f, "{}[]", element),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::ToString;
+
+    use crate::field_type::FieldType;
+
+    #[test]
+    fn descriptors_are_parsed() {
+        assert_eq!(Ok(FieldType::Long), FieldType::parse("J"));
+        assert_eq!(
+            "java.lang.String[]",
+            FieldType::parse("[Ljava/lang/String;").unwrap().to_string()
+        );
+        assert!(FieldType::parse("").is_err());
+        assert!(FieldType::parse("II").is_err());
+        assert!(FieldType::parse("Ljava/lang/String").is_err());
+        assert!(FieldType::parse(&format!("{}I", "[".repeat(256))).is_err());
+    }
+}
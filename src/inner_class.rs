@@ -0,0 +1,92 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::buffer::BufferReader;
+use crate::c_pool::ConstantPool;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::inner_class_flags::InnerClassFlags;
+use crate::raw_flags::RawFlags;
+
+/// An entry of the InnerClasses attribute, resolved against the constant pool.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7.6
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InnerClass {
+    /// Internal name of the nested class, like java/util/Map$Entry.
+    pub inner: String,
+    /// Internal name of the class it is a member of, absent for local and anonymous classes.
+    pub outer: Option<String>,
+    /// Simple name of the class as in the source, absent for anonymous classes.
+    pub name: Option<String>,
+    pub flags: InnerClassFlags,
+    /// Access flag bits that no flag is defined for, see `RawFlags`.
+    pub reserved_flags: u16,
+}
+
+impl RawFlags for InnerClass {
+    fn flag_bits(&self) -> u16 {
+        self.flags.bits()
+    }
+
+    fn reserved_bits(&self) -> u16 {
+        self.reserved_flags
+    }
+}
+
+/// Decodes the body of an InnerClasses attribute.
+pub fn read_inner_classes(info: &[u8], constants: &ConstantPool) -> Result<Vec<InnerClass>> {
+    let mut buffer = BufferReader::new(info);
+    let count = buffer.read_u16()?;
+    let mut classes = Vec::new();
+    for _ in 0..count {
+        let inner = constants.class_name_at(buffer.read_u16()?)?;
+        let outer = match buffer.read_u16()? {
+            0 => None,
+            index => Some(constants.class_name_at(index)?),
+        };
+        let name = match buffer.read_u16()? {
+            0 => None,
+            index => Some(constants.text_of(index)?),
+        };
+        let bits = buffer.read_u16()?;
+        classes.push(InnerClass {
+            inner,
+            outer,
+            name,
+            flags: InnerClassFlags::from_bits_truncate(bits),
+            reserved_flags: bits & !InnerClassFlags::all().bits(),
+        });
+    }
+    if buffer.remaining() > 0 {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "{} unexpected bytes after the inner classes",
+            buffer.remaining()
+        )));
+    }
+    Ok(classes)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+    use crate::inner_class::read_inner_classes;
+    use crate::inner_class_flags::InnerClassFlags;
+    use crate::raw_flags::RawFlags;
+
+    #[test]
+    fn reserved_flag_bits_are_kept() {
+        let mut cp = ConstantPool::new();
+        cp.add(ConstantPoolEntry::Utf8("A$B".to_string())).unwrap();
+        cp.add(ConstantPoolEntry::ClassReference(1)).unwrap();
+        // One entry without outer class or name, public with the unassigned bit 0x0100 set
+        let info = [0, 1, 0, 2, 0, 0, 0, 0, 0x01, 0x01];
+
+        let classes = read_inner_classes(&info, &cp).unwrap();
+
+        assert_eq!(InnerClassFlags::PUBLIC, classes[0].flags);
+        assert_eq!(0x0100, classes[0].reserved_flags);
+        assert_eq!(0x0101, classes[0].raw_flags());
+    }
+}
@@ -0,0 +1,22 @@
+bitflags! {
+    /// The access flags of a nested class, as given by the InnerClasses attribute. Unlike the
+    /// flags of the class itself, they can make it private, protected or static.
+    pub struct InnerClassFlags: u16 {
+        const PUBLIC = 0x0001;
+        const PRIVATE = 0x0002;
+        const PROTECTED = 0x0004;
+        const STATIC = 0x0008;
+        const FINAL = 0x0010;
+        const INTERFACE = 0x0200;
+        const ABSTRACT = 0x0400;
+        const SYNTHETIC = 0x1000;
+        const ANNOTATION = 0x2000;
+        const ENUM = 0x4000;
+    }
+}
+
+impl Default for InnerClassFlags {
+    fn default() -> InnerClassFlags {
+        InnerClassFlags::empty()
+    }
+}
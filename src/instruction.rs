@@ -0,0 +1,1077 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+
+use crate::buffer::BufferReader;
+use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::field_type::FieldType;
+
+macro_rules! opcodes {
+    ($($name:ident = $value:literal => $mnemonic:literal,)*) => {
+        /// The opcodes of the JVM instruction set.
+        /// https://docs.oracle.com/javase/specs/jvms/se7/html/jvms-6.html#jvms-6.5
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Opcode {
+            $($name = $value,)*
+        }
+
+        impl Opcode {
+            pub fn from_u8(value: u8) -> Option<Opcode> {
+                match value {
+                    $($value => Some(Opcode::$name),)*
+                    _ => None,
+                }
+            }
+
+            pub fn mnemonic(&self) -> &'static str {
+                match self {
+                    $(Opcode::$name => $mnemonic,)*
+                }
+            }
+        }
+    };
+}
+
+opcodes! {
+    Nop = 0x00 => "nop",
+    AconstNull = 0x01 => "aconst_null",
+    IconstM1 = 0x02 => "iconst_m1",
+    Iconst0 = 0x03 => "iconst_0",
+    Iconst1 = 0x04 => "iconst_1",
+    Iconst2 = 0x05 => "iconst_2",
+    Iconst3 = 0x06 => "iconst_3",
+    Iconst4 = 0x07 => "iconst_4",
+    Iconst5 = 0x08 => "iconst_5",
+    Lconst0 = 0x09 => "lconst_0",
+    Lconst1 = 0x0A => "lconst_1",
+    Fconst0 = 0x0B => "fconst_0",
+    Fconst1 = 0x0C => "fconst_1",
+    Fconst2 = 0x0D => "fconst_2",
+    Dconst0 = 0x0E => "dconst_0",
+    Dconst1 = 0x0F => "dconst_1",
+    Bipush = 0x10 => "bipush",
+    Sipush = 0x11 => "sipush",
+    Ldc = 0x12 => "ldc",
+    LdcW = 0x13 => "ldc_w",
+    Ldc2W = 0x14 => "ldc2_w",
+    Iload = 0x15 => "iload",
+    Lload = 0x16 => "lload",
+    Fload = 0x17 => "fload",
+    Dload = 0x18 => "dload",
+    Aload = 0x19 => "aload",
+    Iload0 = 0x1A => "iload_0",
+    Iload1 = 0x1B => "iload_1",
+    Iload2 = 0x1C => "iload_2",
+    Iload3 = 0x1D => "iload_3",
+    Lload0 = 0x1E => "lload_0",
+    Lload1 = 0x1F => "lload_1",
+    Lload2 = 0x20 => "lload_2",
+    Lload3 = 0x21 => "lload_3",
+    Fload0 = 0x22 => "fload_0",
+    Fload1 = 0x23 => "fload_1",
+    Fload2 = 0x24 => "fload_2",
+    Fload3 = 0x25 => "fload_3",
+    Dload0 = 0x26 => "dload_0",
+    Dload1 = 0x27 => "dload_1",
+    Dload2 = 0x28 => "dload_2",
+    Dload3 = 0x29 => "dload_3",
+    Aload0 = 0x2A => "aload_0",
+    Aload1 = 0x2B => "aload_1",
+    Aload2 = 0x2C => "aload_2",
+    Aload3 = 0x2D => "aload_3",
+    Iaload = 0x2E => "iaload",
+    Laload = 0x2F => "laload",
+    Faload = 0x30 => "faload",
+    Daload = 0x31 => "daload",
+    Aaload = 0x32 => "aaload",
+    Baload = 0x33 => "baload",
+    Caload = 0x34 => "caload",
+    Saload = 0x35 => "saload",
+    Istore = 0x36 => "istore",
+    Lstore = 0x37 => "lstore",
+    Fstore = 0x38 => "fstore",
+    Dstore = 0x39 => "dstore",
+    Astore = 0x3A => "astore",
+    Istore0 = 0x3B => "istore_0",
+    Istore1 = 0x3C => "istore_1",
+    Istore2 = 0x3D => "istore_2",
+    Istore3 = 0x3E => "istore_3",
+    Lstore0 = 0x3F => "lstore_0",
+    Lstore1 = 0x40 => "lstore_1",
+    Lstore2 = 0x41 => "lstore_2",
+    Lstore3 = 0x42 => "lstore_3",
+    Fstore0 = 0x43 => "fstore_0",
+    Fstore1 = 0x44 => "fstore_1",
+    Fstore2 = 0x45 => "fstore_2",
+    Fstore3 = 0x46 => "fstore_3",
+    Dstore0 = 0x47 => "dstore_0",
+    Dstore1 = 0x48 => "dstore_1",
+    Dstore2 = 0x49 => "dstore_2",
+    Dstore3 = 0x4A => "dstore_3",
+    Astore0 = 0x4B => "astore_0",
+    Astore1 = 0x4C => "astore_1",
+    Astore2 = 0x4D => "astore_2",
+    Astore3 = 0x4E => "astore_3",
+    Iastore = 0x4F => "iastore",
+    Lastore = 0x50 => "lastore",
+    Fastore = 0x51 => "fastore",
+    Dastore = 0x52 => "dastore",
+    Aastore = 0x53 => "aastore",
+    Bastore = 0x54 => "bastore",
+    Castore = 0x55 => "castore",
+    Sastore = 0x56 => "sastore",
+    Pop = 0x57 => "pop",
+    Pop2 = 0x58 => "pop2",
+    Dup = 0x59 => "dup",
+    DupX1 = 0x5A => "dup_x1",
+    DupX2 = 0x5B => "dup_x2",
+    Dup2 = 0x5C => "dup2",
+    Dup2X1 = 0x5D => "dup2_x1",
+    Dup2X2 = 0x5E => "dup2_x2",
+    Swap = 0x5F => "swap",
+    Iadd = 0x60 => "iadd",
+    Ladd = 0x61 => "ladd",
+    Fadd = 0x62 => "fadd",
+    Dadd = 0x63 => "dadd",
+    Isub = 0x64 => "isub",
+    Lsub = 0x65 => "lsub",
+    Fsub = 0x66 => "fsub",
+    Dsub = 0x67 => "dsub",
+    Imul = 0x68 => "imul",
+    Lmul = 0x69 => "lmul",
+    Fmul = 0x6A => "fmul",
+    Dmul = 0x6B => "dmul",
+    Idiv = 0x6C => "idiv",
+    Ldiv = 0x6D => "ldiv",
+    Fdiv = 0x6E => "fdiv",
+    Ddiv = 0x6F => "ddiv",
+    Irem = 0x70 => "irem",
+    Lrem = 0x71 => "lrem",
+    Frem = 0x72 => "frem",
+    Drem = 0x73 => "drem",
+    Ineg = 0x74 => "ineg",
+    Lneg = 0x75 => "lneg",
+    Fneg = 0x76 => "fneg",
+    Dneg = 0x77 => "dneg",
+    Ishl = 0x78 => "ishl",
+    Lshl = 0x79 => "lshl",
+    Ishr = 0x7A => "ishr",
+    Lshr = 0x7B => "lshr",
+    Iushr = 0x7C => "iushr",
+    Lushr = 0x7D => "lushr",
+    Iand = 0x7E => "iand",
+    Land = 0x7F => "land",
+    Ior = 0x80 => "ior",
+    Lor = 0x81 => "lor",
+    Ixor = 0x82 => "ixor",
+    Lxor = 0x83 => "lxor",
+    Iinc = 0x84 => "iinc",
+    I2l = 0x85 => "i2l",
+    I2f = 0x86 => "i2f",
+    I2d = 0x87 => "i2d",
+    L2i = 0x88 => "l2i",
+    L2f = 0x89 => "l2f",
+    L2d = 0x8A => "l2d",
+    F2i = 0x8B => "f2i",
+    F2l = 0x8C => "f2l",
+    F2d = 0x8D => "f2d",
+    D2i = 0x8E => "d2i",
+    D2l = 0x8F => "d2l",
+    D2f = 0x90 => "d2f",
+    I2b = 0x91 => "i2b",
+    I2c = 0x92 => "i2c",
+    I2s = 0x93 => "i2s",
+    Lcmp = 0x94 => "lcmp",
+    Fcmpl = 0x95 => "fcmpl",
+    Fcmpg = 0x96 => "fcmpg",
+    Dcmpl = 0x97 => "dcmpl",
+    Dcmpg = 0x98 => "dcmpg",
+    Ifeq = 0x99 => "ifeq",
+    Ifne = 0x9A => "ifne",
+    Iflt = 0x9B => "iflt",
+    Ifge = 0x9C => "ifge",
+    Ifgt = 0x9D => "ifgt",
+    Ifle = 0x9E => "ifle",
+    IfIcmpeq = 0x9F => "if_icmpeq",
+    IfIcmpne = 0xA0 => "if_icmpne",
+    IfIcmplt = 0xA1 => "if_icmplt",
+    IfIcmpge = 0xA2 => "if_icmpge",
+    IfIcmpgt = 0xA3 => "if_icmpgt",
+    IfIcmple = 0xA4 => "if_icmple",
+    IfAcmpeq = 0xA5 => "if_acmpeq",
+    IfAcmpne = 0xA6 => "if_acmpne",
+    Goto = 0xA7 => "goto",
+    Jsr = 0xA8 => "jsr",
+    Ret = 0xA9 => "ret",
+    Tableswitch = 0xAA => "tableswitch",
+    Lookupswitch = 0xAB => "lookupswitch",
+    Ireturn = 0xAC => "ireturn",
+    Lreturn = 0xAD => "lreturn",
+    Freturn = 0xAE => "freturn",
+    Dreturn = 0xAF => "dreturn",
+    Areturn = 0xB0 => "areturn",
+    Return = 0xB1 => "return",
+    Getstatic = 0xB2 => "getstatic",
+    Putstatic = 0xB3 => "putstatic",
+    Getfield = 0xB4 => "getfield",
+    Putfield = 0xB5 => "putfield",
+    Invokevirtual = 0xB6 => "invokevirtual",
+    Invokespecial = 0xB7 => "invokespecial",
+    Invokestatic = 0xB8 => "invokestatic",
+    Invokeinterface = 0xB9 => "invokeinterface",
+    Invokedynamic = 0xBA => "invokedynamic",
+    New = 0xBB => "new",
+    Newarray = 0xBC => "newarray",
+    Anewarray = 0xBD => "anewarray",
+    Arraylength = 0xBE => "arraylength",
+    Athrow = 0xBF => "athrow",
+    Checkcast = 0xC0 => "checkcast",
+    Instanceof = 0xC1 => "instanceof",
+    Monitorenter = 0xC2 => "monitorenter",
+    Monitorexit = 0xC3 => "monitorexit",
+    Wide = 0xC4 => "wide",
+    Multianewarray = 0xC5 => "multianewarray",
+    Ifnull = 0xC6 => "ifnull",
+    Ifnonnull = 0xC7 => "ifnonnull",
+    GotoW = 0xC8 => "goto_w",
+    JsrW = 0xC9 => "jsr_w",
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic())
+    }
+}
+
+/// The operands of an instruction, as encoded in the bytecode.
+/// Branch offsets are relative to the offset of the instruction itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    None,
+    Byte(i8),
+    Short(i16),
+    Constant(u16),
+    Local(u16),
+    Increment {
+        local: u16,
+        delta: i16,
+    },
+    Branch(i32),
+    InterfaceCall {
+        index: u16,
+        count: u8,
+    },
+    ArrayType(u8),
+    MultiArray {
+        index: u16,
+        dimensions: u8,
+    },
+    TableSwitch {
+        default: i32,
+        low: i32,
+        high: i32,
+        offsets: Vec<i32>,
+    },
+    LookupSwitch {
+        default: i32,
+        pairs: Vec<(i32, i32)>,
+    },
+}
+
+/// A decoded instruction of a method body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    /// Offset of the instruction from the start of the code.
+    pub offset: u32,
+    pub opcode: Opcode,
+    pub operand: Operand,
+    /// Whether the instruction was prefixed by `wide`.
+    pub wide: bool,
+}
+
+/// A constant pool operand of an instruction, resolved to what it names.
+#[derive(Debug, Clone)]
+pub enum ResolvedOperand {
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    String(String),
+    Class(String),
+    Field {
+        owner: String,
+        name: String,
+        descriptor: String,
+    },
+    Method {
+        owner: String,
+        name: String,
+        descriptor: String,
+    },
+    InterfaceMethod {
+        owner: String,
+        name: String,
+        descriptor: String,
+    },
+    /// A method handle, with its reference kind (1 to 9) and the member it refers to.
+    MethodHandle {
+        kind: u8,
+        owner: String,
+        name: String,
+        descriptor: String,
+    },
+    /// A method type, given by its descriptor.
+    MethodType(String),
+    /// A dynamically computed constant, with the index of its bootstrap method.
+    Dynamic {
+        bootstrap: u16,
+        name: String,
+        descriptor: String,
+    },
+    /// A dynamically computed call site, with the index of its bootstrap method.
+    InvokeDynamic {
+        bootstrap: u16,
+        name: String,
+        descriptor: String,
+    },
+}
+
+// Float and double constants are compared by their bits, like the entries they are read from
+impl PartialEq for ResolvedOperand {
+    fn eq(&self, other: &Self) -> bool {
+        use ResolvedOperand::*;
+        match (self, other) {
+            (Integer(a), Integer(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Long(a), Long(b)) => a == b,
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (String(a), String(b)) | (Class(a), Class(b)) | (MethodType(a), MethodType(b)) => {
+                a == b
+            }
+            (
+                Field {
+                    owner: a,
+                    name: b,
+                    descriptor: c,
+                },
+                Field {
+                    owner: x,
+                    name: y,
+                    descriptor: z,
+                },
+            )
+            | (
+                Method {
+                    owner: a,
+                    name: b,
+                    descriptor: c,
+                },
+                Method {
+                    owner: x,
+                    name: y,
+                    descriptor: z,
+                },
+            )
+            | (
+                InterfaceMethod {
+                    owner: a,
+                    name: b,
+                    descriptor: c,
+                },
+                InterfaceMethod {
+                    owner: x,
+                    name: y,
+                    descriptor: z,
+                },
+            ) => (a, b, c) == (x, y, z),
+            (
+                MethodHandle {
+                    kind: k,
+                    owner: a,
+                    name: b,
+                    descriptor: c,
+                },
+                MethodHandle {
+                    kind: l,
+                    owner: x,
+                    name: y,
+                    descriptor: z,
+                },
+            ) => (k, a, b, c) == (l, x, y, z),
+            (
+                Dynamic {
+                    bootstrap: k,
+                    name: b,
+                    descriptor: c,
+                },
+                Dynamic {
+                    bootstrap: l,
+                    name: y,
+                    descriptor: z,
+                },
+            )
+            | (
+                InvokeDynamic {
+                    bootstrap: k,
+                    name: b,
+                    descriptor: c,
+                },
+                InvokeDynamic {
+                    bootstrap: l,
+                    name: y,
+                    descriptor: z,
+                },
+            ) => (k, b, c) == (l, y, z),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ResolvedOperand {}
+
+impl fmt::Display for ResolvedOperand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolvedOperand::Integer(n) => write!(f, "int {}", n),
+            ResolvedOperand::Float(n) => write!(f, "float {}", n),
+            ResolvedOperand::Long(n) => write!(f, "long {}", n),
+            ResolvedOperand::Double(n) => write!(f, "double {}", n),
+            ResolvedOperand::String(s) => write!(f, "String {}", s),
+            ResolvedOperand::Class(name) => write!(f, "class {}", name),
+            ResolvedOperand::Field {
+                owner,
+                name,
+                descriptor,
+            } => write!(f, "Field {}.{}:{}", owner, name, descriptor),
+            ResolvedOperand::Method {
+                owner,
+                name,
+                descriptor,
+            } => write!(f, "Method {}.{}:{}", owner, name, descriptor),
+            ResolvedOperand::InterfaceMethod {
+                owner,
+                name,
+                descriptor,
+            } => write!(f, "InterfaceMethod {}.{}:{}", owner, name, descriptor),
+            ResolvedOperand::MethodHandle {
+                kind,
+                owner,
+                name,
+                descriptor,
+            } => write!(
+                f,
+                "MethodHandle {} {}.{}:{}",
+                reference_kind_name(*kind),
+                owner,
+                name,
+                descriptor
+            ),
+            ResolvedOperand::MethodType(descriptor) => write!(f, "MethodType {}", descriptor),
+            ResolvedOperand::Dynamic {
+                bootstrap,
+                name,
+                descriptor,
+            } => write!(f, "Dynamic #{}:{}:{}", bootstrap, name, descriptor),
+            ResolvedOperand::InvokeDynamic {
+                bootstrap,
+                name,
+                descriptor,
+            } => write!(f, "InvokeDynamic #{}:{}:{}", bootstrap, name, descriptor),
+        }
+    }
+}
+
+impl Instruction {
+    /// Index of the constant pool entry used by the instruction, if any.
+    pub fn constant_index(&self) -> Option<u16> {
+        match self.operand {
+            Operand::Constant(index)
+            | Operand::InterfaceCall { index, .. }
+            | Operand::MultiArray { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Resolves the constant pool operand of the instruction, if it has one.
+    pub fn resolve(&self, constants: &ConstantPool) -> Result<Option<ResolvedOperand>> {
+        let index = match self.constant_index() {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        match resolve_constant(constants, index)? {
+            Some(resolved) if self.can_load(&resolved) => Ok(Some(resolved)),
+            _ => Err(self.invalid_constant(constants.get(index)?)),
+        }
+    }
+
+    // Whether the constant is of a kind that the instruction accepts. Only the ldc family is
+    // checked: ldc2_w loads the two slot constants, ldc and ldc_w all the other loadable ones.
+    fn can_load(&self, resolved: &ResolvedOperand) -> bool {
+        match self.opcode {
+            Opcode::Ldc | Opcode::LdcW => match resolved {
+                ResolvedOperand::Integer(_)
+                | ResolvedOperand::Float(_)
+                | ResolvedOperand::String(_)
+                | ResolvedOperand::Class(_)
+                | ResolvedOperand::MethodHandle { .. }
+                | ResolvedOperand::MethodType(_) => true,
+                ResolvedOperand::Dynamic { descriptor, .. } => {
+                    FieldType::parse(descriptor).is_ok_and(|t| !t.is_wide())
+                }
+                _ => false,
+            },
+            Opcode::Ldc2W => match resolved {
+                ResolvedOperand::Long(_) | ResolvedOperand::Double(_) => true,
+                ResolvedOperand::Dynamic { descriptor, .. } => {
+                    FieldType::parse(descriptor).is_ok_and(|t| t.is_wide())
+                }
+                _ => false,
+            },
+            _ => true,
+        }
+    }
+
+    fn invalid_constant(&self, entry: &ConstantPoolEntry) -> ClassReaderError {
+        ClassReaderError::InvalidClassData(format!(
+            "invalid constant for {}: {:?}",
+            self.opcode, entry
+        ))
+    }
+
+    /// Renders the instruction in a javap-like form, with its constant operand resolved.
+    pub fn describe(&self, constants: &ConstantPool) -> Result<String> {
+        let text = match (&self.operand, self.resolve(constants)?) {
+            (Operand::InterfaceCall { index, count }, Some(resolved)) => {
+                format!("{} #{}, {} // {}", self.opcode, index, count, resolved)
+            }
+            (Operand::MultiArray { index, dimensions }, Some(resolved)) => {
+                format!("{} #{}, {} // {}", self.opcode, index, dimensions, resolved)
+            }
+            (_, Some(resolved)) => format!(
+                "{} #{} // {}",
+                self.opcode,
+                self.constant_index().unwrap_or_default(),
+                resolved
+            ),
+            (_, None) => self.to_string(),
+        };
+        Ok(text)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.opcode)?;
+        match &self.operand {
+            Operand::None => Ok(()),
+            Operand::Byte(n) => write!(f, " {}", n),
+            Operand::Short(n) => write!(f, " {}", n),
+            Operand::Constant(index) => write!(f, " #{}", index),
+            Operand::Local(index) => write!(f, " {}", index),
+            Operand::Increment { local, delta } => write!(f, " {}, {}", local, delta),
+            Operand::Branch(offset) => write!(f, " {}", self.offset as i64 + *offset as i64),
+            Operand::InterfaceCall { index, count } => write!(f, " #{}, {}", index, count),
+            Operand::ArrayType(atype) => write!(f, " {}", atype),
+            Operand::MultiArray { index, dimensions } => {
+                write!(f, " #{}, {}", index, dimensions)
+            }
+            Operand::TableSwitch {
+                default, low, high, ..
+            } => write!(f, " {} to {}, default: {}", low, high, default),
+            Operand::LookupSwitch { default, pairs } => {
+                write!(f, " {} cases, default: {}", pairs.len(), default)
+            }
+        }
+    }
+}
+
+fn reference_kind_name(kind: u8) -> &'static str {
+    match kind {
+        1 => "REF_getField",
+        2 => "REF_getStatic",
+        3 => "REF_putField",
+        4 => "REF_putStatic",
+        5 => "REF_invokeVirtual",
+        6 => "REF_invokeStatic",
+        7 => "REF_invokeSpecial",
+        8 => "REF_newInvokeSpecial",
+        9 => "REF_invokeInterface",
+        _ => "REF_unknown",
+    }
+}
+
+// Resolves a loadable constant, or a member reference, to what it names. Other entries, like
+// Utf8 and NameAndType ones, give None.
+pub(crate) fn resolve_constant(
+    constants: &ConstantPool,
+    index: u16,
+) -> Result<Option<ResolvedOperand>> {
+    let entry = constants.get(index)?;
+    let resolved = match entry {
+        ConstantPoolEntry::Integer(n) => ResolvedOperand::Integer(*n),
+        ConstantPoolEntry::Float(n) => ResolvedOperand::Float(*n),
+        ConstantPoolEntry::Long(n) => ResolvedOperand::Long(*n),
+        ConstantPoolEntry::Double(n) => ResolvedOperand::Double(*n),
+        ConstantPoolEntry::StringReference(n) => ResolvedOperand::String(constants.text_of(*n)?),
+        ConstantPoolEntry::ClassReference(n) => ResolvedOperand::Class(constants.text_of(*n)?),
+        ConstantPoolEntry::FieldReference(class, name_and_type) => {
+            let (owner, name, descriptor) = resolve_member(constants, *class, *name_and_type)?;
+            ResolvedOperand::Field {
+                owner,
+                name,
+                descriptor,
+            }
+        }
+        ConstantPoolEntry::MethodReference(class, name_and_type) => {
+            let (owner, name, descriptor) = resolve_member(constants, *class, *name_and_type)?;
+            ResolvedOperand::Method {
+                owner,
+                name,
+                descriptor,
+            }
+        }
+        ConstantPoolEntry::InterfaceMethodReference(class, name_and_type) => {
+            let (owner, name, descriptor) = resolve_member(constants, *class, *name_and_type)?;
+            ResolvedOperand::InterfaceMethod {
+                owner,
+                name,
+                descriptor,
+            }
+        }
+        ConstantPoolEntry::MethodHandle(kind, reference) => {
+            let (owner, name, descriptor) = match constants.get(*reference)? {
+                ConstantPoolEntry::FieldReference(class, name_and_type)
+                | ConstantPoolEntry::MethodReference(class, name_and_type)
+                | ConstantPoolEntry::InterfaceMethodReference(class, name_and_type) => {
+                    resolve_member(constants, *class, *name_and_type)?
+                }
+                target => {
+                    return Err(ClassReaderError::InvalidClassData(format!(
+                        "invalid method handle target: {:?}",
+                        target
+                    )))
+                }
+            };
+            ResolvedOperand::MethodHandle {
+                kind: *kind,
+                owner,
+                name,
+                descriptor,
+            }
+        }
+        ConstantPoolEntry::MethodType(n) => ResolvedOperand::MethodType(constants.text_of(*n)?),
+        ConstantPoolEntry::Dynamic(bootstrap, name_and_type) => {
+            let (name, descriptor) = constants.name_and_type_at(*name_and_type)?;
+            ResolvedOperand::Dynamic {
+                bootstrap: *bootstrap,
+                name,
+                descriptor,
+            }
+        }
+        ConstantPoolEntry::InvokeDynamic(bootstrap, name_and_type) => {
+            let (name, descriptor) = constants.name_and_type_at(*name_and_type)?;
+            ResolvedOperand::InvokeDynamic {
+                bootstrap: *bootstrap,
+                name,
+                descriptor,
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(resolved))
+}
+
+fn resolve_member(
+    constants: &ConstantPool,
+    class: u16,
+    name_and_type: u16,
+) -> Result<(String, String, String)> {
+    let owner = constants.text_of(class)?;
+    let (name, descriptor) = constants.name_and_type_at(name_and_type)?;
+    Ok((owner, name, descriptor))
+}
+
+/// Decodes the bytecode of a method body.
+pub fn decode(code: &[u8]) -> Result<Vec<Instruction>> {
+    let mut buffer = BufferReader::new(code);
+    let mut instructions = Vec::new();
+    while buffer.remaining() > 0 {
+        instructions.push(decode_instruction(&mut buffer)?);
+    }
+    Ok(instructions)
+}
+
+fn decode_instruction(buffer: &mut BufferReader) -> Result<Instruction> {
+    let offset = buffer.position() as u32;
+    let opcode = read_opcode(buffer)?;
+    if opcode == Opcode::Wide {
+        return decode_wide_instruction(buffer, offset);
+    }
+
+    let operand = match opcode {
+        Opcode::Bipush => Operand::Byte(buffer.read_i8()?),
+        Opcode::Sipush => Operand::Short(buffer.read_i16()?),
+        Opcode::Ldc => Operand::Constant(buffer.read_u8()? as u16),
+        Opcode::LdcW
+        | Opcode::Ldc2W
+        | Opcode::Getstatic
+        | Opcode::Putstatic
+        | Opcode::Getfield
+        | Opcode::Putfield
+        | Opcode::Invokevirtual
+        | Opcode::Invokespecial
+        | Opcode::Invokestatic
+        | Opcode::New
+        | Opcode::Anewarray
+        | Opcode::Checkcast
+        | Opcode::Instanceof => Operand::Constant(buffer.read_u16()?),
+        Opcode::Iload
+        | Opcode::Lload
+        | Opcode::Fload
+        | Opcode::Dload
+        | Opcode::Aload
+        | Opcode::Istore
+        | Opcode::Lstore
+        | Opcode::Fstore
+        | Opcode::Dstore
+        | Opcode::Astore
+        | Opcode::Ret => Operand::Local(buffer.read_u8()? as u16),
+        Opcode::Iinc => Operand::Increment {
+            local: buffer.read_u8()? as u16,
+            delta: buffer.read_i8()? as i16,
+        },
+        Opcode::Ifeq
+        | Opcode::Ifne
+        | Opcode::Iflt
+        | Opcode::Ifge
+        | Opcode::Ifgt
+        | Opcode::Ifle
+        | Opcode::IfIcmpeq
+        | Opcode::IfIcmpne
+        | Opcode::IfIcmplt
+        | Opcode::IfIcmpge
+        | Opcode::IfIcmpgt
+        | Opcode::IfIcmple
+        | Opcode::IfAcmpeq
+        | Opcode::IfAcmpne
+        | Opcode::Goto
+        | Opcode::Jsr
+        | Opcode::Ifnull
+        | Opcode::Ifnonnull => Operand::Branch(buffer.read_i16()? as i32),
+        Opcode::GotoW | Opcode::JsrW => Operand::Branch(buffer.read_i32()?),
+        Opcode::Invokeinterface => {
+            let index = buffer.read_u16()?;
+            let count = buffer.read_u8()?;
+            if count == 0 || buffer.read_u8()? != 0 {
+                return Err(ClassReaderError::InvalidClassData(format!(
+                    "invalid invokeinterface operands at offset {}",
+                    offset
+                )));
+            }
+            Operand::InterfaceCall { index, count }
+        }
+        Opcode::Invokedynamic => {
+            let index = buffer.read_u16()?;
+            if buffer.read_u16()? != 0 {
+                return Err(ClassReaderError::InvalidClassData(format!(
+                    "invalid invokedynamic operands at offset {}",
+                    offset
+                )));
+            }
+            Operand::Constant(index)
+        }
+        Opcode::Newarray => Operand::ArrayType(buffer.read_u8()?),
+        Opcode::Multianewarray => Operand::MultiArray {
+            index: buffer.read_u16()?,
+            dimensions: buffer.read_u8()?,
+        },
+        Opcode::Tableswitch => {
+            skip_switch_padding(buffer)?;
+            let default = buffer.read_i32()?;
+            let low = buffer.read_i32()?;
+            let high = buffer.read_i32()?;
+            if high < low {
+                return Err(ClassReaderError::InvalidClassData(format!(
+                    "invalid tableswitch bounds at offset {}: {} to {}",
+                    offset, low, high
+                )));
+            }
+            let count = (high as i64 - low as i64 + 1) as u64;
+            check_entries_fit(buffer, count, 4)?;
+            let offsets = (0..count)
+                .map(|_| buffer.read_i32())
+                .collect::<Result<Vec<i32>>>()?;
+            Operand::TableSwitch {
+                default,
+                low,
+                high,
+                offsets,
+            }
+        }
+        Opcode::Lookupswitch => {
+            skip_switch_padding(buffer)?;
+            let default = buffer.read_i32()?;
+            let npairs = buffer.read_i32()?;
+            let count = u64::try_from(npairs).map_err(|_| {
+                ClassReaderError::InvalidClassData(format!(
+                    "invalid lookupswitch pair count at offset {}: {}",
+                    offset, npairs
+                ))
+            })?;
+            check_entries_fit(buffer, count, 8)?;
+            let pairs = (0..count)
+                .map(|_| Ok((buffer.read_i32()?, buffer.read_i32()?)))
+                .collect::<Result<Vec<(i32, i32)>>>()?;
+            Operand::LookupSwitch { default, pairs }
+        }
+        _ => Operand::None,
+    };
+
+    Ok(Instruction {
+        offset,
+        opcode,
+        operand,
+        wide: false,
+    })
+}
+
+fn decode_wide_instruction(buffer: &mut BufferReader, offset: u32) -> Result<Instruction> {
+    let opcode = read_opcode(buffer)?;
+    let operand = match opcode {
+        Opcode::Iload
+        | Opcode::Lload
+        | Opcode::Fload
+        | Opcode::Dload
+        | Opcode::Aload
+        | Opcode::Istore
+        | Opcode::Lstore
+        | Opcode::Fstore
+        | Opcode::Dstore
+        | Opcode::Astore
+        | Opcode::Ret => Operand::Local(buffer.read_u16()?),
+        Opcode::Iinc => Operand::Increment {
+            local: buffer.read_u16()?,
+            delta: buffer.read_i16()?,
+        },
+        _ => {
+            return Err(ClassReaderError::InvalidClassData(format!(
+                "invalid opcode after wide at offset {}: {}",
+                offset, opcode
+            )))
+        }
+    };
+    Ok(Instruction {
+        offset,
+        opcode,
+        operand,
+        wide: true,
+    })
+}
+
+fn read_opcode(buffer: &mut BufferReader) -> Result<Opcode> {
+    let offset = buffer.position();
+    let value = buffer.read_u8()?;
+    Opcode::from_u8(value).ok_or_else(|| {
+        ClassReaderError::InvalidClassData(format!(
+            "invalid opcode at offset {}: 0x{:X}",
+            offset, value
+        ))
+    })
+}
+
+// Switch operands start at the next offset that is a multiple of four
+fn skip_switch_padding(buffer: &mut BufferReader) -> Result<()> {
+    let padding = (4 - buffer.position() % 4) % 4;
+    buffer.read_bytes(padding).map(|_| ())
+}
+
+fn check_entries_fit(buffer: &BufferReader, count: u64, entry_size: u64) -> Result<()> {
+    match count.checked_mul(entry_size) {
+        Some(size) if buffer.can_read(size) => Ok(()),
+        _ => Err(ClassReaderError::InvalidClassData(
+            "class does not have expected length".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+    use crate::instruction::{decode, Instruction, Opcode, Operand, ResolvedOperand};
+
+    #[test]
+    fn can_decode_instructions() {
+        let code = vec![
+            0x2A, // aload_0
+            0xB9, 0x00, 0x07, 0x01, 0x00, // invokeinterface #7, 1
+            0x99, 0xFF, 0xFA, // ifeq 0
+            0xC4, 0x84, 0x01, 0x00, 0xFF, 0xFF, // wide iinc 256, -1
+            0xAC, // ireturn
+        ];
+        let instructions = decode(&code).unwrap();
+        assert_eq!(
+            vec![
+                Instruction {
+                    offset: 0,
+                    opcode: Opcode::Aload0,
+                    operand: Operand::None,
+                    wide: false,
+                },
+                Instruction {
+                    offset: 1,
+                    opcode: Opcode::Invokeinterface,
+                    operand: Operand::InterfaceCall { index: 7, count: 1 },
+                    wide: false,
+                },
+                Instruction {
+                    offset: 6,
+                    opcode: Opcode::Ifeq,
+                    operand: Operand::Branch(-6),
+                    wide: false,
+                },
+                Instruction {
+                    offset: 9,
+                    opcode: Opcode::Iinc,
+                    operand: Operand::Increment {
+                        local: 256,
+                        delta: -1
+                    },
+                    wide: true,
+                },
+                Instruction {
+                    offset: 15,
+                    opcode: Opcode::Ireturn,
+                    operand: Operand::None,
+                    wide: false,
+                },
+            ],
+            instructions
+        );
+        assert_eq!("ifeq 0", instructions[2].to_string());
+    }
+
+    #[test]
+    fn invokeinterface_requires_trailing_zero() {
+        let code = vec![0xB9, 0x00, 0x07, 0x01, 0x01];
+        assert!(decode(&code).is_err());
+    }
+    #[test]
+    fn loadable_constants_are_resolved_by_kind() {
+        let mut constants = ConstantPool::new();
+        constants
+            .add(ConstantPoolEntry::Utf8("(I)V".to_string()))
+            .unwrap();
+        constants.add(ConstantPoolEntry::MethodType(1)).unwrap();
+        constants.add(ConstantPoolEntry::Long(7)).unwrap();
+
+        let instructions = decode(&[
+            0x12, 0x02, // ldc #2
+            0x14, 0x00, 0x02, // ldc2_w #2
+            0x12, 0x03, // ldc #3
+            0x14, 0x00, 0x03, // ldc2_w #3
+        ])
+        .unwrap();
+        assert_eq!(
+            Some(ResolvedOperand::MethodType("(I)V".to_string())),
+            instructions[0].resolve(&constants).unwrap()
+        );
+        assert_eq!(
+            "ldc #2 // MethodType (I)V",
+            instructions[0].describe(&constants).unwrap()
+        );
+        assert!(instructions[1].resolve(&constants).is_err());
+        assert!(instructions[2].resolve(&constants).is_err());
+        assert_eq!(
+            Some(ResolvedOperand::Long(7)),
+            instructions[3].resolve(&constants).unwrap()
+        );
+    }
+    #[test]
+    fn floating_point_operands_are_compared_by_their_bits() {
+        let mut constants = ConstantPool::new();
+        constants.add(ConstantPoolEntry::Float(f32::NAN)).unwrap();
+        let ldc = &decode(&[0x12, 0x01]).unwrap()[0];
+        assert_eq!(
+            ldc.resolve(&constants).unwrap(),
+            ldc.resolve(&constants).unwrap()
+        );
+        assert_ne!(ResolvedOperand::Double(0.0), ResolvedOperand::Double(-0.0));
+    }
+    #[test]
+    fn subroutine_instructions_have_their_operand_widths() {
+        let code = vec![
+            0xA8, 0x00, 0x09, // jsr +9
+            0xC9, 0x00, 0x00, 0x00, 0x06, // jsr_w +6
+            0xB1, // return
+            0x4C, // astore_1
+            0xA9, 0x01, // ret 1
+            0xC4, 0xA9, 0x01, 0x00, // wide ret 256
+        ];
+        let instructions = decode(&code).unwrap();
+        assert_eq!(
+            vec![
+                (0, Opcode::Jsr, Operand::Branch(9), false),
+                (3, Opcode::JsrW, Operand::Branch(6), false),
+                (8, Opcode::Return, Operand::None, false),
+                (9, Opcode::Astore1, Operand::None, false),
+                (10, Opcode::Ret, Operand::Local(1), false),
+                (12, Opcode::Ret, Operand::Local(256), true),
+            ],
+            instructions
+                .into_iter()
+                .map(|i| (i.offset, i.opcode, i.operand, i.wide))
+                .collect::<Vec<(u32, Opcode, Operand, bool)>>()
+        );
+    }
+    #[test]
+    fn switches_are_decoded_past_their_padding() {
+        let code = vec![
+            0x1A, // iload_0
+            0xAB, 0x00, 0x00, // lookupswitch, padded to offset 4
+            0x00, 0x00, 0x00, 0x2F, // default +47
+            0x00, 0x00, 0x00, 0x02, // 2 pairs
+            0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x2B, // -1: +43
+            0x00, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x2D, // 10: +45
+            0xAA, 0x00, 0x00, 0x00, // tableswitch at 28, padded to offset 32
+            0x00, 0x00, 0x00, 0x14, // default +20
+            0x00, 0x00, 0x00, 0x01, // low 1
+            0x00, 0x00, 0x00, 0x02, // high 2
+            0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x15, // +20, +21
+            0xAC, // ireturn
+        ];
+        let instructions = decode(&code).unwrap();
+        assert_eq!(
+            vec![
+                (0, Operand::None),
+                (
+                    1,
+                    Operand::LookupSwitch {
+                        default: 47,
+                        pairs: vec![(-1, 43), (10, 45)],
+                    }
+                ),
+                (
+                    28,
+                    Operand::TableSwitch {
+                        default: 20,
+                        low: 1,
+                        high: 2,
+                        offsets: vec![20, 21],
+                    }
+                ),
+                (52, Operand::None),
+            ],
+            instructions
+                .into_iter()
+                .map(|i| (i.offset, i.operand))
+                .collect::<Vec<(u32, Operand)>>()
+        );
+    }
+}
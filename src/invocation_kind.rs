@@ -0,0 +1,13 @@
+/// The instruction that calls a method, which follows from how the JVM dispatches it.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-6.html#jvms-6.5.invokevirtual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InvocationKind {
+    /// invokestatic, for static methods, including those of interfaces.
+    Static,
+    /// invokespecial, for constructors and private methods, which are not dispatched.
+    Special,
+    /// invokevirtual, for the other methods of classes.
+    Virtual,
+    /// invokeinterface, for the other methods of interfaces.
+    Interface,
+}
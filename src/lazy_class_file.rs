@@ -0,0 +1,86 @@
+use alloc::format;
+
+use crate::class_file::ClassFile;
+use crate::class_reader::{read_buffer_without_code, read_code_attribute};
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::code_attribute::CodeAttribute;
+use crate::reader_options::ReaderOptions;
+
+/// A class whose method bodies are decoded only when requested.
+///
+/// Everything but the Code attributes is read up front. Their raw bytes stay in the attributes
+/// of each method, whose `code` is left empty, so a class with a corrupt method body can still
+/// be read as long as that body is never asked for.
+#[derive(Debug)]
+pub struct LazyClassFile {
+    class: ClassFile,
+    options: ReaderOptions,
+}
+
+impl LazyClassFile {
+    pub fn read(data: &[u8]) -> Result<LazyClassFile> {
+        LazyClassFile::read_with_options(data, &ReaderOptions::default())
+    }
+
+    pub fn read_with_options(data: &[u8], options: &ReaderOptions) -> Result<LazyClassFile> {
+        Ok(LazyClassFile {
+            class: read_buffer_without_code(data, options)?,
+            options: options.clone(),
+        })
+    }
+
+    /// The class, with the `code` of every method left as `None`.
+    pub fn class(&self) -> &ClassFile {
+        &self.class
+    }
+
+    /// Decodes the Code attribute of the method at the given index.
+    pub fn method_body(&self, idx: usize) -> Result<CodeAttribute> {
+        let method = self.class.methods.get(idx).ok_or_else(|| {
+            ClassReaderError::InvalidClassData(format!("no method at index {}", idx))
+        })?;
+        let attribute = method
+            .attributes
+            .iter()
+            .find(|attr| attr.name == "Code")
+            .ok_or_else(|| {
+                ClassReaderError::InvalidClassData(format!("method {} has no code", method.name))
+            })?;
+        read_code_attribute(&attribute.info, &self.class, &self.options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class_reader::read_buffer;
+    use crate::lazy_class_file::LazyClassFile;
+
+    #[test]
+    fn method_bodies_are_decoded_on_demand() {
+        let data = [
+            0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x32, // version 50.0
+            0x00, 0x06, // 5 constants
+            0x01, 0x00, 0x03, b'F', b'o', b'o', // Utf8 "Foo"
+            0x07, 0x00, 0x01, // Class Foo
+            0x01, 0x00, 0x01, b'm', // Utf8 "m"
+            0x01, 0x00, 0x03, b'(', b')', b'V', // Utf8 "()V"
+            0x01, 0x00, 0x04, b'C', b'o', b'd', b'e', // Utf8 "Code"
+            0x00, 0x21, 0x00, 0x02, 0x00, 0x00, // public class Foo, no superclass
+            0x00, 0x00, // interfaces
+            0x00, 0x00, // fields
+            0x00, 0x01, // methods
+            0x00, 0x01, 0x00, 0x03, 0x00, 0x04, 0x00, 0x01, // public m()V, 1 attribute
+            0x00, 0x05, 0x00, 0x00, 0x00, 0x09, // Code, 9 bytes
+            0x01, 0x02, 0x00, 0x01, 0xB1, // too short for the sizes of version 50
+            0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, // class attributes
+        ];
+        assert!(read_buffer(&data).is_err());
+
+        let lazy = LazyClassFile::read(&data).unwrap();
+        assert_eq!("m", lazy.class().methods[0].name);
+        assert!(lazy.class().methods[0].code.is_none());
+        assert!(lazy.method_body(0).is_err());
+        assert!(lazy.method_body(1).is_err());
+    }
+}
@@ -1,3 +1,9 @@
+// The crate is named Fejvm, which rustc warns about as it is not snake case
+#![allow(non_snake_case)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 #[macro_use]
 extern crate bitflags;
 
@@ -6,10 +12,30 @@ pub mod class_file_field;
 pub mod field_flags;
 pub mod method_flags;
 mod buffer;
-mod c_pool;
+pub mod c_pool;
 pub mod class_file;
 pub mod class_reader;
 pub mod class_reader_error;
 pub mod class_access_flags;
 pub mod class_file_version;
-pub mod class_file_method;
\ No newline at end of file
+pub mod class_file_method;
+pub mod cfg;
+pub mod code_attribute;
+pub mod instruction;
+pub mod reader_options;
+pub mod member_ref;
+pub mod field_type;
+pub mod names;
+pub mod verifier;
+pub mod annotation;
+pub mod lazy_class_file;
+pub mod signature;
+pub mod parse_warning;
+pub mod bootstrap_method;
+pub mod equivalence;
+pub mod inner_class_flags;
+pub mod inner_class;
+pub mod compaction;
+pub mod nest_role;
+pub mod invocation_kind;
+pub mod raw_flags;
\ No newline at end of file
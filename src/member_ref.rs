@@ -0,0 +1,19 @@
+use alloc::string::String;
+
+/// The kind of member named by a constant pool reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemberRefKind {
+    Field,
+    Method,
+    InterfaceMethod,
+}
+
+/// A field or method referenced from the constant pool, resolved to the class declaring it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MemberRef {
+    pub kind: MemberRefKind,
+    /// Internal name of the class, like java/lang/Object.
+    pub owner: String,
+    pub name: String,
+    pub descriptor: String,
+}
@@ -1,3 +1,6 @@
+use core::fmt;
+use core::fmt::Formatter;
+
 bitflags! {
     pub struct MethodFlags: u16 {
         const PUBLIC = 0x0001;
@@ -19,4 +22,29 @@ impl Default for MethodFlags {
     fn default() -> MethodFlags {
         MethodFlags::empty()
     }
-}
\ No newline at end of file
+}
+
+// Renders the flags that are source keywords, like "public static final"
+impl fmt::Display for MethodFlags {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let keywords = [
+            (MethodFlags::PUBLIC, "public"),
+            (MethodFlags::PROTECTED, "protected"),
+            (MethodFlags::PRIVATE, "private"),
+            (MethodFlags::ABSTRACT, "abstract"),
+            (MethodFlags::STATIC, "static"),
+            (MethodFlags::FINAL, "final"),
+            (MethodFlags::SYNCHRONIZED, "synchronized"),
+            (MethodFlags::NATIVE, "native"),
+            (MethodFlags::STRICT, "strictfp"),
+        ];
+        let mut separator = "";
+        for (flag, keyword) in keywords {
+            if self.contains(flag) {
+                write!(f, "{}{}", separator, keyword)?;
+                separator = " ";
+            }
+        }
+        Ok(())
+    }
+}
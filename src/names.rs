@@ -0,0 +1,37 @@
+use alloc::string::String;
+
+// Array types keep their descriptor form, as Class.getName() does: [Ljava/lang/String; becomes
+// [Ljava.lang.String;. Nested classes keep their $ separator.
+
+/// Converts an internal name, like java/util/Map$Entry, to a binary name, like java.util.Map$Entry.
+pub fn internal_to_binary(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+/// Converts a binary name, like java.util.Map$Entry, to an internal name, like java/util/Map$Entry.
+pub fn binary_to_internal(name: &str) -> String {
+    name.replace('.', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::names::{binary_to_internal, internal_to_binary};
+
+    #[test]
+    fn names_can_be_converted_both_ways() {
+        assert_eq!(
+            "java.util.Map$Entry",
+            internal_to_binary("java/util/Map$Entry")
+        );
+        assert_eq!(
+            "java/util/Map$Entry",
+            binary_to_internal("java.util.Map$Entry")
+        );
+        assert_eq!(
+            "[[Ljava.lang.String;",
+            internal_to_binary("[[Ljava/lang/String;")
+        );
+        assert_eq!("[I", binary_to_internal("[I"));
+        assert_eq!("Foo", internal_to_binary("Foo"));
+    }
+}
@@ -0,0 +1,48 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::buffer::BufferReader;
+use crate::c_pool::ConstantPool;
+use crate::class_reader_error::{ClassReaderError, Result};
+
+/// The place of a class in its nest, the classes that may access each other's private members.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-5.html#jvms-5.4.4
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestRole {
+    /// The class is the host of a nest with the given members, from its NestMembers attribute.
+    Host(Vec<String>),
+    /// The class is a member of the nest of the given host, from its NestHost attribute.
+    Member(String),
+    /// The class declares neither, so it is alone in its own nest.
+    None,
+}
+
+/// Decodes the body of a NestHost attribute into the internal name of the host.
+pub fn read_nest_host(info: &[u8], constants: &ConstantPool) -> Result<String> {
+    let mut buffer = BufferReader::new(info);
+    let host = constants.class_name_at(buffer.read_u16()?)?;
+    if buffer.remaining() > 0 {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "{} unexpected bytes after the nest host",
+            buffer.remaining()
+        )));
+    }
+    Ok(host)
+}
+
+/// Decodes the body of a NestMembers attribute into the internal names of the members.
+pub fn read_nest_members(info: &[u8], constants: &ConstantPool) -> Result<Vec<String>> {
+    let mut buffer = BufferReader::new(info);
+    let count = buffer.read_u16()?;
+    let members = (0..count)
+        .map(|_| Ok(constants.class_name_at(buffer.read_u16()?)?))
+        .collect::<Result<Vec<String>>>()?;
+    if buffer.remaining() > 0 {
+        return Err(ClassReaderError::InvalidClassData(format!(
+            "{} unexpected bytes after the nest members",
+            buffer.remaining()
+        )));
+    }
+    Ok(members)
+}
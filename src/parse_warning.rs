@@ -0,0 +1,204 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Formatter;
+
+use crate::class_access_flags::ClassAccessFlags;
+use crate::class_file::{AttributeLocation, ClassFile};
+use crate::field_flags::FieldFlags;
+use crate::method_flags::MethodFlags;
+
+// Attributes defined by the JVM specification, up to Java 17
+const KNOWN_ATTRIBUTES: [&str; 30] = [
+    "AnnotationDefault",
+    "BootstrapMethods",
+    "Code",
+    "ConstantValue",
+    "Deprecated",
+    "EnclosingMethod",
+    "Exceptions",
+    "InnerClasses",
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "MethodParameters",
+    "Module",
+    "ModuleMainClass",
+    "ModulePackages",
+    "NestHost",
+    "NestMembers",
+    "PermittedSubclasses",
+    "Record",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+    "RuntimeVisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "Signature",
+    "SourceDebugExtension",
+    "SourceFile",
+    "StackMapTable",
+    "Synthetic",
+];
+
+/// Something unusual about a class that does not prevent reading it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// An attribute that the JVM specification does not define, which the JVM ignores.
+    UnknownAttribute { name: String, location: String },
+    /// Access flag bits that no flag is defined for.
+    ReservedFlags { location: String, bits: u16 },
+    /// Access flags that the JVM specification does not allow together.
+    ConflictingFlags {
+        location: String,
+        reason: &'static str,
+    },
+    /// Bytes after the end of the class.
+    TrailingData { length: usize },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::UnknownAttribute { name, location } => {
+                write!(f, "unknown attribute {} in {}", name, location)
+            }
+            ParseWarning::ReservedFlags { location, bits } => {
+                write!(f, "reserved flags 0x{:04X} set on {}", bits, location)
+            }
+            ParseWarning::ConflictingFlags { location, reason } => {
+                write!(f, "{} has conflicting flags: {}", location, reason)
+            }
+            ParseWarning::TrailingData { length } => {
+                write!(f, "{} unexpected bytes after the end of the class", length)
+            }
+        }
+    }
+}
+
+/// A class along with the warnings raised while reading it.
+#[derive(Debug, PartialEq)]
+pub struct ReadOutcome {
+    pub class: ClassFile,
+    pub warnings: Vec<ParseWarning>,
+}
+
+// Looks for the issues of a class that was read successfully, given the number of bytes left
+// after it
+pub(crate) fn check(class: &ClassFile, trailing_data: usize) -> Vec<ParseWarning> {
+    let mut warnings = Vec::new();
+    for (location, attribute) in class.all_attributes() {
+        if !KNOWN_ATTRIBUTES.contains(&attribute.name.as_str()) {
+            warnings.push(ParseWarning::UnknownAttribute {
+                name: attribute.name.clone(),
+                location: describe(&location),
+            });
+        }
+    }
+
+    let mut flag_issues = |location: String, reserved: u16, conflict: Option<&'static str>| {
+        if reserved != 0 {
+            warnings.push(ParseWarning::ReservedFlags {
+                location: location.clone(),
+                bits: reserved,
+            });
+        }
+        if let Some(reason) = conflict {
+            warnings.push(ParseWarning::ConflictingFlags { location, reason });
+        }
+    };
+    flag_issues(
+        describe(&AttributeLocation::Class),
+        class.reserved_flags,
+        class_flags_conflict(class.flags),
+    );
+    for field in &class.fields {
+        flag_issues(
+            describe(&AttributeLocation::Field(field)),
+            field.reserved_flags,
+            field_flags_conflict(field.flags),
+        );
+    }
+    for method in &class.methods {
+        flag_issues(
+            describe(&AttributeLocation::Method(method)),
+            method.reserved_flags,
+            method_flags_conflict(method.flags),
+        );
+    }
+
+    if trailing_data > 0 {
+        warnings.push(ParseWarning::TrailingData {
+            length: trailing_data,
+        });
+    }
+    warnings
+}
+
+fn describe(location: &AttributeLocation) -> String {
+    match location {
+        AttributeLocation::Class => String::from("the class"),
+        AttributeLocation::Field(field) => format!("field {}", field.name),
+        AttributeLocation::Method(method) => {
+            format!("method {}{}", method.name, method.type_descriptor)
+        }
+        AttributeLocation::Code(method) => {
+            format!(
+                "the code of method {}{}",
+                method.name, method.type_descriptor
+            )
+        }
+    }
+}
+
+// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.1
+fn class_flags_conflict(flags: ClassAccessFlags) -> Option<&'static str> {
+    if flags.contains(ClassAccessFlags::INTERFACE) {
+        if !flags.contains(ClassAccessFlags::ABSTRACT) {
+            return Some("interface without abstract");
+        }
+        if flags
+            .intersects(ClassAccessFlags::FINAL | ClassAccessFlags::SUPER | ClassAccessFlags::ENUM)
+        {
+            return Some("interface with final, super or enum");
+        }
+    } else if flags.contains(ClassAccessFlags::ANNOTATION) {
+        return Some("annotation without interface");
+    }
+    if flags.contains(ClassAccessFlags::ABSTRACT | ClassAccessFlags::FINAL) {
+        return Some("abstract and final");
+    }
+    None
+}
+
+// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.5
+fn field_flags_conflict(flags: FieldFlags) -> Option<&'static str> {
+    let visibility = flags & (FieldFlags::PUBLIC | FieldFlags::PRIVATE | FieldFlags::PROTECTED);
+    if visibility.bits().count_ones() > 1 {
+        return Some("more than one of public, private and protected");
+    }
+    if flags.contains(FieldFlags::FINAL | FieldFlags::VOLATILE) {
+        return Some("final and volatile");
+    }
+    None
+}
+
+// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.6
+fn method_flags_conflict(flags: MethodFlags) -> Option<&'static str> {
+    let visibility = flags & (MethodFlags::PUBLIC | MethodFlags::PRIVATE | MethodFlags::PROTECTED);
+    if visibility.bits().count_ones() > 1 {
+        return Some("more than one of public, private and protected");
+    }
+    let concrete = MethodFlags::PRIVATE
+        | MethodFlags::STATIC
+        | MethodFlags::FINAL
+        | MethodFlags::SYNCHRONIZED
+        | MethodFlags::NATIVE
+        | MethodFlags::STRICT;
+    if flags.contains(MethodFlags::ABSTRACT) && flags.intersects(concrete) {
+        return Some("abstract with private, static, final, synchronized, native or strict");
+    }
+    None
+}
@@ -0,0 +1,15 @@
+/// Access flags as they were read, including the bits that no flag is defined for. The JVM
+/// ignores those, but obfuscators set them, so they are kept next to the parsed flags and the
+/// original value can be reproduced exactly.
+pub trait RawFlags {
+    /// The bits of the flags that are defined.
+    fn flag_bits(&self) -> u16;
+
+    /// The bits that no flag is defined for.
+    fn reserved_bits(&self) -> u16;
+
+    /// The access flags as they were read, reserved bits included.
+    fn raw_flags(&self) -> u16 {
+        self.flag_bits() | self.reserved_bits()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::class_file_version::ClassFileVersion;
+
+/// Options that control how a class file is read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReaderOptions {
+    /// Largest attribute body, in bytes, that the reader will accept.
+    pub max_attribute_length: u32,
+    /// Largest number of constant pool entries that the reader will accept.
+    pub max_constant_pool_entries: u16,
+    /// Largest number of methods that the reader will accept.
+    pub max_method_count: u16,
+    /// Whether bytes after the end of the class are ignored rather than rejected.
+    pub allow_trailing_data: bool,
+    /// Whether invalid modified UTF-8 is decoded with replacement characters rather than rejected.
+    pub lossy_strings: bool,
+    /// Whether names that refer to missing constant pool entries are read as placeholders like
+    /// `<unresolved #N>` rather than rejected.
+    pub lenient_references: bool,
+    /// Version of the running JVM. When set in strict mode, classes that need preview features
+    /// are rejected unless they were compiled for exactly this version, as the JVM itself does.
+    pub current_version: Option<ClassFileVersion>,
+    /// Whether fields and methods that are neither public nor protected are left out. Their
+    /// attributes are skipped without being decoded.
+    pub public_api_only: bool,
+    /// Whether the bytes of each Utf8 constant are kept as they were read, for
+    /// `ConstantPool::raw_utf8_at`.
+    pub keep_raw_utf8: bool,
+    /// Whether rules of the JVM specification that the reader otherwise lets through are
+    /// enforced: `<init>` methods must return void, `<clinit>` must be static with `()V`, and
+    /// preview classes must match `current_version`.
+    pub strict: bool,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> ReaderOptions {
+        ReaderOptions {
+            max_attribute_length: u32::MAX,
+            max_constant_pool_entries: u16::MAX,
+            max_method_count: u16::MAX,
+            allow_trailing_data: true,
+            lossy_strings: false,
+            lenient_references: false,
+            current_version: None,
+            public_api_only: false,
+            keep_raw_utf8: false,
+            strict: false,
+        }
+    }
+}
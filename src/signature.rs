@@ -0,0 +1,320 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::field_type::FieldType;
+
+// Types nested in each other deeper than this are rejected, rather than risking running out of
+// stack while parsing them
+const MAX_NESTING: usize = 255;
+
+/// The generic signature of a class, as given by its Signature attribute.
+/// https://docs.oracle.com/javase/specs/jvms/se17/html/jvms-4.html#jvms-4.7.9.1
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+/// A type parameter, like `T extends Number & Comparable<T>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeParameter {
+    pub name: String,
+    /// Absent when the first bound is an interface, as in `T extends Comparable<T>`.
+    pub class_bound: Option<ReferenceTypeSignature>,
+    pub interface_bounds: Vec<ReferenceTypeSignature>,
+}
+
+/// A type that may be generic, like `List<String>` or `T[]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReferenceTypeSignature {
+    Class(ClassTypeSignature),
+    TypeVariable(String),
+    Array(Box<JavaTypeSignature>),
+}
+
+/// The element type of an array signature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JavaTypeSignature {
+    /// A primitive type.
+    Base(FieldType),
+    Reference(ReferenceTypeSignature),
+}
+
+/// A class type with its type arguments. The outermost class comes first, by its internal
+/// name like java/util/Map, followed by the classes nested in it like Entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassTypeSignature {
+    pub segments: Vec<SimpleClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimpleClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+/// A type argument, like `String`, `?` or `? extends Number`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeArgument {
+    Exact(ReferenceTypeSignature),
+    Extends(ReferenceTypeSignature),
+    Super(ReferenceTypeSignature),
+    Wildcard,
+}
+
+impl ClassSignature {
+    /// Parses a class signature, like `<T:Ljava/lang/Object;>Ljava/lang/Object;`.
+    pub fn parse(signature: &str) -> Result<ClassSignature> {
+        let mut parser = SignatureParser {
+            text: signature,
+            position: 0,
+            depth: 0,
+        };
+        parser
+            .class_signature()
+            .filter(|_| parser.position == signature.len())
+            .ok_or_else(|| {
+                ClassReaderError::InvalidClassData(format!(
+                    "invalid class signature: {}",
+                    signature
+                ))
+            })
+    }
+}
+
+impl ClassTypeSignature {
+    /// The binary name of the class, like java/util/Map$Entry.
+    pub fn name(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.name.as_str())
+            .collect::<Vec<_>>()
+            .join("$")
+    }
+}
+
+struct SignatureParser<'a> {
+    text: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> SignatureParser<'a> {
+    fn class_signature(&mut self) -> Option<ClassSignature> {
+        let type_parameters = self.type_parameters()?;
+        let superclass = self.class_type()?;
+        let mut interfaces = Vec::new();
+        while self.peek().is_some() {
+            interfaces.push(self.class_type()?);
+        }
+        Some(ClassSignature {
+            type_parameters,
+            superclass,
+            interfaces,
+        })
+    }
+
+    fn type_parameters(&mut self) -> Option<Vec<TypeParameter>> {
+        let mut type_parameters = Vec::new();
+        if !self.eat('<') {
+            return Some(type_parameters);
+        }
+        while !self.eat('>') {
+            let name = self.identifier()?;
+            self.expect(':')?;
+            let class_bound = match self.peek()? {
+                ':' => None,
+                _ => Some(self.reference_type()?),
+            };
+            let mut interface_bounds = Vec::new();
+            while self.eat(':') {
+                interface_bounds.push(self.reference_type()?);
+            }
+            type_parameters.push(TypeParameter {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+        }
+        // The brackets may not be empty
+        (!type_parameters.is_empty()).then_some(type_parameters)
+    }
+
+    fn reference_type(&mut self) -> Option<ReferenceTypeSignature> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return None;
+        }
+        let reference_type = match self.peek()? {
+            'L' => ReferenceTypeSignature::Class(self.class_type()?),
+            'T' => {
+                self.position += 1;
+                let name = self.identifier()?;
+                self.expect(';')?;
+                ReferenceTypeSignature::TypeVariable(name)
+            }
+            '[' => {
+                self.position += 1;
+                let element = match self.peek()? {
+                    'L' | 'T' | '[' => JavaTypeSignature::Reference(self.reference_type()?),
+                    _ => {
+                        let (base, rest) = FieldType::parse_prefix(self.rest())?;
+                        self.position = self.text.len() - rest.len();
+                        JavaTypeSignature::Base(base)
+                    }
+                };
+                ReferenceTypeSignature::Array(Box::new(element))
+            }
+            _ => return None,
+        };
+        self.depth -= 1;
+        Some(reference_type)
+    }
+
+    fn class_type(&mut self) -> Option<ClassTypeSignature> {
+        self.expect('L')?;
+        // The package is part of the name of the outermost class
+        let start = self.position;
+        self.identifier()?;
+        while self.eat('/') {
+            self.identifier()?;
+        }
+        let mut segments = Vec::new();
+        let mut name = self.text[start..self.position].to_string();
+        loop {
+            let type_arguments = self.type_arguments()?;
+            segments.push(SimpleClassTypeSignature {
+                name,
+                type_arguments,
+            });
+            if !self.eat('.') {
+                break;
+            }
+            name = self.identifier()?;
+        }
+        self.expect(';')?;
+        Some(ClassTypeSignature { segments })
+    }
+
+    fn type_arguments(&mut self) -> Option<Vec<TypeArgument>> {
+        let mut type_arguments = Vec::new();
+        if !self.eat('<') {
+            return Some(type_arguments);
+        }
+        while !self.eat('>') {
+            let type_argument = match self.peek()? {
+                '*' => {
+                    self.position += 1;
+                    TypeArgument::Wildcard
+                }
+                '+' => {
+                    self.position += 1;
+                    TypeArgument::Extends(self.reference_type()?)
+                }
+                '-' => {
+                    self.position += 1;
+                    TypeArgument::Super(self.reference_type()?)
+                }
+                _ => TypeArgument::Exact(self.reference_type()?),
+            };
+            type_arguments.push(type_argument);
+        }
+        (!type_arguments.is_empty()).then_some(type_arguments)
+    }
+
+    // Identifiers run up to the next character with a meaning in signatures, all of which are
+    // ASCII, so the position stays on a character boundary
+    fn identifier(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let len = rest
+            .find(['.', ';', '[', '/', '<', '>', ':'])
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.position += len;
+        Some(rest[..len].to_string())
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += c.len_utf8();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        self.eat(c).then_some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use crate::signature::{ClassSignature, ReferenceTypeSignature, TypeArgument};
+
+    #[test]
+    fn type_parameters_are_parsed() {
+        // class Box<T>
+        let signature = ClassSignature::parse("<T:Ljava/lang/Object;>Ljava/lang/Object;").unwrap();
+        assert_eq!(1, signature.type_parameters.len());
+        let parameter = &signature.type_parameters[0];
+        assert_eq!("T", parameter.name);
+        assert!(matches!(
+            &parameter.class_bound,
+            Some(ReferenceTypeSignature::Class(bound)) if bound.name() == "java/lang/Object"
+        ));
+        assert!(parameter.interface_bounds.is_empty());
+        assert_eq!("java/lang/Object", signature.superclass.name());
+        assert!(signature.interfaces.is_empty());
+
+        // class Sorted<K extends Comparable<? super K>, V> extends Map<K, V[]>.Entry
+        let signature = ClassSignature::parse(
+            "<K::Ljava/lang/Comparable<-TK;>;V:Ljava/lang/Object;>Ljava/util/Map<TK;[TV;>.Entry;",
+        )
+        .unwrap();
+        let key = &signature.type_parameters[0];
+        assert_eq!(None, key.class_bound);
+        assert!(matches!(
+            &key.interface_bounds[..],
+            [ReferenceTypeSignature::Class(bound)] if matches!(
+                &bound.segments[0].type_arguments[..],
+                [TypeArgument::Super(ReferenceTypeSignature::TypeVariable(name))] if name == "K"
+            )
+        ));
+        assert_eq!("V", signature.type_parameters[1].name);
+        assert_eq!("java/util/Map$Entry", signature.superclass.name());
+        assert_eq!(2, signature.superclass.segments[0].type_arguments.len());
+        assert!(signature.superclass.segments[1].type_arguments.is_empty());
+    }
+
+    #[test]
+    fn invalid_signatures_are_rejected() {
+        for signature in [
+            "",
+            "<>Ljava/lang/Object;",
+            "<T:Ljava/lang/Object;Ljava/lang/Object;",
+            "Ljava/util/List<>;",
+            "Ljava/lang/Object;junk",
+            "LFoo<[Q>;",
+        ] {
+            assert!(ClassSignature::parse(signature).is_err(), "{}", signature);
+        }
+        let deep = format!("LFoo{};", "<LFoo".repeat(1000));
+        assert!(ClassSignature::parse(&deep).is_err());
+    }
+}
@@ -0,0 +1,615 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use crate::c_pool::{ConstantPool, ConstantPoolEntry};
+use crate::cfg::falls_through;
+use crate::class_file_method::ClassFileMethod;
+use crate::class_reader_error::{ClassReaderError, Result};
+use crate::code_attribute::CodeAttribute;
+use crate::field_type::FieldType;
+use crate::instruction::{decode, Instruction, Opcode, Operand};
+
+/// Reasons for a method body to be rejected by [verify_method].
+#[derive(Error, Debug, PartialEq)]
+pub enum VerifyError {
+    #[error("stack underflow at offset {offset}")]
+    StackUnderflow { offset: u32 },
+
+    #[error("stack exceeds max_stack of {max_stack} at offset {offset}")]
+    StackOverflow { offset: u32, max_stack: u16 },
+
+    #[error("local {local} used at offset {offset} is outside max_locals of {max_locals}")]
+    LocalOutOfRange {
+        offset: u32,
+        local: u32,
+        max_locals: u16,
+    },
+
+    #[error("arguments take {slots} locals, more than max_locals of {max_locals}")]
+    ArgumentsOutOfRange { slots: usize, max_locals: u16 },
+
+    #[error(transparent)]
+    InvalidCode(#[from] ClassReaderError),
+}
+
+/// Checks that the operand stack of a method never underflows or grows past `max_stack`, and
+/// that its arguments and local variable accesses fit in `max_locals`. Methods without code
+/// always pass.
+///
+/// This is a single linear pass rather than a dataflow analysis: the stack depth after an
+/// unconditional jump is taken from an earlier branch to the next instruction, or is 0 if
+/// there is none, so code only reached by a backward branch may be checked against the wrong
+/// depth. Types on the stack are not checked.
+pub fn verify_method(
+    method: &ClassFileMethod,
+    constants: &ConstantPool,
+) -> core::result::Result<(), VerifyError> {
+    let code = match &method.code {
+        Some(code) => code,
+        None => return Ok(()),
+    };
+    let slots = method.argument_slot_count()?;
+    if slots > code.max_locals as usize {
+        return Err(VerifyError::ArgumentsOutOfRange {
+            slots,
+            max_locals: code.max_locals,
+        });
+    }
+
+    // Stack depth at the targets of branches seen so far, and at exception handlers
+    let mut known_depths: BTreeMap<u32, u32> = BTreeMap::new();
+    for handler in code.exception_table.iter() {
+        known_depths.insert(handler.handler_pc as u32, 1);
+    }
+
+    let mut depth = Some(0);
+    for instruction in decode(&code.code)? {
+        let offset = instruction.offset;
+        let before = match known_depths.get(&offset) {
+            Some(known) => *known,
+            None => depth.unwrap_or(0),
+        };
+
+        if let Some((local, width)) = local_access(&instruction) {
+            if local + width > code.max_locals as u32 {
+                return Err(VerifyError::LocalOutOfRange {
+                    offset,
+                    local,
+                    max_locals: code.max_locals,
+                });
+            }
+        }
+
+        let (pops, pushes) = stack_effect(&instruction, constants)?;
+        let after = before
+            .checked_sub(pops)
+            .ok_or(VerifyError::StackUnderflow { offset })?
+            + pushes;
+        if after > code.max_stack as u32 {
+            return Err(VerifyError::StackOverflow {
+                offset,
+                max_stack: code.max_stack,
+            });
+        }
+
+        for target in branch_targets(&instruction) {
+            // A jsr pushes its return address for the subroutine only
+            let at_target = if matches!(instruction.opcode, Opcode::Jsr | Opcode::JsrW) {
+                after + 1
+            } else {
+                after
+            };
+            known_depths.entry(target).or_insert(at_target);
+        }
+        depth = if falls_through(instruction.opcode) {
+            Some(after)
+        } else {
+            None
+        };
+    }
+    Ok(())
+}
+
+// The max_stack and max_locals that a method body needs, following the branches from the start
+// of the code and from the exception handlers. Each instruction is only visited with the first
+// stack depth that reaches it, which is the depth at every visit in verifiable code.
+pub(crate) fn compute_maxes(
+    code: &CodeAttribute,
+    descriptor: &str,
+    is_static: bool,
+    constants: &ConstantPool,
+) -> Result<(u16, u16)> {
+    let (arguments, _) = method_slots(descriptor).ok_or_else(|| {
+        ClassReaderError::InvalidClassData(format!("invalid method descriptor: {}", descriptor))
+    })?;
+    let instructions = decode(&code.code)?;
+    let positions: BTreeMap<u32, usize> = instructions
+        .iter()
+        .enumerate()
+        .map(|(position, instruction)| (instruction.offset, position))
+        .collect();
+
+    let mut max_locals = arguments + if is_static { 0 } else { 1 };
+    for (local, width) in instructions.iter().filter_map(local_access) {
+        max_locals = max_locals.max(local + width);
+    }
+
+    let mut depths = vec![None; instructions.len()];
+    let mut pending: Vec<(usize, u32)> = Vec::new();
+    if !instructions.is_empty() {
+        pending.push((0, 0));
+    }
+    for handler in code.exception_table.iter() {
+        if let Some(&position) = positions.get(&(handler.handler_pc as u32)) {
+            // Handlers start with the exception alone on the stack
+            pending.push((position, 1));
+        }
+    }
+    let mut max_stack = 0;
+    while let Some((position, depth)) = pending.pop() {
+        if depths[position].is_some() {
+            continue;
+        }
+        depths[position] = Some(depth);
+        let instruction = &instructions[position];
+        let (pops, pushes) = stack_effect(instruction, constants)?;
+        let after = depth.saturating_sub(pops) + pushes;
+        max_stack = max_stack.max(depth).max(after);
+        if falls_through(instruction.opcode) && position + 1 < instructions.len() {
+            pending.push((position + 1, after));
+        }
+        for target in branch_targets(instruction) {
+            if let Some(&target) = positions.get(&target) {
+                // A jsr pushes its return address for the subroutine only
+                let at_target = if matches!(instruction.opcode, Opcode::Jsr | Opcode::JsrW) {
+                    after + 1
+                } else {
+                    after
+                };
+                pending.push((target, at_target));
+            }
+        }
+    }
+    let clamp = |value: u32| u16::try_from(value).unwrap_or(u16::MAX);
+    Ok((clamp(max_stack), clamp(max_locals)))
+}
+
+// Absolute offsets the instruction can jump to, ignoring the invalid ones
+fn branch_targets(instruction: &Instruction) -> impl Iterator<Item = u32> + '_ {
+    let relative: &[i32] = match &instruction.operand {
+        Operand::Branch(offset) => core::slice::from_ref(offset),
+        Operand::TableSwitch { offsets, .. } => offsets,
+        _ => &[],
+    };
+    let (default, pairs): (Option<&i32>, &[(i32, i32)]) = match &instruction.operand {
+        Operand::TableSwitch { default, .. } => (Some(default), &[]),
+        Operand::LookupSwitch { default, pairs } => (Some(default), pairs),
+        _ => (None, &[]),
+    };
+    relative
+        .iter()
+        .chain(default)
+        .chain(pairs.iter().map(|(_, offset)| offset))
+        .filter_map(move |offset| u32::try_from(instruction.offset as i64 + *offset as i64).ok())
+}
+
+// The first local variable used by the instruction and the number of slots it takes
+fn local_access(instruction: &Instruction) -> Option<(u32, u32)> {
+    let width = match instruction.opcode {
+        Opcode::Lload
+        | Opcode::Dload
+        | Opcode::Lstore
+        | Opcode::Dstore
+        | Opcode::Lload0
+        | Opcode::Lload1
+        | Opcode::Lload2
+        | Opcode::Lload3
+        | Opcode::Dload0
+        | Opcode::Dload1
+        | Opcode::Dload2
+        | Opcode::Dload3
+        | Opcode::Lstore0
+        | Opcode::Lstore1
+        | Opcode::Lstore2
+        | Opcode::Lstore3
+        | Opcode::Dstore0
+        | Opcode::Dstore1
+        | Opcode::Dstore2
+        | Opcode::Dstore3 => 2,
+        _ => 1,
+    };
+    let local = match (instruction.opcode, &instruction.operand) {
+        (_, Operand::Local(local)) | (_, Operand::Increment { local, .. }) => *local as u32,
+        (
+            Opcode::Iload0
+            | Opcode::Lload0
+            | Opcode::Fload0
+            | Opcode::Dload0
+            | Opcode::Aload0
+            | Opcode::Istore0
+            | Opcode::Lstore0
+            | Opcode::Fstore0
+            | Opcode::Dstore0
+            | Opcode::Astore0,
+            _,
+        ) => 0,
+        (
+            Opcode::Iload1
+            | Opcode::Lload1
+            | Opcode::Fload1
+            | Opcode::Dload1
+            | Opcode::Aload1
+            | Opcode::Istore1
+            | Opcode::Lstore1
+            | Opcode::Fstore1
+            | Opcode::Dstore1
+            | Opcode::Astore1,
+            _,
+        ) => 1,
+        (
+            Opcode::Iload2
+            | Opcode::Lload2
+            | Opcode::Fload2
+            | Opcode::Dload2
+            | Opcode::Aload2
+            | Opcode::Istore2
+            | Opcode::Lstore2
+            | Opcode::Fstore2
+            | Opcode::Dstore2
+            | Opcode::Astore2,
+            _,
+        ) => 2,
+        (
+            Opcode::Iload3
+            | Opcode::Lload3
+            | Opcode::Fload3
+            | Opcode::Dload3
+            | Opcode::Aload3
+            | Opcode::Istore3
+            | Opcode::Lstore3
+            | Opcode::Fstore3
+            | Opcode::Dstore3
+            | Opcode::Astore3,
+            _,
+        ) => 3,
+        _ => return None,
+    };
+    Some((local, width))
+}
+
+// Number of stack slots popped and pushed by the instruction
+fn stack_effect(instruction: &Instruction, constants: &ConstantPool) -> Result<(u32, u32)> {
+    let effect = match instruction.opcode {
+        Opcode::Nop
+        | Opcode::Iinc
+        | Opcode::Goto
+        | Opcode::GotoW
+        | Opcode::Ret
+        | Opcode::Return
+        | Opcode::Wide => (0, 0),
+        Opcode::AconstNull
+        | Opcode::IconstM1
+        | Opcode::Iconst0
+        | Opcode::Iconst1
+        | Opcode::Iconst2
+        | Opcode::Iconst3
+        | Opcode::Iconst4
+        | Opcode::Iconst5
+        | Opcode::Fconst0
+        | Opcode::Fconst1
+        | Opcode::Fconst2
+        | Opcode::Bipush
+        | Opcode::Sipush
+        | Opcode::Ldc
+        | Opcode::LdcW
+        | Opcode::Iload
+        | Opcode::Fload
+        | Opcode::Aload
+        | Opcode::Iload0
+        | Opcode::Iload1
+        | Opcode::Iload2
+        | Opcode::Iload3
+        | Opcode::Fload0
+        | Opcode::Fload1
+        | Opcode::Fload2
+        | Opcode::Fload3
+        | Opcode::Aload0
+        | Opcode::Aload1
+        | Opcode::Aload2
+        | Opcode::Aload3
+        | Opcode::New
+        | Opcode::Jsr
+        | Opcode::JsrW => (0, 1),
+        Opcode::Lconst0
+        | Opcode::Lconst1
+        | Opcode::Dconst0
+        | Opcode::Dconst1
+        | Opcode::Ldc2W
+        | Opcode::Lload
+        | Opcode::Dload
+        | Opcode::Lload0
+        | Opcode::Lload1
+        | Opcode::Lload2
+        | Opcode::Lload3
+        | Opcode::Dload0
+        | Opcode::Dload1
+        | Opcode::Dload2
+        | Opcode::Dload3 => (0, 2),
+        Opcode::Istore
+        | Opcode::Fstore
+        | Opcode::Astore
+        | Opcode::Istore0
+        | Opcode::Istore1
+        | Opcode::Istore2
+        | Opcode::Istore3
+        | Opcode::Fstore0
+        | Opcode::Fstore1
+        | Opcode::Fstore2
+        | Opcode::Fstore3
+        | Opcode::Astore0
+        | Opcode::Astore1
+        | Opcode::Astore2
+        | Opcode::Astore3
+        | Opcode::Pop
+        | Opcode::Ifeq
+        | Opcode::Ifne
+        | Opcode::Iflt
+        | Opcode::Ifge
+        | Opcode::Ifgt
+        | Opcode::Ifle
+        | Opcode::Ifnull
+        | Opcode::Ifnonnull
+        | Opcode::Tableswitch
+        | Opcode::Lookupswitch
+        | Opcode::Ireturn
+        | Opcode::Freturn
+        | Opcode::Areturn
+        | Opcode::Athrow
+        | Opcode::Monitorenter
+        | Opcode::Monitorexit => (1, 0),
+        Opcode::Lstore
+        | Opcode::Dstore
+        | Opcode::Lstore0
+        | Opcode::Lstore1
+        | Opcode::Lstore2
+        | Opcode::Lstore3
+        | Opcode::Dstore0
+        | Opcode::Dstore1
+        | Opcode::Dstore2
+        | Opcode::Dstore3
+        | Opcode::Pop2
+        | Opcode::IfIcmpeq
+        | Opcode::IfIcmpne
+        | Opcode::IfIcmplt
+        | Opcode::IfIcmpge
+        | Opcode::IfIcmpgt
+        | Opcode::IfIcmple
+        | Opcode::IfAcmpeq
+        | Opcode::IfAcmpne
+        | Opcode::Lreturn
+        | Opcode::Dreturn => (2, 0),
+        Opcode::Iastore
+        | Opcode::Fastore
+        | Opcode::Aastore
+        | Opcode::Bastore
+        | Opcode::Castore
+        | Opcode::Sastore => (3, 0),
+        Opcode::Lastore | Opcode::Dastore => (4, 0),
+        Opcode::Ineg
+        | Opcode::Fneg
+        | Opcode::I2f
+        | Opcode::F2i
+        | Opcode::I2b
+        | Opcode::I2c
+        | Opcode::I2s
+        | Opcode::Newarray
+        | Opcode::Anewarray
+        | Opcode::Arraylength
+        | Opcode::Checkcast
+        | Opcode::Instanceof => (1, 1),
+        Opcode::I2l | Opcode::I2d | Opcode::F2l | Opcode::F2d => (1, 2),
+        Opcode::L2i | Opcode::L2f | Opcode::D2i | Opcode::D2f => (2, 1),
+        Opcode::Lneg | Opcode::Dneg | Opcode::L2d | Opcode::D2l => (2, 2),
+        Opcode::Iaload
+        | Opcode::Faload
+        | Opcode::Aaload
+        | Opcode::Baload
+        | Opcode::Caload
+        | Opcode::Saload
+        | Opcode::Iadd
+        | Opcode::Fadd
+        | Opcode::Isub
+        | Opcode::Fsub
+        | Opcode::Imul
+        | Opcode::Fmul
+        | Opcode::Idiv
+        | Opcode::Fdiv
+        | Opcode::Irem
+        | Opcode::Frem
+        | Opcode::Ishl
+        | Opcode::Ishr
+        | Opcode::Iushr
+        | Opcode::Iand
+        | Opcode::Ior
+        | Opcode::Ixor
+        | Opcode::Fcmpl
+        | Opcode::Fcmpg => (2, 1),
+        Opcode::Laload | Opcode::Daload => (2, 2),
+        Opcode::Lshl | Opcode::Lshr | Opcode::Lushr => (3, 2),
+        Opcode::Ladd
+        | Opcode::Dadd
+        | Opcode::Lsub
+        | Opcode::Dsub
+        | Opcode::Lmul
+        | Opcode::Dmul
+        | Opcode::Ldiv
+        | Opcode::Ddiv
+        | Opcode::Lrem
+        | Opcode::Drem
+        | Opcode::Land
+        | Opcode::Lor
+        | Opcode::Lxor => (4, 2),
+        Opcode::Lcmp | Opcode::Dcmpl | Opcode::Dcmpg => (4, 1),
+        Opcode::Dup => (1, 2),
+        Opcode::DupX1 => (2, 3),
+        Opcode::DupX2 => (3, 4),
+        Opcode::Dup2 => (2, 4),
+        Opcode::Dup2X1 => (3, 5),
+        Opcode::Dup2X2 => (4, 6),
+        Opcode::Swap => (2, 2),
+        Opcode::Multianewarray => match instruction.operand {
+            Operand::MultiArray { dimensions, .. } => (dimensions as u32, 1),
+            _ => (0, 1),
+        },
+        Opcode::Getstatic
+        | Opcode::Putstatic
+        | Opcode::Getfield
+        | Opcode::Putfield
+        | Opcode::Invokevirtual
+        | Opcode::Invokespecial
+        | Opcode::Invokestatic
+        | Opcode::Invokeinterface
+        | Opcode::Invokedynamic => member_effect(instruction, constants)?,
+    };
+    Ok(effect)
+}
+
+// Stack effect of a field access or a call, which depends on the descriptor of the member
+fn member_effect(instruction: &Instruction, constants: &ConstantPool) -> Result<(u32, u32)> {
+    let index = instruction.constant_index().unwrap_or(0);
+    let name_and_type = match constants.get(index)? {
+        ConstantPoolEntry::FieldReference(_, name_and_type)
+        | ConstantPoolEntry::MethodReference(_, name_and_type)
+        | ConstantPoolEntry::InterfaceMethodReference(_, name_and_type)
+        | ConstantPoolEntry::InvokeDynamic(_, name_and_type) => *name_and_type,
+        entry => return Err(invalid_member(instruction, entry)),
+    };
+    let (_, descriptor) = constants.name_and_type_at(name_and_type)?;
+    let receiver = match instruction.opcode {
+        Opcode::Getstatic | Opcode::Putstatic | Opcode::Invokestatic | Opcode::Invokedynamic => 0,
+        _ => 1,
+    };
+    let (arguments, value) = match instruction.opcode {
+        Opcode::Getstatic | Opcode::Putstatic | Opcode::Getfield | Opcode::Putfield => {
+            (0, slots_of(&FieldType::parse(&descriptor)?))
+        }
+        _ => {
+            method_slots(&descriptor).ok_or_else(|| invalid_descriptor(instruction, &descriptor))?
+        }
+    };
+    Ok(match instruction.opcode {
+        Opcode::Getstatic | Opcode::Getfield => (receiver, value),
+        Opcode::Putstatic | Opcode::Putfield => (receiver + value, 0),
+        _ => (receiver + arguments, value),
+    })
+}
+
+// Slots taken by the arguments and the return value of a method descriptor
+fn method_slots(descriptor: &str) -> Option<(u32, u32)> {
+    let (mut parameters, return_type) = descriptor.strip_prefix('(')?.split_once(')')?;
+    let mut arguments = 0;
+    while !parameters.is_empty() {
+        let (parameter, rest) = FieldType::parse_prefix(parameters)?;
+        arguments += slots_of(&parameter);
+        parameters = rest;
+    }
+    let value = match return_type {
+        "V" => 0,
+        _ => slots_of(&FieldType::parse(return_type).ok()?),
+    };
+    Some((arguments, value))
+}
+
+fn slots_of(field_type: &FieldType) -> u32 {
+    if field_type.is_wide() {
+        2
+    } else {
+        1
+    }
+}
+
+fn invalid_member(instruction: &Instruction, entry: &ConstantPoolEntry) -> ClassReaderError {
+    ClassReaderError::InvalidClassData(format!(
+        "invalid member for {} at offset {}: {:?}",
+        instruction.opcode, instruction.offset, entry
+    ))
+}
+
+fn invalid_descriptor(instruction: &Instruction, descriptor: &str) -> ClassReaderError {
+    ClassReaderError::InvalidClassData(format!(
+        "invalid method descriptor for {} at offset {}: {}",
+        instruction.opcode, instruction.offset, descriptor
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use crate::c_pool::ConstantPool;
+    use crate::class_file_method::ClassFileMethod;
+    use crate::code_attribute::CodeAttribute;
+    use crate::method_flags::MethodFlags;
+    use crate::verifier::{verify_method, VerifyError};
+
+    fn method(max_stack: u16, max_locals: u16, code: &[u8]) -> ClassFileMethod {
+        ClassFileMethod {
+            flags: MethodFlags::STATIC,
+            type_descriptor: "(J)J".to_string(),
+            code: Some(CodeAttribute {
+                max_stack,
+                max_locals,
+                code: code.to_vec(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stack_and_locals_are_checked() {
+        let constants = ConstantPool::new();
+        let code = vec![
+            0x1E, // lload_0
+            0x09, // lconst_0
+            0x94, // lcmp
+            0x99, 0x00, 0x07, // ifeq +7
+            0x1E, // lload_0
+            0xA7, 0x00, 0x04, // goto +4
+            0x0A, // lconst_1
+            0xAD, // lreturn
+        ];
+        assert_eq!(Ok(()), verify_method(&method(4, 2, &code), &constants));
+        assert_eq!(
+            Err(VerifyError::StackOverflow {
+                offset: 1,
+                max_stack: 3
+            }),
+            verify_method(&method(3, 2, &code), &constants)
+        );
+        assert_eq!(
+            Err(VerifyError::ArgumentsOutOfRange {
+                slots: 2,
+                max_locals: 1
+            }),
+            verify_method(&method(4, 1, &code), &constants)
+        );
+        assert_eq!(
+            Err(VerifyError::StackUnderflow { offset: 0 }),
+            verify_method(&method(2, 2, &[0x57, 0xB1]), &constants)
+        );
+        assert_eq!(
+            Err(VerifyError::LocalOutOfRange {
+                offset: 0,
+                local: 1,
+                max_locals: 2
+            }),
+            verify_method(&method(2, 2, &[0x1F, 0xAD]), &constants)
+        );
+    }
+}
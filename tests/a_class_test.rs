@@ -0,0 +1,181 @@
+extern crate Fejvm;
+
+use Fejvm::annotation::{
+    Annotation, DeprecationInfo, ElementValue, TypeAnnotationTarget, TypePathStep,
+};
+use Fejvm::class_access_flags::ClassAccessFlags;
+use Fejvm::class_reader;
+
+mod utils;
+
+fn hidden(value: &str) -> Annotation {
+    Annotation {
+        type_descriptor: "LFejvm/Annotations$Hidden;".to_string(),
+        elements: vec![("value".to_string(), ElementValue::String(value.to_string()))],
+    }
+}
+
+#[test]
+fn parameter_annotations_are_read_for_both_retentions() {
+    let class = utils::read_class_from_file("Annotations");
+    let greet = class
+        .methods
+        .iter()
+        .find(|method| method.name == "greet")
+        .unwrap();
+
+    assert_eq!(
+        vec![vec![hidden("name")], vec![]],
+        greet.invisible_parameter_annotations
+    );
+    assert_eq!(
+        vec![
+            vec![],
+            vec![Annotation {
+                type_descriptor: "LFejvm/Annotations$Shown;".to_string(),
+                elements: vec![(
+                    "value".to_string(),
+                    ElementValue::Array(vec![ElementValue::Int(1), ElementValue::Int(2)])
+                )],
+            }]
+        ],
+        greet.visible_parameter_annotations
+    );
+
+    assert_eq!(1, greet.invisible_type_annotations.len());
+    assert_eq!(
+        TypeAnnotationTarget::FormalParameter { index: 0 },
+        greet.invisible_type_annotations[0].target
+    );
+    assert_eq!(1, greet.visible_type_annotations.len());
+    assert_eq!(
+        TypeAnnotationTarget::FormalParameter { index: 1 },
+        greet.visible_type_annotations[0].target
+    );
+}
+
+#[test]
+fn invisible_type_annotations_of_fields_are_read() {
+    let class = utils::read_class_from_file("Annotations");
+    let names = &class.fields[0];
+
+    assert!(names.visible_type_annotations.is_empty());
+    assert_eq!(1, names.invisible_type_annotations.len());
+    let annotation = &names.invisible_type_annotations[0];
+    assert_eq!(0x13, annotation.target_type);
+    assert_eq!(TypeAnnotationTarget::Empty, annotation.target);
+    // List<@Hidden String> annotates the type argument, not the list
+    assert_eq!(vec![TypePathStep::TypeArgument(0)], annotation.type_path);
+    assert_eq!(
+        Annotation {
+            type_descriptor: "LFejvm/Annotations$Hidden;".to_string(),
+            elements: vec![],
+        },
+        annotation.annotation
+    );
+}
+
+#[test]
+fn type_paths_lead_to_the_annotated_part() {
+    let class = utils::read_class_from_file("Annotations");
+
+    let numbers = &class.fields[1];
+    assert_eq!(
+        vec![TypePathStep::TypeArgument(0), TypePathStep::WildcardBound],
+        numbers.invisible_type_annotations[0].type_path
+    );
+
+    // In @Hidden String @Shown [], the array is @Shown and its elements are @Hidden
+    let words = &class.fields[2];
+    assert!(words.visible_type_annotations[0].type_path.is_empty());
+    assert_eq!(
+        vec![TypePathStep::Array],
+        words.invisible_type_annotations[0].type_path
+    );
+}
+
+#[test]
+fn deprecation_details_are_read() {
+    let class = utils::read_class_from_file("Annotations");
+    let method = |name: &str| {
+        class
+            .methods
+            .iter()
+            .find(|method| method.name == name)
+            .unwrap()
+    };
+    assert_eq!(
+        Some(DeprecationInfo {
+            since: Some("9".to_string()),
+            for_removal: true,
+        }),
+        method("retire").deprecation_info()
+    );
+    assert_eq!(
+        Some(DeprecationInfo::default()),
+        method("fade").deprecation_info()
+    );
+    assert_eq!(None, method("greet").deprecation_info());
+}
+
+// Reverses the attributes of the table starting at the given offset, in place, and returns the
+// offset where the table ends
+fn reverse_attributes(data: &mut [u8], start: usize) -> usize {
+    let count = u16::from_be_bytes([data[start], data[start + 1]]);
+    let mut attributes = Vec::new();
+    let mut position = start + 2;
+    for _ in 0..count {
+        let length = u32::from_be_bytes(data[position + 2..position + 6].try_into().unwrap());
+        let end = position + 6 + length as usize;
+        attributes.push(data[position..end].to_vec());
+        position = end;
+    }
+    let reversed: Vec<u8> = attributes.into_iter().rev().flatten().collect();
+    data[start + 2..position].copy_from_slice(&reversed);
+    position
+}
+
+#[test]
+fn attributes_can_come_in_any_order() {
+    let mut data = utils::read_bytes_from_file("Annotations");
+    let mut expected = utils::read_class_from_file("Annotations");
+    for field in expected.fields.iter_mut() {
+        reverse_attributes(&mut data, field.byte_range.unwrap().0 + 6);
+        field.attributes.reverse();
+    }
+    let mut class_attributes = 0;
+    for method in expected.methods.iter_mut() {
+        class_attributes = reverse_attributes(&mut data, method.byte_range.unwrap().0 + 6);
+        method.attributes.reverse();
+    }
+    reverse_attributes(&mut data, class_attributes);
+    expected.attributes.reverse();
+
+    let class = class_reader::read_buffer(&data).unwrap();
+    assert_ne!(
+        utils::read_class_from_file("Annotations").attributes,
+        class.attributes
+    );
+    assert_eq!(expected, class);
+}
+
+#[test]
+fn package_annotations_are_read_from_package_info() {
+    let class = utils::read_class_from_file("package-info");
+    assert!(class.is_package_info());
+    assert_eq!("Fejvm/package-info", class.name);
+    assert_eq!(
+        ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT | ClassAccessFlags::SYNTHETIC,
+        class.flags
+    );
+    assert_eq!(
+        vec![Annotation {
+            type_descriptor: "Ljava/lang/Deprecated;".to_string(),
+            elements: vec![("since".to_string(), ElementValue::String("1.0".to_string()))],
+        }],
+        class.visible_annotations
+    );
+    assert!(class.invisible_annotations.is_empty());
+
+    assert!(!utils::read_class_from_file("Annotations").is_package_info());
+}
@@ -0,0 +1,73 @@
+extern crate Fejvm;
+
+use Fejvm::signature::{ReferenceTypeSignature, TypeArgument};
+
+mod utils;
+
+#[test]
+fn declared_methods_skip_bridges() {
+    let class = utils::read_class_from_file("Bridges");
+    assert_eq!(3, class.methods.len());
+
+    let bridge = class
+        .methods
+        .iter()
+        .find(|method| method.type_descriptor == "(Ljava/lang/Object;)I")
+        .unwrap();
+    assert!(bridge.is_bridge());
+
+    let declared: Vec<(&str, &str)> = class
+        .declared_methods()
+        .map(|method| (method.name.as_str(), method.type_descriptor.as_str()))
+        .collect();
+    assert_eq!(
+        vec![("<init>", "(I)V"), ("compareTo", "(LFejvm/Bridges;)I")],
+        declared
+    );
+}
+
+#[test]
+fn generic_superinterfaces_are_in_the_signature() {
+    let class = utils::read_class_from_file("Bridges");
+    let signature = class.signature().unwrap().unwrap();
+    assert!(signature.type_parameters.is_empty());
+    assert_eq!("java/lang/Object", signature.superclass.name());
+    assert_eq!(1, signature.interfaces.len());
+    let comparable = &signature.interfaces[0];
+    assert_eq!("java/lang/Comparable", comparable.name());
+    assert!(matches!(
+        &comparable.segments[0].type_arguments[..],
+        [TypeArgument::Exact(ReferenceTypeSignature::Class(argument))]
+            if argument.name() == "Fejvm/Bridges"
+    ));
+
+    assert_eq!(Ok(None), utils::read_class_from_file("hi").signature());
+}
+
+#[test]
+fn classes_can_be_renamed() {
+    let mut class = utils::read_class_from_file("Bridges");
+    class.rename("java/lang/Object", "shaded/Object").unwrap();
+    assert_eq!("shaded/Object", class.superclass);
+    assert_eq!("Fejvm/Bridges", class.name);
+    assert!(class
+        .methods
+        .iter()
+        .any(|method| method.type_descriptor == "(Lshaded/Object;)I"));
+
+    // The code refers to the renamed class through the constant pool
+    let texts: Vec<String> = class
+        .constants
+        .iter()
+        .filter_map(|(index, _)| class.constants.text_of(index).ok())
+        .collect();
+    assert!(texts.iter().any(|text| text == "shaded/Object.<init>: ()V"));
+    assert!(!texts.iter().any(|text| text == "java/lang/Object"));
+
+    class.rename("Fejvm/Bridges", "relocated/Bridges").unwrap();
+    assert_eq!("relocated/Bridges", class.name);
+    assert!(class
+        .methods
+        .iter()
+        .any(|method| method.type_descriptor == "(Lrelocated/Bridges;)I"));
+}
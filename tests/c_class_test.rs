@@ -1,6 +1,10 @@
 extern crate Fejvm;
 
+use Fejvm::attribute::Attribute;
+use Fejvm::c_pool::{ConstantPool, ConstantPoolEntry};
 use Fejvm::class_file_field::{ClassFileField, FieldConstantValue};
+use Fejvm::class_reader;
+use Fejvm::reader_options::ReaderOptions;
 
 use Fejvm::field_flags::FieldFlags;
 
@@ -18,32 +22,112 @@ fn can_read_constants() {
                 name: "AN_INT".to_string(),
                 type_descriptor: "I".to_string(),
                 constant_value: Some(FieldConstantValue::Int(2023)),
+                attributes: constant_value_attribute(7),
+                byte_range: Some((284, 300)),
+                ..Default::default()
             },
             ClassFileField {
                 flags: FieldFlags::PROTECTED | FieldFlags::STATIC | FieldFlags::FINAL,
                 name: "A_FLOAT".to_string(),
                 type_descriptor: "F".to_string(),
                 constant_value: Some(FieldConstantValue::Float(20.23)),
+                attributes: constant_value_attribute(10),
+                byte_range: Some((300, 316)),
+                ..Default::default()
             },
             ClassFileField {
                 flags: FieldFlags::PRIVATE | FieldFlags::STATIC | FieldFlags::FINAL,
                 name: "A_LONG".to_string(),
                 type_descriptor: "J".to_string(),
                 constant_value: Some(FieldConstantValue::Long(2023)),
+                attributes: constant_value_attribute(13),
+                byte_range: Some((316, 332)),
+                ..Default::default()
             },
             ClassFileField {
                 flags: FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL,
                 name: "A_DOUBLE".to_string(),
                 type_descriptor: "D".to_string(),
                 constant_value: Some(FieldConstantValue::Double(20.23)),
+                attributes: constant_value_attribute(17),
+                byte_range: Some((332, 348)),
+                ..Default::default()
             },
             ClassFileField {
                 flags: FieldFlags::PUBLIC | FieldFlags::STATIC | FieldFlags::FINAL,
                 name: "A_STRING".to_string(),
                 type_descriptor: "Ljava/lang/String;".to_string(),
                 constant_value: Some(FieldConstantValue::String("2023".to_string())),
+                attributes: constant_value_attribute(21),
+                byte_range: Some((348, 364)),
+                ..Default::default()
             }
         ),
         class.fields
     );
 }
+
+#[test]
+fn can_list_string_constants() {
+    let class = utils::read_class_from_file("Constants");
+    assert_eq!(vec!["2023"], class.string_constants());
+}
+
+#[test]
+fn constant_fields_can_be_built() {
+    let mut constants = ConstantPool::new();
+    let field = ClassFileField::from_i32("ANSWER", 42, &mut constants).unwrap();
+    assert_eq!(Some(FieldConstantValue::Int(42)), field.constant_value);
+    let index = u16::from_be_bytes(field.attributes[0].info[..].try_into().unwrap());
+    assert_eq!(Ok(&ConstantPoolEntry::Integer(42)), constants.get(index));
+    assert_eq!(Ok("ANSWER".to_string()), constants.text_of(2));
+
+    // The fields of a compiled class are rebuilt as they were read, without any new entry
+    let mut class = utils::read_class_from_file("Constants");
+    let slots = class.constants.slot_count();
+    let rebuilt = [
+        ClassFileField::from_i32("AN_INT", 2023, &mut class.constants).unwrap(),
+        ClassFileField::from_f64("A_DOUBLE", 20.23, &mut class.constants).unwrap(),
+        ClassFileField::from_str("A_STRING", "2023", &mut class.constants).unwrap(),
+    ];
+    assert_eq!(slots, class.constants.slot_count());
+    for (field, original) in rebuilt.into_iter().zip([0, 3, 4]) {
+        let original = &class.fields[original];
+        assert_eq!(
+            ClassFileField {
+                byte_range: original.byte_range,
+                ..field
+            },
+            *original
+        );
+    }
+}
+
+#[test]
+fn only_public_api_members_can_be_read() {
+    let options = ReaderOptions {
+        public_api_only: true,
+        ..Default::default()
+    };
+    let bytes = utils::read_bytes_from_file("Constants");
+    let class = class_reader::read_buffer_with_options(&bytes, &options).unwrap();
+    let names: Vec<&str> = class
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect();
+    assert_eq!(vec!["AN_INT", "A_FLOAT", "A_DOUBLE", "A_STRING"], names);
+
+    // What comes after the dropped field is still read
+    let full = utils::read_class_from_file("Constants");
+    assert_eq!(full.fields[3], class.fields[2]);
+    assert_eq!(full.methods, class.methods);
+    assert_eq!(full.attributes, class.attributes);
+}
+
+fn constant_value_attribute(constant_index: u16) -> Vec<Attribute> {
+    vec![Attribute {
+        name: "ConstantValue".to_string(),
+        info: constant_index.to_be_bytes().to_vec(),
+    }]
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "std")]
+
+extern crate Fejvm;
+
+use Fejvm::class_reader;
+use Fejvm::names::binary_to_internal;
+use Fejvm::parse_warning::ParseWarning;
+use Fejvm::reader_options::ReaderOptions;
+
+mod utils;
+
+#[test]
+fn classes_in_a_directory_are_named_by_their_path() {
+    let classes = class_reader::read_dir(&utils::resources_path().join("listing")).unwrap();
+
+    let names: Vec<&str> = classes.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(vec!["Root", "sample.Leaf"], names);
+    for (name, class) in classes.iter() {
+        assert_eq!(binary_to_internal(name), class.name);
+    }
+}
+
+#[test]
+fn compiled_classes_raise_no_warnings() {
+    let dir = utils::resources_path().join("Fejvm");
+    for name in [
+        "Annotations",
+        "Bridges",
+        "Calls",
+        "Constants",
+        "DebugInfo",
+        "Flow",
+        "KotlinPoint",
+        "Lambdas",
+        "Nested",
+        "NoDebugInfo",
+        "ScalaGreeter",
+        "hi",
+        "package-info",
+    ] {
+        let path = dir.join(format!("{}.class", name));
+        let outcome = class_reader::read_with_warnings(&path, &ReaderOptions::default()).unwrap();
+        assert_eq!(Vec::<ParseWarning>::new(), outcome.warnings, "{}", name);
+    }
+}
+
+#[test]
+fn compiled_classes_have_valid_constant_pools() {
+    for (name, class) in class_reader::read_dir(&utils::resources_path()).unwrap() {
+        assert_eq!(Ok(()), class.constants.validate(), "{}", name);
+    }
+}
@@ -0,0 +1,160 @@
+extern crate Fejvm;
+
+use Fejvm::cfg::{build_cfg, Edge, EdgeKind};
+use Fejvm::class_file::ClassFile;
+use Fejvm::class_file_method::ClassFileMethod;
+use Fejvm::code_attribute::CodeAttribute;
+use Fejvm::verifier::verify_method;
+
+mod utils;
+
+fn method<'a>(class: &'a ClassFile, name: &str) -> &'a ClassFileMethod {
+    class
+        .methods
+        .iter()
+        .find(|method| method.name == name)
+        .unwrap()
+}
+
+#[test]
+fn if_statements_split_blocks() {
+    let class = utils::read_class_from_file("Flow");
+    let graph = build_cfg(method(&class, "abs")).unwrap();
+
+    assert_eq!(
+        vec![(0, 4), (4, 7), (7, 9)],
+        graph
+            .blocks
+            .iter()
+            .map(|block| (block.start, block.end))
+            .collect::<Vec<(u32, u32)>>()
+    );
+    assert_eq!(
+        vec![
+            Edge {
+                from: 0,
+                to: 2,
+                kind: EdgeKind::ConditionalBranch
+            },
+            Edge {
+                from: 0,
+                to: 1,
+                kind: EdgeKind::FallThrough
+            },
+            Edge {
+                from: 1,
+                to: 2,
+                kind: EdgeKind::FallThrough
+            },
+        ],
+        graph.edges
+    );
+}
+
+#[test]
+fn exception_handlers_are_edges() {
+    let class = utils::read_class_from_file("Flow");
+    let graph = build_cfg(method(&class, "parse")).unwrap();
+
+    assert_eq!(3, graph.blocks.len());
+    assert_eq!(
+        vec![
+            Edge {
+                from: 0,
+                to: 1,
+                kind: EdgeKind::FallThrough
+            },
+            Edge {
+                from: 0,
+                to: 2,
+                kind: EdgeKind::ExceptionHandler
+            },
+        ],
+        graph.successors(0).cloned().collect::<Vec<Edge>>()
+    );
+    assert_eq!(0, graph.successors(1).count());
+}
+
+#[test]
+fn caught_exception_types_are_resolved() {
+    let class = utils::read_class_from_file("Flow");
+
+    assert_eq!(
+        vec![Some("java/io/IOException".to_owned()), None, None],
+        method(&class, "read")
+            .caught_exception_types(&class.constants)
+            .unwrap()
+    );
+    assert_eq!(
+        Vec::<Option<String>>::new(),
+        method(&class, "abs")
+            .caught_exception_types(&class.constants)
+            .unwrap()
+    );
+}
+
+#[test]
+fn subroutines_jump_and_fall_through() {
+    // A try/finally as compiled before Java 6, with the finally block as a subroutine
+    let method = ClassFileMethod {
+        code: Some(CodeAttribute {
+            max_stack: 1,
+            max_locals: 2,
+            code: vec![
+                0xA8, 0x00, 0x04, // jsr +4
+                0xB1, // return
+                0x4C, // astore_1
+                0xA9, 0x01, // ret 1
+            ],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let graph = build_cfg(&method).unwrap();
+
+    assert_eq!(3, graph.blocks.len());
+    assert_eq!(
+        vec![
+            Edge {
+                from: 0,
+                to: 2,
+                kind: EdgeKind::UnconditionalBranch
+            },
+            Edge {
+                from: 0,
+                to: 1,
+                kind: EdgeKind::FallThrough
+            },
+        ],
+        graph.successors(0).cloned().collect::<Vec<Edge>>()
+    );
+    // Where ret returns to is only known at runtime
+    assert_eq!(0, graph.successors(2).count());
+}
+
+#[test]
+fn complexity_counts_branches_and_handlers() {
+    let class = utils::read_class_from_file("Flow");
+
+    let abs = method(&class, "abs");
+    assert_eq!(Ok(7), abs.instruction_count());
+    assert_eq!(Ok(2), abs.cyclomatic_complexity());
+    assert_eq!(Ok(2), method(&class, "parse").cyclomatic_complexity());
+    assert_eq!(Ok(1), method(&class, "<init>").cyclomatic_complexity());
+}
+
+#[test]
+fn compiled_methods_pass_verification() {
+    for name in ["Flow", "Calls", "Constants", "Bridges", "Lambdas", "hi"] {
+        let class = utils::read_class_from_file(name);
+        for method in class.methods.iter() {
+            assert_eq!(
+                Ok(()),
+                verify_method(method, &class.constants),
+                "{}.{}",
+                name,
+                method.name
+            );
+        }
+    }
+}
@@ -0,0 +1,72 @@
+extern crate Fejvm;
+
+use Fejvm::class_file::ClassFile;
+use Fejvm::instruction::{self, Opcode, Operand, ResolvedOperand};
+use Fejvm::invocation_kind::InvocationKind;
+
+mod utils;
+
+fn code_of<'a>(class: &'a ClassFile, method_name: &str) -> &'a [u8] {
+    let method = class
+        .methods
+        .iter()
+        .find(|method| method.name == method_name)
+        .unwrap();
+    &method.code.as_ref().unwrap().code
+}
+
+#[test]
+fn interface_calls_are_resolved_distinctly() {
+    let class = utils::read_class_from_file("Calls");
+    let instructions = instruction::decode(code_of(&class, "count")).unwrap();
+
+    let call = &instructions[1];
+    assert_eq!(Opcode::Invokeinterface, call.opcode);
+    assert_eq!(Operand::InterfaceCall { index: 7, count: 1 }, call.operand);
+    assert_eq!(6, instructions[2].offset);
+    assert_eq!(
+        Some(ResolvedOperand::InterfaceMethod {
+            owner: "java/util/List".to_string(),
+            name: "size".to_string(),
+            descriptor: "()I".to_string(),
+        }),
+        call.resolve(&class.constants).unwrap()
+    );
+    assert_eq!(
+        "invokeinterface #7, 1 // InterfaceMethod java/util/List.size:()I",
+        call.describe(&class.constants).unwrap()
+    );
+
+    let instructions = instruction::decode(code_of(&class, "first")).unwrap();
+    let virtual_call = instructions
+        .iter()
+        .find(|instruction| instruction.opcode == Opcode::Invokevirtual)
+        .unwrap();
+    assert!(matches!(
+        virtual_call.resolve(&class.constants).unwrap(),
+        Some(ResolvedOperand::Method { .. })
+    ));
+}
+
+#[test]
+fn methods_are_classified_by_invocation() {
+    let method = |class: &str, name: &str| {
+        utils::read_class_from_file(class)
+            .methods
+            .into_iter()
+            .find(|method| method.name == name)
+            .unwrap()
+    };
+
+    assert_eq!(
+        InvocationKind::Static,
+        method("Flow", "abs").expected_invocation(false)
+    );
+    assert_eq!(
+        InvocationKind::Special,
+        method("Calls", "<init>").expected_invocation(false)
+    );
+    let count = method("Calls", "count");
+    assert_eq!(InvocationKind::Virtual, count.expected_invocation(false));
+    assert_eq!(InvocationKind::Interface, count.expected_invocation(true));
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "zip")]
+
+extern crate Fejvm;
+
+use std::fs;
+use std::io::Write;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+
+use zip::write::SimpleFileOptions;
+use Fejvm::class_reader;
+
+mod utils;
+
+// Packs the compiled fixtures into a jar named after the test, so that it always holds the
+// current classes. The manifest comes first, as the jar tool puts it. The names of the class
+// entries are returned along with the path.
+fn fixture_jar(name: &str) -> (PathBuf, Vec<String>) {
+    let mut classes: Vec<PathBuf> = fs::read_dir(utils::resources_path().join("Fejvm"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "class")
+        })
+        .collect();
+    classes.sort();
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.jar", name));
+    let mut jar = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+    let options = SimpleFileOptions::default();
+    jar.start_file("META-INF/MANIFEST.MF", options).unwrap();
+    jar.write_all(b"Manifest-Version: 1.0\r\n\r\n").unwrap();
+    let mut entries = vec![];
+    for class in classes {
+        let entry = format!("Fejvm/{}", class.file_name().unwrap().to_str().unwrap());
+        jar.start_file(entry.as_str(), options).unwrap();
+        jar.write_all(&fs::read(&class).unwrap()).unwrap();
+        entries.push(entry);
+    }
+    jar.finish().unwrap();
+    (path, entries)
+}
+
+#[test]
+fn classes_in_a_jar_are_streamed() {
+    let (jar, entries) = fixture_jar("streamed");
+    let mut names = vec![];
+    class_reader::for_each_class_in_jar(&jar, |name, class| {
+        assert_eq!(name, format!("{}.class", class.name));
+        names.push(name.to_string());
+        ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(entries, names);
+}
+
+#[test]
+fn streaming_can_stop_early() {
+    let (jar, entries) = fixture_jar("stopped");
+    let mut names = vec![];
+    class_reader::for_each_class_in_jar(&jar, |name, _| {
+        names.push(name.to_string());
+        ControlFlow::Break(())
+    })
+    .unwrap();
+    assert_eq!(entries[..1], names);
+}
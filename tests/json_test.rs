@@ -0,0 +1,25 @@
+#![cfg(feature = "serde")]
+
+extern crate Fejvm;
+
+mod utils;
+
+#[test]
+fn constant_pool_can_be_rendered_as_json() {
+    let class = utils::read_class_from_file("Constants");
+    let json = class.constants.to_json();
+    let entries = json.as_array().unwrap();
+    // One object per entry, the second slots of the long and double left out
+    assert_eq!(class.constants.iter().count(), entries.len());
+
+    let method_ref = &entries[0];
+    assert_eq!(1, method_ref["index"]);
+    assert_eq!(10, method_ref["tag"]);
+    assert_eq!("MethodReference", method_ref["kind"]);
+
+    let long = entries.iter().find(|entry| entry["tag"] == 5).unwrap();
+    assert_eq!(2023, long["value"]);
+    let next = long["index"].as_u64().unwrap() + 2;
+    assert!(entries.iter().any(|entry| entry["index"] == next));
+    assert!(!entries.iter().any(|entry| entry["index"] == next - 1));
+}
@@ -0,0 +1,90 @@
+extern crate Fejvm;
+
+use Fejvm::annotation::ElementValue;
+use Fejvm::class_reader;
+
+mod utils;
+
+#[test]
+fn kotlin_metadata_is_read() {
+    let class = utils::read_class_from_file("KotlinPoint");
+    assert_eq!("Fejvm/KotlinPoint", class.name);
+
+    let metadata = &class.visible_annotations[0];
+    assert_eq!("Lkotlin/Metadata;", metadata.type_descriptor);
+    let names: Vec<&str> = metadata
+        .elements
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    assert_eq!(vec!["mv", "k", "xi", "d1", "d2"], names);
+    assert_eq!(
+        ElementValue::Array(vec![
+            ElementValue::Int(1),
+            ElementValue::Int(9),
+            ElementValue::Int(0)
+        ]),
+        metadata.elements[0].1
+    );
+    // NULs are encoded on two bytes in the constant pool, and come back as they were
+    match &metadata.elements[3].1 {
+        ElementValue::Array(strings) => match &strings[0] {
+            ElementValue::String(d1) => {
+                assert!(d1.starts_with("\0\u{14}\n\u{2}\u{18}"));
+                assert!(d1.contains('\u{a2}'));
+            }
+            value => panic!("unexpected d1 value {:?}", value),
+        },
+        value => panic!("unexpected d1 value {:?}", value),
+    }
+}
+
+#[test]
+fn kotlin_companion_is_a_nested_class() {
+    let class = utils::read_class_from_file("KotlinPoint");
+    let inner_classes = class.inner_classes().unwrap();
+    assert_eq!(1, inner_classes.len());
+    assert_eq!("Fejvm/KotlinPoint$Companion", inner_classes[0].inner);
+    assert_eq!(Some("Companion".to_string()), inner_classes[0].name);
+    assert_eq!(
+        vec!["Fejvm/KotlinPoint$Companion".to_string()],
+        class.nest_members().unwrap()
+    );
+}
+
+// kotlinc maps the lines of inlined functions back to their sources with a JSR-45 SMAP in the
+// SourceDebugExtension attribute
+#[test]
+fn kotlin_inline_line_mappings_are_read() {
+    let smap =
+        "SMAP\nKotlinPoint.kt\nKotlin\n*S Kotlin\n*F\n+ 1 KotlinPoint.kt\nFejvm/KotlinPoint\n\
+                + 2 _Collections.kt\nkotlin/collections/CollectionsKt___CollectionsKt\n*L\n\
+                1#1,12:1\n1855#2,2:13\n*E\n";
+    let mut data = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x06];
+    for (tag, entry) in [
+        (1, &b"Fejvm/KotlinPoint"[..]),
+        (7, &[0x00, 0x01][..]),
+        (1, &b"java/lang/Object"[..]),
+        (7, &[0x00, 0x03][..]),
+        (1, &b"SourceDebugExtension"[..]),
+    ] {
+        data.push(tag);
+        if tag == 1 {
+            data.extend_from_slice(&(entry.len() as u16).to_be_bytes());
+        }
+        data.extend_from_slice(entry);
+    }
+    data.extend([
+        0x00, 0x31, 0x00, 0x02, 0x00, 0x04, // public final super, extends java/lang/Object
+        0x00, 0x00, // interfaces
+        0x00, 0x00, // fields
+        0x00, 0x00, // methods
+        0x00, 0x01, // attributes
+        0x00, 0x05,
+    ]);
+    data.extend_from_slice(&(smap.len() as u32).to_be_bytes());
+    data.extend_from_slice(smap.as_bytes());
+
+    let class = class_reader::read_buffer(&data).unwrap();
+    assert_eq!(Some(smap.to_string()), class.source_debug_extension);
+}
@@ -0,0 +1,76 @@
+extern crate Fejvm;
+
+use Fejvm::c_pool::ConstantPoolEntry;
+use Fejvm::class_file_version::ClassFileVersion;
+use Fejvm::instruction::ResolvedOperand;
+
+mod utils;
+
+#[test]
+fn modern_classes_with_lambdas_can_be_read() {
+    let class = utils::read_class_from_file("Lambdas");
+    println!("Read class file: {}", class);
+    assert_eq!(ClassFileVersion::Jdk17, class.version);
+    assert_eq!("Fejvm/Lambdas", class.name);
+
+    let attribute_names: Vec<&str> = class
+        .attributes
+        .iter()
+        .map(|attribute| attribute.name.as_str())
+        .collect();
+    assert_eq!(
+        vec![
+            "SourceFile",
+            "RuntimeVisibleAnnotations",
+            "NestMembers",
+            "BootstrapMethods",
+            "InnerClasses"
+        ],
+        attribute_names
+    );
+
+    assert_eq!(
+        Ok("#0:get: (Ljava/lang/String;)Ljava/util/function/Supplier;".to_string()),
+        class.constants.text_of(7)
+    );
+    assert_eq!(
+        Ok(&ConstantPoolEntry::MethodHandle(6, 36)),
+        class.constants.get(35)
+    );
+    assert_eq!(
+        Ok("()Ljava/lang/Object;".to_string()),
+        class.constants.text_of(42)
+    );
+}
+
+#[test]
+fn string_concatenation_bootstrap_is_resolved() {
+    let class = utils::read_class_from_file("Lambdas");
+    let call_sites: Vec<u16> = class
+        .constants
+        .iter()
+        .filter(|(_, entry)| matches!(entry, ConstantPoolEntry::InvokeDynamic(..)))
+        .map(|(index, _)| index)
+        .collect();
+    // The lambda itself, then the concatenation in its body
+    assert_eq!(2, call_sites.len());
+    let concat = class.bootstrap_method_for(call_sites[1]).unwrap();
+    assert_eq!("makeConcatWithConstants", concat.name);
+    assert_eq!("(Ljava/lang/String;)Ljava/lang/String;", concat.descriptor);
+    assert!(matches!(
+        &concat.method,
+        ResolvedOperand::MethodHandle { kind: 6, owner, name, .. }
+            if owner == "java/lang/invoke/StringConcatFactory" && name == "makeConcatWithConstants"
+    ));
+    // The recipe, where \u{1} stands for the argument
+    assert_eq!(
+        vec![ResolvedOperand::String("Hello, \u{1}".to_string())],
+        concat.arguments
+    );
+
+    let lambda = class.bootstrap_method_for(call_sites[0]).unwrap();
+    assert_eq!("get", lambda.name);
+    assert_eq!(3, lambda.arguments.len());
+
+    assert!(class.bootstrap_method_for(1).is_err());
+}
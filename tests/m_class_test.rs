@@ -0,0 +1,27 @@
+#![cfg(feature = "std")]
+
+extern crate Fejvm;
+
+use Fejvm::c_pool::ConstantPoolEntry;
+use Fejvm::class_access_flags::ClassAccessFlags;
+use Fejvm::class_reader;
+
+mod utils;
+
+#[test]
+fn module_descriptors_can_be_read() {
+    let class = class_reader::read(&utils::resources_path().join("module-info.class")).unwrap();
+    assert_eq!(ClassAccessFlags::MODULE, class.flags);
+    assert_eq!(0, class.reserved_flags);
+    assert_eq!("module-info", class.name);
+    assert_eq!("", class.superclass);
+    assert!(class.interfaces.is_empty());
+    assert!(class.fields.is_empty());
+    assert!(class.methods.is_empty());
+    assert!(class.attributes.iter().any(|attr| attr.name == "Module"));
+
+    assert_eq!(Ok(&ConstantPoolEntry::Module(7)), class.constants.get(6));
+    assert_eq!(Ok("fejvm.fixtures".to_string()), class.constants.text_of(6));
+    assert_eq!(Ok(&ConstantPoolEntry::Package(14)), class.constants.get(13));
+    assert_eq!(Ok("Fejvm".to_string()), class.constants.text_of(13));
+}
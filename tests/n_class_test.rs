@@ -0,0 +1,73 @@
+extern crate Fejvm;
+
+use Fejvm::attribute::Attribute;
+use Fejvm::inner_class_flags::InnerClassFlags;
+use Fejvm::nest_role::NestRole;
+
+mod utils;
+
+#[test]
+fn nested_class_flags_are_read() {
+    let class = utils::read_class_from_file("Nested");
+    let inner_classes = class.inner_classes().unwrap();
+    let flags_of = |name: &str| {
+        inner_classes
+            .iter()
+            .find(|inner| inner.name.as_deref() == Some(name))
+            .map(|inner| inner.flags)
+    };
+
+    assert_eq!(
+        Some(InnerClassFlags::PRIVATE | InnerClassFlags::STATIC),
+        flags_of("Secret")
+    );
+    assert_eq!(
+        Some(InnerClassFlags::PROTECTED | InnerClassFlags::ABSTRACT),
+        flags_of("Inner")
+    );
+    assert_eq!(
+        Some(InnerClassFlags::PUBLIC | InnerClassFlags::STATIC | InnerClassFlags::FINAL),
+        flags_of("Open")
+    );
+
+    let secret = inner_classes
+        .iter()
+        .find(|inner| inner.inner == "Fejvm/Nested$Secret")
+        .unwrap();
+    assert_eq!(Some("Fejvm/Nested".to_string()), secret.outer);
+
+    assert_eq!(
+        Ok(vec![]),
+        utils::read_class_from_file("hi").inner_classes()
+    );
+}
+
+#[test]
+fn nest_relationship_is_read() {
+    let host = utils::read_class_from_file("Nested");
+    assert_eq!(
+        Ok(NestRole::Host(vec![
+            "Fejvm/Nested$Open".to_string(),
+            "Fejvm/Nested$Inner".to_string(),
+            "Fejvm/Nested$Secret".to_string(),
+        ])),
+        host.nest_relationship()
+    );
+
+    // The members themselves are not kept as fixtures, so hi is made a member of a nest hosted
+    // by the class at #6 of its constant pool
+    let mut member = utils::read_class_from_file("hi");
+    assert_eq!(Ok(NestRole::None), member.nest_relationship());
+    let host_name = member.constants.class_name_at(6).unwrap();
+    member.attributes.push(Attribute {
+        name: "NestHost".to_string(),
+        info: vec![0, 6],
+    });
+    assert_eq!(Ok(NestRole::Member(host_name)), member.nest_relationship());
+
+    member.attributes.push(Attribute {
+        name: "NestMembers".to_string(),
+        info: vec![0, 0],
+    });
+    assert!(member.nest_relationship().is_err());
+}
@@ -1,10 +1,18 @@
 extern crate Fejvm;
 
-use Fejvm::class_file::ClassFile;
+use Fejvm::c_pool::ConstantPoolEntry;
+use Fejvm::class_file::{AttributeLocation, ClassFile};
 use Fejvm::class_file_field::ClassFileField;
 use Fejvm::class_file_method::ClassFileMethod;
+use Fejvm::class_reader;
+use Fejvm::class_reader_error::ClassReaderError;
 use Fejvm::field_flags::FieldFlags;
+use Fejvm::field_type::FieldType;
+use Fejvm::lazy_class_file::LazyClassFile;
+use Fejvm::member_ref::{MemberRef, MemberRefKind};
 use Fejvm::method_flags::MethodFlags;
+use Fejvm::raw_flags::RawFlags;
+use Fejvm::reader_options::ReaderOptions;
 use Fejvm::{class_access_flags::ClassAccessFlags, class_file_version::ClassFileVersion};
 
 mod utils;
@@ -14,10 +22,18 @@ fn can_read_pojo_class_file() {
     let class = utils::read_class_from_file("hi");
     println!("Read class file: {}", class);
     assert_eq!(ClassFileVersion::Jdk6, class.version);
+    assert_eq!(0, class.minor_version);
     assert_eq!(
         ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER,
         class.flags
     );
+    assert!(class.flags.is_super());
+    assert_eq!("public", class.flags.to_string());
+    assert_eq!(
+        "public abstract interface",
+        (ClassAccessFlags::PUBLIC | ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT)
+            .to_string()
+    );
     assert_eq!("Fejvm/hi", class.name);
     assert_eq!("java/lang/Object", class.superclass);
     assert_eq!(
@@ -29,6 +45,31 @@ fn can_read_pojo_class_file() {
     check_methods(&class);
 }
 
+#[test]
+fn parsed_classes_can_be_compared() {
+    let class = utils::read_class_from_file("hi");
+    assert_eq!(utils::read_class_from_file("hi"), class);
+    assert_ne!(utils::read_class_from_file("Constants"), class);
+}
+
+#[test]
+fn raw_attribute_bytes_are_kept() {
+    let class = utils::read_class_from_file("hi");
+    let data = utils::read_bytes_from_file("hi");
+
+    let code = &class.methods[0].attributes[0];
+    assert_eq!("Code", code.name);
+    // The attribute body is preceded by its length in the original file
+    let mut expected = (code.info.len() as u32).to_be_bytes().to_vec();
+    expected.extend_from_slice(&code.info);
+    assert!(data
+        .windows(expected.len())
+        .any(|window| window == expected.as_slice()));
+
+    assert_eq!(1, class.attributes.len());
+    assert_eq!("SourceFile", class.attributes[0].name);
+}
+
 fn check_fields(class: &ClassFile) {
     assert_eq!(
         vec!(
@@ -37,12 +78,18 @@ fn check_fields(class: &ClassFile) {
                 name: "real".to_string(),
                 type_descriptor: "D".to_string(),
                 constant_value: None,
+                attributes: vec![],
+                byte_range: Some((313, 321)),
+                ..Default::default()
             },
             ClassFileField {
                 flags: FieldFlags::PRIVATE | FieldFlags::FINAL,
                 name: "imag".to_string(),
                 type_descriptor: "D".to_string(),
                 constant_value: None,
+                attributes: vec![],
+                byte_range: Some((321, 329)),
+                ..Default::default()
             }
         ),
         class.fields
@@ -63,3 +110,474 @@ fn check_method(method: &ClassFileMethod, flags: MethodFlags, name: &str, type_d
     assert_eq!(method.name, name);
     assert_eq!(method.type_descriptor, type_descriptor);
 }
+
+#[test]
+fn methods_can_be_grouped_by_name() {
+    let class = utils::read_class_from_file("hi");
+    let groups: Vec<(&str, Vec<&str>)> = class
+        .methods_grouped_by_name()
+        .map(|(name, methods)| {
+            let descriptors = methods
+                .iter()
+                .map(|method| method.type_descriptor.as_str())
+                .collect();
+            (name, descriptors)
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            ("<init>", vec!["(D)V", "(DD)V"]),
+            ("abs", vec!["()D"]),
+            ("getImag", vec!["()D"]),
+            ("getReal", vec!["()D"]),
+        ],
+        groups
+    );
+}
+
+#[test]
+fn code_attribute_exposes_nested_attributes() {
+    let class = utils::read_class_from_file("hi");
+    for method in class.methods.iter() {
+        let code = method.code.as_ref().unwrap();
+        assert!(!code.code.is_empty());
+        assert_eq!(
+            vec!["LineNumberTable"],
+            code.attributes
+                .iter()
+                .map(|attr| attr.name.as_str())
+                .collect::<Vec<&str>>()
+        );
+    }
+}
+
+#[test]
+fn trailing_data_is_only_rejected_when_strict() {
+    let mut data = utils::read_bytes_from_file("hi");
+    data.extend_from_slice(b"junk");
+
+    let tolerant = class_reader::read_buffer(&data).unwrap();
+    assert_eq!(utils::read_class_from_file("hi"), tolerant);
+
+    let strict = ReaderOptions {
+        allow_trailing_data: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(ClassReaderError::InvalidClassData(
+            "4 unexpected bytes after the end of the class".to_string()
+        )),
+        class_reader::read_buffer_with_options(&data, &strict)
+    );
+    assert!(class_reader::read_buffer_with_options(&data[..data.len() - 4], &strict).is_ok());
+}
+
+#[test]
+fn preview_classes_must_match_the_current_version() {
+    let mut data = utils::read_bytes_from_file("hi");
+    // Version 61.65535, i.e. Java 17 with preview features
+    data[4..8].copy_from_slice(&[0xFF, 0xFF, 0x00, 0x3D]);
+
+    let class = class_reader::read_buffer(&data).unwrap();
+    assert!(class.requires_preview_features());
+    assert!(!utils::read_class_from_file("hi").requires_preview_features());
+
+    let options = |current_version| ReaderOptions {
+        current_version: Some(current_version),
+        strict: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        Err(ClassReaderError::UnsupportedVersion(61, 0xFFFF)),
+        class_reader::read_buffer_with_options(&data, &options(ClassFileVersion::Jdk16))
+    );
+    assert!(
+        class_reader::read_buffer_with_options(&data, &options(ClassFileVersion::Jdk17)).is_ok()
+    );
+
+    // The version is only checked in strict mode
+    let lenient = ReaderOptions {
+        strict: false,
+        ..options(ClassFileVersion::Jdk16)
+    };
+    assert!(class_reader::read_buffer_with_options(&data, &lenient).is_ok());
+}
+
+#[test]
+fn class_name_can_be_read_alone() {
+    let bytes = utils::read_bytes_from_file("hi");
+    assert_eq!(
+        Ok("Fejvm/hi".to_string()),
+        class_reader::read_name_only(&bytes)
+    );
+}
+
+#[test]
+fn constants_can_be_read_alone() {
+    let bytes = utils::read_bytes_from_file("hi");
+    let (version, constants) = class_reader::read_constants_only(&bytes).unwrap();
+    assert_eq!(ClassFileVersion::Jdk6, version);
+    assert_eq!(utils::read_class_from_file("hi").constants, constants);
+
+    // Nothing after the pool is read: the class may end right there
+    assert!(class_reader::read_constants_only(&bytes[..0x12b]).is_ok());
+    assert!(class_reader::read_buffer(&bytes[..0x12b]).is_err());
+}
+
+#[test]
+fn external_member_references_are_listed() {
+    let class = utils::read_class_from_file("hi");
+    let references = class.external_member_references();
+    assert_eq!(
+        vec![
+            MemberRef {
+                kind: MemberRefKind::Method,
+                owner: "java/lang/Object".to_string(),
+                name: "<init>".to_string(),
+                descriptor: "()V".to_string(),
+            },
+            MemberRef {
+                kind: MemberRefKind::Method,
+                owner: "java/lang/Math".to_string(),
+                name: "sqrt".to_string(),
+                descriptor: "(D)D".to_string(),
+            },
+        ],
+        references
+    );
+}
+
+#[test]
+fn all_attributes_are_visited() {
+    let class = utils::read_class_from_file("hi");
+    let locations: Vec<(&str, &str)> = class
+        .all_attributes()
+        .map(|(location, attribute)| {
+            let location = match location {
+                AttributeLocation::Class => "class",
+                AttributeLocation::Field(_) => "field",
+                AttributeLocation::Method(_) => "method",
+                AttributeLocation::Code(_) => "code",
+            };
+            (location, attribute.name.as_str())
+        })
+        .collect();
+    assert_eq!(11, locations.len());
+    assert_eq!(("class", "SourceFile"), locations[0]);
+    assert_eq!(
+        5,
+        locations
+            .iter()
+            .filter(|l| **l == ("method", "Code"))
+            .count()
+    );
+    assert_eq!(
+        5,
+        locations
+            .iter()
+            .filter(|l| **l == ("code", "LineNumberTable"))
+            .count()
+    );
+}
+
+#[test]
+fn member_byte_ranges_cover_their_definition() {
+    let class = utils::read_class_from_file("hi");
+    let data = utils::read_bytes_from_file("hi");
+
+    let (start, end) = class.fields[0].byte_range.unwrap();
+    // Flags, name index, descriptor index and an empty attribute count
+    assert_eq!(8, end - start);
+    assert_eq!([0x00, 0x12], data[start..start + 2]);
+    assert_eq!([0x00, 0x00], data[end - 2..end]);
+    assert_eq!(
+        Some(end),
+        class.fields[1].byte_range.map(|(start, _)| start)
+    );
+
+    // The methods count sits between the last field and the first method
+    let (fields_end, methods_start) = (
+        class.fields[1].byte_range.unwrap().1,
+        class.methods[0].byte_range.unwrap().0,
+    );
+    assert_eq!(fields_end + 2, methods_start);
+    let (_, methods_end) = class.methods[4].byte_range.unwrap();
+    // Followed by the class attributes: a count and a SourceFile attribute
+    assert_eq!(data.len(), methods_end + 2 + 8);
+}
+
+#[test]
+fn classes_can_be_read_from_part_of_a_larger_buffer() {
+    let class = utils::read_class_from_file("hi");
+    let mut archive = vec![0xEE; 100];
+    archive.extend(utils::read_bytes_from_file("hi"));
+    archive.extend([0xEE; 10]);
+    let end = archive.len() - 10;
+
+    let embedded =
+        class_reader::read_buffer_at(&archive[100..end], 100, &ReaderOptions::default()).unwrap();
+    let (start, field_end) = embedded.fields[0].byte_range.unwrap();
+    assert_eq!(
+        class.fields[0].byte_range,
+        Some((start - 100, field_end - 100))
+    );
+    assert_eq!([0x00, 0x12], archive[start..start + 2]);
+    for (method, original) in embedded.methods.iter().zip(&class.methods) {
+        let (start, end) = method.byte_range.unwrap();
+        assert_eq!(original.byte_range, Some((start - 100, end - 100)));
+    }
+    assert_eq!(
+        Some(end - 10),
+        embedded.methods[4].byte_range.map(|(_, end)| end)
+    );
+
+    // Apart from the byte ranges, the class is the same wherever it is read from
+    let strip = |mut class: ClassFile| {
+        class
+            .fields
+            .iter_mut()
+            .for_each(|field| field.byte_range = None);
+        class
+            .methods
+            .iter_mut()
+            .for_each(|method| method.byte_range = None);
+        class
+    };
+    assert_eq!(strip(class), strip(embedded));
+}
+
+#[test]
+fn lazy_method_bodies_match_eager_ones() {
+    let bytes = utils::read_bytes_from_file("hi");
+    let eager = class_reader::read_buffer(&bytes).unwrap();
+    let lazy = LazyClassFile::read(&bytes).unwrap();
+    assert!(lazy.class().methods.iter().all(|m| m.code.is_none()));
+    for (idx, method) in eager.methods.iter().enumerate() {
+        assert_eq!(method.code.as_ref(), lazy.method_body(idx).ok().as_ref());
+    }
+}
+
+#[test]
+fn annotated_dump_labels_the_structure() {
+    let bytes = utils::read_bytes_from_file("hi");
+    let dump = class_reader::annotated_dump(&bytes);
+    let mut lines = dump.lines();
+    assert_eq!(Some("000000..000004 magic: ca fe ba be"), lines.next());
+    assert_eq!(Some("000004..000008 version: 00 00 00 32"), lines.next());
+    assert!(dump.contains("\n00000a..00000f constant #1: 0a 00 06 00 17\n"));
+    assert!(dump.contains("\n000139..000141 field #0: 00 12 00 09 00 0a 00 00\n"));
+    assert!(!dump.contains("error"));
+
+    // Everything before the first error is still labelled
+    let dump = class_reader::annotated_dump(&bytes[..0x140]);
+    assert!(dump.contains("\n000131..000137 interfaces: 00 02 00 07 00 08\n"));
+    assert!(dump.ends_with(
+        "000139 error: invalid class file: 2 fields need at least 16 bytes, but only 7 remain\n"
+    ));
+}
+
+#[test]
+fn reserved_flag_bits_are_kept() {
+    let mut data = utils::read_bytes_from_file("hi");
+    // Set the reserved 0x0002 bit of the class and 0x8000 bit of the first method
+    data[0x12c] |= 0x02;
+    data[0x14b] |= 0x80;
+
+    let class = class_reader::read_buffer(&data).unwrap();
+    assert_eq!(
+        ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER,
+        class.flags
+    );
+    assert_eq!(0x0002, class.reserved_flags);
+    assert_eq!(0x0023, class.raw_flags());
+    assert_eq!(MethodFlags::PUBLIC, class.methods[0].flags);
+    assert_eq!(0x8001, class.methods[0].raw_flags());
+
+    let plain = utils::read_class_from_file("hi");
+    assert_eq!(0, plain.reserved_flags);
+    assert_eq!(plain.flags.bits(), plain.raw_flags());
+    assert_eq!(0x0012, plain.fields[0].raw_flags());
+}
+
+#[test]
+fn referenced_types_include_primitives() {
+    let class = utils::read_class_from_file("hi");
+    assert_eq!(vec![FieldType::Double], class.referenced_types());
+
+    // Bridges implements Comparable<Bridges>, with a bridge taking an Object
+    let class = utils::read_class_from_file("Bridges");
+    assert_eq!(
+        vec![
+            FieldType::Int,
+            FieldType::Object("Fejvm/Bridges".to_string()),
+            FieldType::Object("java/lang/Object".to_string()),
+        ],
+        class.referenced_types()
+    );
+}
+
+#[test]
+fn classes_do_not_borrow_their_bytes() {
+    fn owned<T: 'static>(value: T) -> T {
+        value
+    }
+
+    let bytes = utils::read_bytes_from_file("hi");
+    let class = owned(class_reader::read_buffer(&bytes).unwrap());
+    let lazy = owned(LazyClassFile::read(&bytes).unwrap());
+    drop(bytes);
+
+    assert_eq!("Fejvm/hi", class.name);
+    assert_eq!(Some((321, 329)), class.fields[1].byte_range);
+    assert!(lazy.method_body(0).is_ok());
+}
+
+#[test]
+fn tree_view_shows_the_containment_hierarchy() {
+    let tree = utils::read_class_from_file("hi").tree_view();
+    let fields = "\
+├── fields
+│   ├── real: D
+│   └── imag: D
+├── methods
+│   ├── <init>(D)V
+│   │   └── Code (data = 51 bytes)
+";
+    assert!(tree.starts_with("Fejvm/hi\n├── version: Jdk6\n├── flags: public\n"));
+    assert!(tree.contains(fields));
+    assert!(tree.ends_with("└── attributes\n    └── SourceFile (data = 2 bytes)\n"));
+}
+
+#[test]
+fn debug_info_is_detected() {
+    let class = utils::read_class_from_file("DebugInfo");
+    assert!(class.has_debug_info());
+    let code = class.methods[1].code.as_ref().unwrap();
+    let names: Vec<&str> = code.attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(
+        vec![
+            "LineNumberTable",
+            "LocalVariableTable",
+            "LocalVariableTypeTable"
+        ],
+        names
+    );
+
+    let class = utils::read_class_from_file("NoDebugInfo");
+    assert!(!class.has_debug_info());
+    // Only the code and the generic signature of the method are left
+    assert!(class
+        .all_attributes()
+        .all(|(_, attr)| attr.name == "Code" || attr.name == "Signature"));
+}
+
+#[test]
+fn line_numbers_do_not_make_classes_different() {
+    let original = utils::read_class_from_file("hi");
+    let mut data = utils::read_bytes_from_file("hi");
+    // Moves the first line of the first method: its LineNumberTable is the first attribute of
+    // the code, after the sizes, the code and the empty exception table
+    let method = &original.methods[0];
+    let code = method.code.as_ref().unwrap();
+    assert_eq!("LineNumberTable", code.attributes[0].name);
+    let table = method.byte_range.unwrap().0 + 8 + 6 + 8 + code.code.len() + 2 + 2;
+    data[table + 6 + 2 + 3] += 100;
+
+    let moved = class_reader::read_buffer(&data).unwrap();
+    assert_ne!(original, moved);
+    assert!(original.equivalent_ignoring_debug(&moved));
+
+    // Turns the first dadd of the last method into a dsub
+    let abs = &original.methods[4];
+    let code_start = abs.byte_range.unwrap().0 + 8 + 6 + 8;
+    let dadd = code_start
+        + abs
+            .code
+            .as_ref()
+            .unwrap()
+            .code
+            .iter()
+            .position(|&b| b == 0x63)
+            .unwrap();
+    data[dadd] = 0x67;
+    let changed = class_reader::read_buffer(&data).unwrap();
+    assert!(!original.equivalent_ignoring_debug(&changed));
+    assert!(!original.equivalent_ignoring_debug(&utils::read_class_from_file("Flow")));
+}
+
+#[test]
+fn direct_supertypes_start_with_the_superclass() {
+    let class = utils::read_class_from_file("hi");
+    assert_eq!(
+        vec![
+            "java/lang/Object",
+            "java/lang/Cloneable",
+            "java/io/Serializable"
+        ],
+        class.direct_supertypes()
+    );
+
+    // java/lang/Object itself has no superclass
+    let object = ClassFile {
+        name: "java/lang/Object".to_string(),
+        interfaces: vec!["java/io/Serializable".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(vec!["java/io/Serializable"], object.direct_supertypes());
+}
+
+#[test]
+fn recomputed_maxes_match_the_compiler() {
+    for name in ["hi", "Flow", "Calls", "Lambdas", "Annotations"] {
+        let class = utils::read_class_from_file(name);
+        for method in &class.methods {
+            let code = method.code.as_ref().unwrap();
+            assert_eq!(
+                Ok((code.max_stack, code.max_locals)),
+                code.recompute_maxes(
+                    &method.type_descriptor,
+                    method.is_static(),
+                    &class.constants
+                ),
+                "{}.{}",
+                name,
+                method.name
+            );
+        }
+    }
+}
+
+#[test]
+fn unused_constants_are_dropped() {
+    let original = utils::read_class_from_file("hi");
+    let mut class = utils::read_class_from_file("hi");
+    class
+        .constants
+        .add(ConstantPoolEntry::Utf8("unused".to_string()))
+        .unwrap();
+    class.compact_constant_pool().unwrap();
+    assert_eq!(original, class);
+
+    // Without the SourceFile attribute, its name and value are no longer used. They come before
+    // entries that other entries refer to, which are renumbered.
+    class.attributes.retain(|attr| attr.name != "SourceFile");
+    class.compact_constant_pool().unwrap();
+    assert_eq!(
+        original.constants.iter().count() - 2,
+        class.constants.iter().count()
+    );
+    assert!(!class
+        .constants
+        .iter()
+        .any(|(_, entry)| *entry == ConstantPoolEntry::Utf8("hi.java".to_string())));
+    assert!(original.equivalent_ignoring_debug(&class));
+    assert_eq!(
+        Ok("java/lang/Math.sqrt: (D)D".to_string()),
+        class.constants.text_of(4)
+    );
+
+    // Annotations cannot be rewritten yet
+    let mut annotated = utils::read_class_from_file("Annotations");
+    assert!(annotated.compact_constant_pool().is_err());
+    assert_eq!(utils::read_class_from_file("Annotations"), annotated);
+}
@@ -0,0 +1,40 @@
+package Fejvm;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+
+// Annotations on parameters and type uses, kept in the class file with both
+// CLASS and RUNTIME retention
+public class Annotations {
+    @Retention(RetentionPolicy.CLASS)
+    @Target({ElementType.PARAMETER, ElementType.TYPE_USE})
+    public @interface Hidden {
+        String value() default "";
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target({ElementType.PARAMETER, ElementType.TYPE_USE})
+    public @interface Shown {
+        int[] value() default {};
+    }
+
+    public List<@Hidden String> names;
+
+    public List<? extends @Hidden Number> numbers;
+
+    public @Hidden String @Shown [] words;
+
+    public void greet(@Hidden("name") String name, @Shown({1, 2}) int times) {
+    }
+
+    @Deprecated(since = "9", forRemoval = true)
+    public void retire() {
+    }
+
+    @Deprecated
+    public void fade() {
+    }
+}
@@ -0,0 +1,13 @@
+package Fejvm;
+
+public class Bridges implements Comparable<Bridges> {
+    private final int value;
+
+    public Bridges(int value) {
+        this.value = value;
+    }
+
+    public int compareTo(Bridges other) {
+        return Integer.compare(this.value, other.value);
+    }
+}
@@ -0,0 +1,13 @@
+package Fejvm;
+
+import java.util.List;
+
+public class Calls {
+    public int count(List<String> items) {
+        return items.size();
+    }
+
+    public String first(List<String> items) {
+        return items.isEmpty() ? "" : items.get(0).toString();
+    }
+}
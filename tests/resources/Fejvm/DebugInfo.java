@@ -0,0 +1,11 @@
+package Fejvm;
+
+import java.util.List;
+
+// Compiled with -g, so that the local variables are described as well as the lines
+public class DebugInfo {
+    public static int firstLength(List<String> names) {
+        String name = names.get(0);
+        return name.length();
+    }
+}
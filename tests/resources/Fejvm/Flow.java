@@ -0,0 +1,31 @@
+package Fejvm;
+
+import java.io.IOException;
+import java.io.Reader;
+
+public class Flow {
+    public static int abs(int x) {
+        if (x < 0) {
+            x = -x;
+        }
+        return x;
+    }
+
+    public static int parse(String s) {
+        try {
+            return Integer.parseInt(s);
+        } catch (NumberFormatException e) {
+            return -1;
+        }
+    }
+
+    public static int read(Reader reader) {
+        try {
+            return reader.read();
+        } catch (IOException e) {
+            return -1;
+        } finally {
+            reader = null;
+        }
+    }
+}
@@ -0,0 +1,54 @@
+package Fejvm;
+
+import kotlin.Metadata;
+
+// The class that kotlinc 1.9 compiles from
+//
+//     class KotlinPoint(val x: Int, val y: Int) {
+//         companion object {
+//             fun origin() = KotlinPoint(0, 0)
+//         }
+//     }
+//
+// written out in Java, as kotlinc is not part of the fixture toolchain. The metadata strings hold
+// protobuf messages, so they are full of control characters and NULs.
+@Metadata(
+        mv = {1, 9, 0},
+        k = 1,
+        xi = 48,
+        d1 = {"\0\u0014\n\u0002\u0018\u0002\n\u0002\u0010\0\n\0\n\u0002\u0010\b\n\u0002\b\u0007\u0018\0 \t2\u00020\u0001:\u0001\tB\u0015\u0012\u0006\u0010\u0002\u001a\u00020\u0003\u0012\u0006\u0010\u0004\u001a\u00020\u0003\u00a2\u0006\u0002\u0010\u0005"},
+        d2 = {"LFejvm/KotlinPoint;", "", "x", "", "y", "(II)V", "getX", "()I", "getY", "Companion"})
+public final class KotlinPoint {
+    public static final Companion Companion = new Companion();
+
+    private final int x;
+    private final int y;
+
+    public KotlinPoint(int x, int y) {
+        this.x = x;
+        this.y = y;
+    }
+
+    public final int getX() {
+        return x;
+    }
+
+    public final int getY() {
+        return y;
+    }
+
+    @Metadata(
+            mv = {1, 9, 0},
+            k = 1,
+            xi = 48,
+            d1 = {"\0\u0012\n\u0002\u0018\u0002\n\u0002\u0010\0\n\u0002\b\u0002\n\u0002\u0018\u0002\n\0\b\u0086\u0003\u0018\u00002\u00020\u0001"},
+            d2 = {"LFejvm/KotlinPoint$Companion;", "", "()V", "origin", "LFejvm/KotlinPoint;"})
+    public static final class Companion {
+        private Companion() {
+        }
+
+        public final KotlinPoint origin() {
+            return new KotlinPoint(0, 0);
+        }
+    }
+}
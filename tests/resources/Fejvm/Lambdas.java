@@ -0,0 +1,23 @@
+package Fejvm;
+
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.util.function.Supplier;
+
+// Uses the features that Kotlin and Scala compilers lean on: invokedynamic
+// lambdas, nested classes and runtime visible metadata annotations
+@Lambdas.Metadata(version = 2)
+public class Lambdas {
+    @Retention(RetentionPolicy.RUNTIME)
+    public @interface Metadata {
+        int version();
+    }
+
+    public static class Box {
+        public String value;
+    }
+
+    public static Supplier<String> greeter(String name) {
+        return () -> "Hello, " + name;
+    }
+}
@@ -0,0 +1,13 @@
+package Fejvm;
+
+// Nested classes with the access flags that only nested classes can have
+public class Nested {
+    private static class Secret {
+    }
+
+    protected abstract class Inner {
+    }
+
+    public static final class Open {
+    }
+}
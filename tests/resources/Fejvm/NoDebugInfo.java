@@ -0,0 +1,11 @@
+package Fejvm;
+
+import java.util.List;
+
+// Compiled with -g:none, which leaves out every debug attribute
+public class NoDebugInfo {
+    public static int firstLength(List<String> names) {
+        String name = names.get(0);
+        return name.length();
+    }
+}
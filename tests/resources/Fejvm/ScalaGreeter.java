@@ -0,0 +1,19 @@
+package Fejvm;
+
+import scala.reflect.ScalaSignature;
+
+// The mirror class that scalac 2.13 compiles from
+//
+//     object ScalaGreeter {
+//         def greet(name: String): String = s"Hello, $name"
+//     }
+//
+// written out in Java, as scalac is not part of the fixture toolchain. The static forwarder
+// calls the module class, and the pickled signature encodes 0 as the overlong NUL of modified
+// UTF-8.
+@ScalaSignature(bytes = "\u0006\u0005\u0015;Q\0\0\u0004\u0005\u00021\u0001\0\u0004\u0005\u0002\u0016\u0011\0\u000e\0\u0017\u0005\u0005\u0010\u0001\u00192\u0006\u0001")
+public final class ScalaGreeter {
+    public static String greet(String name) {
+        return ScalaGreeter$.MODULE$.greet(name);
+    }
+}
@@ -0,0 +1,3 @@
+// Package annotations, which javac stores on a synthetic package-info interface
+@Deprecated(since = "1.0")
+package Fejvm;
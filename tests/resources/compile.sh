@@ -1,2 +1,10 @@
 #!/usr/bin/env sh
-javac -source 6 -target 6 Fejvm/*.java
\ No newline at end of file
+# hi and Constants are kept as they were compiled for Java 6, which javac no longer targets, so
+# that the old class file layout stays covered. Their sources are not compiled again.
+# The stubs declare what the Kotlin and Scala stand-ins refer to, and are not compiled themselves
+javac --release 17 -sourcepath .:stubs -implicit:none $(ls Fejvm/*.java | grep -v -e '/hi.java' -e '/Constants.java' -e 'DebugInfo.java') module-info.java && rm -f 'Fejvm/'*'$'*.class
+# The same class with and without debug information
+javac --release 17 -g Fejvm/DebugInfo.java
+javac --release 17 -g:none Fejvm/NoDebugInfo.java
+# A separate tree for listing classes by their path, which does not grow with the fixtures above
+javac --release 17 listing/Root.java listing/sample/Leaf.java
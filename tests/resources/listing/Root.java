@@ -0,0 +1,3 @@
+// A class in the unnamed package, listed under its simple name
+public class Root {
+}
@@ -0,0 +1,5 @@
+package sample;
+
+// A class in a package, listed under the directories it is found in
+public class Leaf {
+}
@@ -0,0 +1,5 @@
+// A module descriptor, compiled along with the package it exports
+module fejvm.fixtures {
+    requires java.logging;
+    exports Fejvm;
+}
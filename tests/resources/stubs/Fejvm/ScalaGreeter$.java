@@ -0,0 +1,13 @@
+package Fejvm;
+
+// The module class of the ScalaGreeter object, which holds its single instance
+public final class ScalaGreeter$ {
+    public static final ScalaGreeter$ MODULE$ = new ScalaGreeter$();
+
+    private ScalaGreeter$() {
+    }
+
+    public String greet(String name) {
+        return "Hello, " + name;
+    }
+}
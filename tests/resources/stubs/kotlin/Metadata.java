@@ -0,0 +1,26 @@
+package kotlin;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+
+// The annotation that kotlinc puts on every class it compiles, as declared by the Kotlin
+// standard library
+@Retention(RetentionPolicy.RUNTIME)
+@Target(ElementType.TYPE)
+public @interface Metadata {
+    int k() default 1;
+
+    int[] mv() default {};
+
+    String[] d1() default {};
+
+    String[] d2() default {};
+
+    String xs() default "";
+
+    String pn() default "";
+
+    int xi() default 0;
+}
@@ -0,0 +1,39 @@
+extern crate Fejvm;
+
+use Fejvm::class_reader;
+
+mod utils;
+
+#[test]
+fn truncated_classes_do_not_panic() {
+    let data = utils::read_bytes_from_file("hi");
+    for len in 0..data.len() {
+        // Any outcome is fine, as long as it is not a panic
+        let _ = class_reader::read_buffer(&data[..len]);
+    }
+}
+
+#[test]
+fn corrupted_classes_do_not_panic() {
+    let data = utils::read_bytes_from_file("hi");
+
+    // Simple LCG so that the mutations are reproducible without extra dependencies
+    let mut seed: u32 = 0x2023;
+    let mut next = || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        seed >> 8
+    };
+
+    for _ in 0..5000 {
+        let mut mutated = data.clone();
+        for _ in 0..(1 + next() % 4) {
+            let pos = next() as usize % mutated.len();
+            mutated[pos] = next() as u8;
+        }
+        let len = next() as usize % (mutated.len() + 1);
+
+        // Any outcome is fine, as long as it is not a panic
+        let _ = class_reader::read_buffer(&mutated[..len]);
+        let _ = class_reader::read_buffer(&mutated);
+    }
+}
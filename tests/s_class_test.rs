@@ -0,0 +1,94 @@
+extern crate Fejvm;
+
+use Fejvm::annotation::ElementValue;
+use Fejvm::attribute::Attribute;
+use Fejvm::class_reader;
+use Fejvm::member_ref::{MemberRef, MemberRefKind};
+
+mod utils;
+
+#[test]
+fn scala_signature_is_read() {
+    let class = utils::read_class_from_file("ScalaGreeter");
+    assert_eq!("Fejvm/ScalaGreeter", class.name);
+
+    let signature = &class.visible_annotations[0];
+    assert_eq!("Lscala/reflect/ScalaSignature;", signature.type_descriptor);
+    match &signature.elements[..] {
+        [(name, ElementValue::String(bytes))] => {
+            assert_eq!("bytes", name);
+            assert_eq!(5, bytes.matches('\0').count());
+        }
+        elements => panic!("unexpected elements {:?}", elements),
+    }
+}
+
+#[test]
+fn scala_static_forwarder_calls_the_module() {
+    let class = utils::read_class_from_file("ScalaGreeter");
+    let references = class.external_member_references();
+    assert!(references.contains(&MemberRef {
+        kind: MemberRefKind::Field,
+        owner: "Fejvm/ScalaGreeter$".to_string(),
+        name: "MODULE$".to_string(),
+        descriptor: "LFejvm/ScalaGreeter$;".to_string(),
+    }));
+}
+
+// scalac also gives its classes a ScalaSig attribute, which only holds the version of the pickle
+// when the pickle is in the annotation, and a ScalaInlineInfo attribute listing the methods it
+// may inline. Neither is known to the reader, so they are kept as they are.
+#[test]
+fn scala_attributes_are_kept_as_they_are() {
+    let mut data = vec![0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x34, 0x00, 0x09];
+    for (tag, entry) in [
+        (1, &b"Fejvm/ScalaGreeter"[..]),
+        (7, &[0x00, 0x01][..]),
+        (1, &b"java/lang/Object"[..]),
+        (7, &[0x00, 0x03][..]),
+        (1, &b"ScalaSig"[..]),
+        (1, &b"ScalaInlineInfo"[..]),
+        (1, &b"greet"[..]),
+        (1, &b"(Ljava/lang/String;)Ljava/lang/String;"[..]),
+    ] {
+        data.push(tag);
+        if tag == 1 {
+            data.extend_from_slice(&(entry.len() as u16).to_be_bytes());
+        }
+        data.extend_from_slice(entry);
+    }
+    let scala_sig = [0x05, 0x00, 0x00]; // version 5.0, no entries
+    let inline_info = [
+        0x01, // version
+        0x00, // not a final class
+        0x00, 0x01, // 1 method
+        0x00, 0x07, 0x00, 0x08, 0x00, // greet, not inlined
+    ];
+    data.extend([
+        0x00, 0x31, 0x00, 0x02, 0x00, 0x04, // public final super, extends java/lang/Object
+        0x00, 0x00, // interfaces
+        0x00, 0x00, // fields
+        0x00, 0x00, // methods
+        0x00, 0x02, // attributes
+        0x00, 0x05, 0x00, 0x00, 0x00, 0x03,
+    ]);
+    data.extend(scala_sig);
+    data.extend([0x00, 0x06, 0x00, 0x00, 0x00, 0x09]);
+    data.extend(inline_info);
+
+    let class = class_reader::read_buffer(&data).unwrap();
+    assert_eq!("Fejvm/ScalaGreeter", class.name);
+    assert_eq!(
+        vec![
+            Attribute {
+                name: "ScalaSig".to_string(),
+                info: scala_sig.to_vec(),
+            },
+            Attribute {
+                name: "ScalaInlineInfo".to_string(),
+                info: inline_info.to_vec(),
+            },
+        ],
+        class.attributes
+    );
+}
@@ -1,12 +1,31 @@
+// Not every test crate uses every helper
+#![allow(dead_code)]
+
 use std::path::PathBuf;
 use Fejvm::class_file::ClassFile;
 use Fejvm::class_reader;
 
-pub fn read_class_from_file(file: &str) -> ClassFile {
+fn class_path(file: &str) -> PathBuf {
     let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     path.push("tests/resources/Fejvm");
     path.push(String::from(file) + ".class");
+    path
+}
+
+pub fn resources_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests/resources");
+    path
+}
+
+pub fn read_class_from_file(file: &str) -> ClassFile {
+    let path = class_path(file);
     println!("Reading class from file: {}", path.display());
 
-    class_reader::read(path.as_path()).unwrap()
+    // Read from memory, so that the tests also run without the std feature
+    class_reader::read_buffer(&std::fs::read(path).unwrap()).unwrap()
+}
+
+pub fn read_bytes_from_file(file: &str) -> Vec<u8> {
+    std::fs::read(class_path(file)).unwrap()
 }